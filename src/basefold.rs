@@ -1,14 +1,22 @@
-use std::fmt::Display;
+use std::fmt::{Display, Write};
 
 use crate::{
-    errors::SecurityAssumption,
+    errors::{ParameterError, SecurityAssumption, DEFAULT_ETA_FACTOR},
+    hash::DigestConfig,
     protocol::{
         builder::ProtocolBuilder,
-        proof_size::{FieldElements, MerkleQueries, MerkleTree, ProofElement},
-        Protocol, ProverMessage, RbRError, VerifierMessage,
+        proof_size::{
+            merkle_digest_bits, Encoding, FieldElements, FinalPhase, InterleavingMode,
+            MerkleQueries, MerkleTree, ProofElement,
+        },
+        total_oracle_bits, Protocol, ProtocolReport, ProverMessage, RbRError, Report,
+        VerifierMessage,
     },
-    utils::{pow_util, pretty_print_float_slice},
-    LowDegreeParameters,
+    utils::{
+        float_precision, pow_util, pretty_print_float_slice, print_final_poly_warning,
+        print_pow_warning, ROUNDS_CSV_HEADER,
+    },
+    LowDegreeParameters, LowDegreeTest,
 };
 
 /// Parameters parametrizing an instance of Basefold.
@@ -24,24 +32,108 @@ pub struct BasefoldParameters {
 
     /// The folding factors in the remaining rounds.
     /// Given in log form, i.e. folding_factors[i] = 2 implies that the degree in round i is reduced by a factor of 4.
+    /// If empty, only the starting fold is done, and with a starting folding factor of 0 the polynomial is sent whole.
     pub folding_factors: Vec<usize>,
 
     /// The security assumption under which to configure Basefold.
     pub security_assumption: SecurityAssumption,
 
+    /// Optionally, the divisor used to set η (see `SecurityAssumption::log_eta_with_factor`), defaults to `DEFAULT_ETA_FACTOR`.
+    pub eta_factor: Option<f64>,
+
+    /// Optionally, a cap on the (log) list size, see `SecurityAssumptionWithEta::with_max_list_size_bits`.
+    pub max_list_size_bits: Option<f64>,
+
     /// The security level desired.
     pub security_level: usize,
 
     /// The number of pow bits to use to reduce query error.
     /// Traditionally called also "grinding".
-    /// NOTE: This does not affect the pow bits used to reduce proximity gaps errors.
+    /// NOTE: This does not bound the pow bits used to reduce proximity gaps errors, see `folding_pow_bits`.
     pub pow_bits: usize,
 
-    /// The size of the digest for the Merkle tree
-    pub digest_size_bits: usize,
+    /// Optionally, the maximum number of pow bits used to reduce proximity gaps (folding and batching) errors.
+    /// If unset, these are ground up to the security level.
+    pub folding_pow_bits: Option<usize>,
+
+    /// Optionally, a hard cap on the pow bits of any grind (e.g. the most that is practical on the prover's hardware).
+    /// Unlike `pow_bits`, the queries are not increased to compensate, and so the protocol may fall short of
+    /// `security_level` (see `security_shortfall` in the config).
+    pub grinding_cap: Option<usize>,
+
+    /// The size of the digest for the Merkle tree. If `None`, digests are sized for the security level
+    /// (see `digest_size_bits_for_security_level`).
+    pub digest_size_bits: Option<usize>,
+
+    /// Optionally, the hash used for the Merkle digests, overriding `digest_size_bits` with its output size
+    /// and bounding the collision resistance of the digests.
+    pub digest: Option<DigestConfig>,
+
+    /// Whether grinding is forbidden, in which case no PoW is used and `pow_bits` is ignored.
+    pub no_grinding: bool,
+
+    /// Whether the commitments are zero-knowledge, in which case the Merkle leaves are salted.
+    pub zero_knowledge: bool,
+
+    /// Whether the statement is over the base field, see `WhirParameters::base_field_statement`.
+    pub base_field_statement: bool,
+
+    /// How field elements are serialized in the proof.
+    pub encoding: Encoding,
+
+    /// How the batched polynomials are committed to in the starting commitment, see `InterleavingMode`.
+    pub interleaving: InterleavingMode,
+
+    /// How the final polynomial is checked, see `FinalPhase`.
+    pub final_phase: FinalPhase,
+
+    /// The smallest (log) degree the final polynomial may have, guarding against folding all the way down by accident.
+    pub min_final_log_degree: usize,
+
+    /// Whether the starting commitment is over the extension field (e.g. when the witness is already extension-valued),
+    /// rather than over the base field.
+    pub commit_over_extension: bool,
 }
 
 impl BasefoldParameters {
+    /// Checks the parameters against the LDT parameters, reporting every violation rather than only the first.
+    /// These are the checks done when constructing the protocol, see `BasefoldProtocol::new`.
+    pub fn validate(
+        &self,
+        ldt_parameters: &LowDegreeParameters,
+    ) -> Result<(), Vec<ParameterError>> {
+        let mut errors = vec![];
+        errors.extend(ldt_parameters.validate().err());
+
+        // Each fold needs to reduce the degree, only without rounds may the starting fold be skipped
+        if (self.starting_folding_factor == 0 && !self.folding_factors.is_empty())
+            || self.folding_factors.contains(&0)
+        {
+            errors.push(ParameterError::ZeroFoldingFactor);
+        }
+
+        // We cannot fold too much
+        let total_reduction =
+            self.starting_folding_factor + self.folding_factors.iter().sum::<usize>();
+        if total_reduction > ldt_parameters.log_degree {
+            errors.push(ParameterError::OverFolded {
+                total_reduction,
+                log_degree: ldt_parameters.log_degree,
+            });
+        } else if ldt_parameters.log_degree - total_reduction < self.min_final_log_degree {
+            errors.push(ParameterError::FinalDegreeTooSmall {
+                final_log_degree: ldt_parameters.log_degree - total_reduction,
+                min_final_log_degree: self.min_final_log_degree,
+            });
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
     /// Instantiate a Basefold configuration where each round does a fixed amount of folding.
     pub fn fixed_folding(
         log_inv_rate: usize,
@@ -57,9 +149,22 @@ impl BasefoldParameters {
             starting_folding_factor: folding_factor,
             folding_factors: vec![folding_factor; num_rounds],
             security_assumption,
+            eta_factor: None,
+            max_list_size_bits: None,
             security_level,
             pow_bits,
-            digest_size_bits,
+            digest_size_bits: Some(digest_size_bits),
+            digest: None,
+            no_grinding: false,
+            folding_pow_bits: None,
+            grinding_cap: None,
+            zero_knowledge: false,
+            base_field_statement: false,
+            encoding: Encoding::default(),
+            interleaving: InterleavingMode::default(),
+            final_phase: FinalPhase::default(),
+            min_final_log_degree: 0,
+            commit_over_extension: false,
         }
     }
 }
@@ -73,119 +178,220 @@ pub struct BasefoldProtocol {
 
 impl BasefoldProtocol {
     /// Given a LDT parameter and some parameters for Basefold, populate the config.
+    /// Without any folds (i.e. no rounds and no starting fold), this is the trivial LDT: the prover sends the whole
+    /// polynomial, which the verifier checks against the committed evaluations at the queried points.
     pub fn new(
         ldt_parameters: LowDegreeParameters,
         basefold_parameters: BasefoldParameters,
-    ) -> Self {
-        // We need to fold at least some time
-        assert!(
-            basefold_parameters.starting_folding_factor > 0
-                && basefold_parameters.folding_factors.iter().all(|&x| x > 0),
-            "folding factors should be non zero"
-        );
-
-        // We cannot fold too much
-        let total_reduction = basefold_parameters.starting_folding_factor
-            + basefold_parameters.folding_factors.iter().sum::<usize>();
-        assert!(total_reduction <= ldt_parameters.log_degree);
-
-        // If less, just send the damn polynomials
-        assert!(ldt_parameters.log_degree >= basefold_parameters.folding_factors[0]);
+    ) -> Result<Self, ParameterError> {
+        // Check the parameters, reporting the first violation
+        if let Err(errors) = basefold_parameters.validate(&ldt_parameters) {
+            return Err(errors[0]);
+        }
+        let final_log_degree = ldt_parameters.log_degree
+            - basefold_parameters.starting_folding_factor
+            - basefold_parameters.folding_factors.iter().sum::<usize>();
 
-        // Compute the number of rounds and the leftover
-        let final_log_degree = ldt_parameters.log_degree - total_reduction;
+        // Compute the number of rounds
         let num_rounds = basefold_parameters.folding_factors.len();
 
         // Compute the security level
         let security_level = basefold_parameters.security_level;
-        let protocol_security_level =
-            0.max(basefold_parameters.security_level - basefold_parameters.pow_bits);
+        // Without grinding, the queries alone reach the security level and no PoW is used anywhere
+        let max_pow_bits = if basefold_parameters.no_grinding {
+            0
+        } else {
+            basefold_parameters.pow_bits
+        };
+        let protocol_security_level = security_level.saturating_sub(max_pow_bits);
+        let pow_security_level = if basefold_parameters.no_grinding {
+            0
+        } else {
+            security_level
+        };
+        // The PoW for the proximity gaps errors is capped separately from the one for the queries
+        let folding_pow_cap = basefold_parameters
+            .folding_pow_bits
+            .map_or(f64::INFINITY, |bits| bits as f64);
+        let grinding_cap = basefold_parameters.grinding_cap;
+        let folding_pow_util =
+            |error: f64| pow_util(pow_security_level, error, grinding_cap).min(folding_pow_cap);
+
+        // The security assumption (and choice of η) used to compute the errors
+        let eta_factor = basefold_parameters.eta_factor.unwrap_or(DEFAULT_ETA_FACTOR);
+        let security_assumption = basefold_parameters
+            .security_assumption
+            .with_eta_factor(eta_factor)
+            .with_list_size_cap(basefold_parameters.max_list_size_bits);
 
         // Initial domain size (the trace domain)
         let starting_folding_factor = basefold_parameters.starting_folding_factor;
         let starting_domain_log_size =
             ldt_parameters.log_degree + basefold_parameters.starting_log_inv_rate;
 
-        let mut protocol_builder =
-            ProtocolBuilder::new("Basefold protocol", basefold_parameters.digest_size_bits);
+        let (digest_size_bits, collision_bits) = merkle_digest_bits(
+            basefold_parameters.digest,
+            basefold_parameters.digest_size_bits,
+            security_level,
+        );
+
+        // In zero-knowledge mode, each leaf is salted with randomness
+        let salt_bits = if basefold_parameters.zero_knowledge {
+            2 * security_level
+        } else {
+            0
+        };
+
+        // How field elements are serialized in the proof
+        let encoding = basefold_parameters.encoding;
+        let interleaving = basefold_parameters.interleaving;
+        let final_phase = basefold_parameters.final_phase;
+
+        // Each verifier challenge (other than the query indices) is an extension field element
+        let challenge_bits = ldt_parameters.field.extension_bit_size();
+
+        let mut protocol_builder = ProtocolBuilder::new("Basefold protocol", digest_size_bits)
+            .with_collision_bits(collision_bits);
 
         // Pow bits for the batching steps
         let mut batching_pow_bits = 0.;
         if ldt_parameters.batch_size > 1 {
             // We can't really batch non linear constraints
-            assert!(ldt_parameters.constraint_degree <= 2);
-            let prox_gaps_error_batching = basefold_parameters.security_assumption.prox_gaps_error(
+            if ldt_parameters.constraint_degree > 2 {
+                return Err(ParameterError::UnsupportedConstraintDegree {
+                    constraint_degree: ldt_parameters.constraint_degree,
+                });
+            }
+            let prox_gaps_error_batching = security_assumption.prox_gaps_error(
                 ldt_parameters.log_degree,
                 basefold_parameters.starting_log_inv_rate,
                 ldt_parameters.field.extension_bit_size(),
                 ldt_parameters.batch_size,
             ); // we now start, the initial folding pow bits
-            batching_pow_bits = pow_util(security_level, prox_gaps_error_batching);
+            batching_pow_bits = folding_pow_util(prox_gaps_error_batching);
 
-            // Add the round for the batching
+            // Add the round for the batching.
+            // NOTE: No commitment to the combined codeword is needed, as the starting tree stores the evaluations
+            // of all the batched functions in each leaf, from which the verifier derives the combination.
             protocol_builder = protocol_builder
                 .start_round("batching_round")
-                .verifier_message(VerifierMessage::new(
-                    vec![RbRError::new("batching_error", prox_gaps_error_batching)],
-                    batching_pow_bits,
-                ))
+                .verifier_message(
+                    VerifierMessage::new(
+                        vec![RbRError::new("batching_error", prox_gaps_error_batching)],
+                        batching_pow_bits,
+                    )
+                    .with_challenge_bits(challenge_bits),
+                )
                 .end_round();
         }
 
         // Merkle tree committed to
-        let starting_merkle_tree = MerkleTree::new(
+        let starting_merkle_tree = MerkleTree::with_digest_size(
             starting_domain_log_size - starting_folding_factor,
             ldt_parameters.field,
-            (1 << starting_folding_factor) * ldt_parameters.batch_size,
-            false, // first tree is over the base
-        );
+            (1 << starting_folding_factor) * interleaving.polys_per_tree(ldt_parameters.batch_size),
+            basefold_parameters.commit_over_extension, // first tree is over the base, unless told otherwise
+            digest_size_bits,
+        )
+        .with_salt_bits(salt_bits)
+        .with_encoding(encoding);
         let mut commitments = vec![starting_merkle_tree];
 
         // Degree of next polynomial to send
         let mut current_log_degree = ldt_parameters.log_degree;
         let mut starting_folding_pow_bits_vec = Vec::with_capacity(starting_folding_factor);
-        protocol_builder = protocol_builder.start_round("initial_iteration");
-        for _ in 0..starting_folding_factor {
+        // Without a starting fold (only allowed without rounds) or claims, the polynomial is just sent whole
+        let has_starting_round =
+            starting_folding_factor > 0 || ldt_parameters.constraint_degree > 0;
+        if has_starting_round {
+            protocol_builder = protocol_builder.start_round("initial_iteration");
+        }
+
+        // As a PCS, the prover starts by sending the claimed value of each constraint for each committed polynomial
+        if ldt_parameters.constraint_degree > 0 {
+            protocol_builder =
+                protocol_builder.prover_message(ProverMessage::new(ProofElement::FieldElements(
+                    FieldElements::extension(
+                        ldt_parameters.field,
+                        ldt_parameters.batch_size * ldt_parameters.num_constraints,
+                    )
+                    .with_encoding(encoding),
+                )));
+
+            // Several constraints are combined into one (of the maximum degree) with verifier randomness
+            if ldt_parameters.num_constraints > 1 {
+                let constraint_batching_error = security_assumption.constraint_folding_error(
+                    ldt_parameters.log_degree,
+                    basefold_parameters.starting_log_inv_rate,
+                    ldt_parameters.field.extension_bit_size(),
+                    ldt_parameters.num_constraints,
+                );
+                protocol_builder = protocol_builder.verifier_message(
+                    VerifierMessage::new(
+                        vec![RbRError::new(
+                            "constraint_batching_error",
+                            constraint_batching_error,
+                        )],
+                        folding_pow_util(constraint_batching_error),
+                    )
+                    .with_challenge_bits(challenge_bits),
+                );
+            }
+        }
+
+        // See `WhirParameters::base_field_statement`, the polynomial must also be over the base field
+        let base_field_sumcheck = basefold_parameters.base_field_statement
+            && ldt_parameters.batch_size == 1
+            && (ldt_parameters.constraint_degree == 0 || ldt_parameters.num_constraints == 1)
+            && !basefold_parameters.commit_over_extension;
+
+        for i in 0..starting_folding_factor {
             // we now start, the initial folding pow bits
-            let prox_gaps_error = basefold_parameters.security_assumption.prox_gaps_error(
+            let prox_gaps_error = security_assumption.prox_gaps_error(
                 current_log_degree - 1,
                 basefold_parameters.starting_log_inv_rate,
                 ldt_parameters.field.extension_bit_size(),
                 2,
             );
 
-            let sumcheck_error = basefold_parameters
-                .security_assumption
-                .constraint_folding_error(
-                    current_log_degree,
-                    basefold_parameters.starting_log_inv_rate,
-                    ldt_parameters.field.extension_bit_size(),
-                    ldt_parameters.constraint_degree,
-                );
+            let sumcheck_error = security_assumption.constraint_folding_error(
+                current_log_degree,
+                basefold_parameters.starting_log_inv_rate,
+                ldt_parameters.field.extension_bit_size(),
+                ldt_parameters.constraint_degree,
+            );
+
+            let starting_folding_pow_bits = folding_pow_util(prox_gaps_error.min(sumcheck_error));
 
-            let starting_folding_pow_bits =
-                pow_util(security_level, prox_gaps_error.min(sumcheck_error));
+            let sumcheck_polynomial = if i == 0 && base_field_sumcheck {
+                FieldElements::base(ldt_parameters.field, ldt_parameters.constraint_degree + 1)
+                    .with_encoding(encoding)
+            } else {
+                FieldElements::extension(ldt_parameters.field, ldt_parameters.constraint_degree + 1)
+                    .with_encoding(encoding)
+            };
 
             protocol_builder = protocol_builder
                 .prover_message(ProverMessage::new(ProofElement::FieldElements(
-                    FieldElements {
-                        field: ldt_parameters.field,
-                        num_elements: ldt_parameters.constraint_degree + 1,
-                        is_extension: true,
-                    },
+                    sumcheck_polynomial,
                 )))
-                .verifier_message(VerifierMessage::new(
-                    vec![
-                        RbRError::new("folding_error", prox_gaps_error),
-                        RbRError::new("sumcheck_error", sumcheck_error),
-                    ],
-                    starting_folding_pow_bits,
-                ));
+                .verifier_message(
+                    VerifierMessage::new(
+                        vec![
+                            RbRError::new("folding_error", prox_gaps_error),
+                            RbRError::new("sumcheck_error", sumcheck_error),
+                        ],
+                        starting_folding_pow_bits,
+                    )
+                    .with_challenge_bits(challenge_bits),
+                );
 
             starting_folding_pow_bits_vec.push(starting_folding_pow_bits);
             current_log_degree -= 1;
         }
-        protocol_builder = protocol_builder.end_round();
+        if has_starting_round {
+            protocol_builder = protocol_builder.end_round();
+        }
 
         let mut round_parameters = Vec::with_capacity(num_rounds);
 
@@ -194,12 +400,15 @@ impl BasefoldProtocol {
                 current_log_degree + basefold_parameters.starting_log_inv_rate;
 
             // Send the new oracle
-            let current_merkle_tree = MerkleTree::new(
+            let current_merkle_tree = MerkleTree::with_digest_size(
                 new_evaluation_domain_size - folding_factor,
                 ldt_parameters.field,
                 1 << folding_factor,
                 true,
-            );
+                digest_size_bits,
+            )
+            .with_salt_bits(salt_bits)
+            .with_encoding(encoding);
             protocol_builder = protocol_builder
                 .start_round("basefold_iteration")
                 .prover_message(ProverMessage::new(ProofElement::MerkleRoot(
@@ -210,40 +419,41 @@ impl BasefoldProtocol {
             let mut pow_bits_vec = Vec::with_capacity(folding_factor);
             for _ in 0..folding_factor {
                 // we now start, the initial folding pow bits
-                let prox_gaps_error = basefold_parameters.security_assumption.prox_gaps_error(
+                let prox_gaps_error = security_assumption.prox_gaps_error(
                     current_log_degree - 1,
                     basefold_parameters.starting_log_inv_rate,
                     ldt_parameters.field.extension_bit_size(),
                     2,
                 );
 
-                let sumcheck_error = basefold_parameters
-                    .security_assumption
-                    .constraint_folding_error(
-                        current_log_degree,
-                        basefold_parameters.starting_log_inv_rate,
-                        ldt_parameters.field.extension_bit_size(),
-                        ldt_parameters.constraint_degree,
-                    );
+                let sumcheck_error = security_assumption.constraint_folding_error(
+                    current_log_degree,
+                    basefold_parameters.starting_log_inv_rate,
+                    ldt_parameters.field.extension_bit_size(),
+                    ldt_parameters.constraint_degree,
+                );
 
                 let starting_folding_pow_bits =
-                    pow_util(security_level, prox_gaps_error.min(sumcheck_error));
+                    folding_pow_util(prox_gaps_error.min(sumcheck_error));
 
                 protocol_builder = protocol_builder
                     .prover_message(ProverMessage::new(ProofElement::FieldElements(
-                        FieldElements {
-                            field: ldt_parameters.field,
-                            num_elements: ldt_parameters.constraint_degree + 1,
-                            is_extension: true,
-                        },
+                        FieldElements::extension(
+                            ldt_parameters.field,
+                            ldt_parameters.constraint_degree + 1,
+                        )
+                        .with_encoding(encoding),
                     )))
-                    .verifier_message(VerifierMessage::new(
-                        vec![
-                            RbRError::new("folding_error", prox_gaps_error),
-                            RbRError::new("sumcheck_error", sumcheck_error),
-                        ],
-                        starting_folding_pow_bits,
-                    ));
+                    .verifier_message(
+                        VerifierMessage::new(
+                            vec![
+                                RbRError::new("folding_error", prox_gaps_error),
+                                RbRError::new("sumcheck_error", sumcheck_error),
+                            ],
+                            starting_folding_pow_bits,
+                        )
+                        .with_challenge_bits(challenge_bits),
+                    );
 
                 pow_bits_vec.push(starting_folding_pow_bits);
                 current_log_degree -= 1;
@@ -258,74 +468,156 @@ impl BasefoldProtocol {
             round_parameters.push(round_config);
         }
 
+        // The queries are indices into the starting tree, from which the indices into the later trees are derived
+        let query_index_bits = commitments[0].tree_depth;
+
         // Compute the number of queries required
-        let final_queries = basefold_parameters.security_assumption.queries(
+        let final_queries = security_assumption.queries(
             protocol_security_level,
             basefold_parameters.starting_log_inv_rate,
-        );
+        )?;
 
-        // We need to compute the errors, to compute the according PoW
-        let query_error = basefold_parameters
-            .security_assumption
-            .queries_error(basefold_parameters.starting_log_inv_rate, final_queries);
+        // We need to compute the errors, to compute the according PoW.
+        let query_error = security_assumption
+            .queries_error(basefold_parameters.starting_log_inv_rate, final_queries)?;
 
         // Now compute the PoW
-        let final_pow_bits = pow_util(security_level, query_error);
+        let final_pow_bits = pow_util(pow_security_level, query_error, grinding_cap);
 
         protocol_builder = protocol_builder
             .start_round("query_round")
-            .verifier_message(VerifierMessage::new(
-                vec![RbRError::new("query_error", query_error)],
-                final_pow_bits,
-            ))
-            .prover_message(ProverMessage::new(ProofElement::FieldElements(
-                FieldElements {
-                    field: ldt_parameters.field,
-                    num_elements: 1 << final_log_degree,
-                    is_extension: true,
-                },
-            )));
-
-        for current_merkle_tree in commitments {
-            // The queries
-            protocol_builder = protocol_builder.prover_message(ProverMessage::new(
-                ProofElement::MerkleQueries(MerkleQueries {
-                    merkle_tree: current_merkle_tree,
-                    num_openings: final_queries,
-                }),
-            ));
+            .verifier_message(
+                VerifierMessage::new(
+                    vec![RbRError::new("query_error", query_error)],
+                    final_pow_bits,
+                )
+                .with_challenge_bits(final_queries * query_index_bits),
+            );
+        // The final sumcheck has the same degree, and hence error, as the folding sumchecks
+        protocol_builder = protocol_builder.final_phase(
+            final_phase,
+            ldt_parameters.field,
+            encoding,
+            final_log_degree,
+            ldt_parameters.constraint_degree,
+            |log_degree| {
+                let sumcheck_error = security_assumption.constraint_folding_error(
+                    log_degree,
+                    basefold_parameters.starting_log_inv_rate,
+                    ldt_parameters.field.extension_bit_size(),
+                    ldt_parameters.constraint_degree,
+                );
+                (sumcheck_error, folding_pow_util(sumcheck_error))
+            },
+        );
+
+        for (i, current_merkle_tree) in commitments.into_iter().enumerate() {
+            // The queries, to each of the starting trees if the batched polynomials are committed to separately
+            let num_trees = if i == 0 {
+                interleaving.num_trees(ldt_parameters.batch_size)
+            } else {
+                1
+            };
+            for _ in 0..num_trees {
+                protocol_builder = protocol_builder.prover_message(ProverMessage::new(
+                    ProofElement::MerkleQueries(MerkleQueries {
+                        merkle_tree: current_merkle_tree,
+                        num_openings: final_queries,
+                        shared_leaves: 1,
+                    }),
+                ));
+            }
         }
 
-        BasefoldProtocol {
+        let protocol = protocol_builder.end_round().build();
+        // Unless some grind is capped or forbidden, every round reaches the security level
+        let security_shortfall = 0f64.max(security_level as f64 - protocol.rbr_error());
+
+        Ok(BasefoldProtocol {
             config: BasefoldConfig {
                 ldt_parameters,
                 security_assumption: basefold_parameters.security_assumption,
                 security_level,
-                max_pow_bits: basefold_parameters.pow_bits,
+                max_pow_bits,
+                no_grinding: basefold_parameters.no_grinding,
+                max_folding_pow_bits: basefold_parameters.folding_pow_bits,
+                grinding_cap: basefold_parameters.grinding_cap,
+                security_shortfall,
+                eta_factor,
+                max_list_size_bits: basefold_parameters.max_list_size_bits,
+                digest_size_bits: basefold_parameters.digest_size_bits,
+                digest: basefold_parameters.digest,
+                zero_knowledge: basefold_parameters.zero_knowledge,
+                base_field_statement: basefold_parameters.base_field_statement,
+                min_final_log_degree: basefold_parameters.min_final_log_degree,
+                encoding,
+                interleaving,
+                final_phase,
                 batching_pow_bits,
                 starting_folding_factor,
                 starting_domain_log_size,
                 log_inv_rate: basefold_parameters.starting_log_inv_rate,
+                commit_over_extension: basefold_parameters.commit_over_extension,
                 starting_folding_pow_bits: starting_folding_pow_bits_vec,
                 round_parameters,
                 queries: final_queries,
                 pow_bits: final_pow_bits,
                 final_poly_log_degree: final_log_degree,
             },
-            protocol: protocol_builder.end_round().build(),
-        }
+            protocol,
+        })
+    }
+}
+
+impl BasefoldProtocol {
+    /// Calculates the size in bits of the query phase, i.e. of opening every commitment at each query.
+    pub fn query_phase_size_bits(&self) -> usize {
+        self.protocol.merkle_queries_size_bits()
+    }
+
+    /// Serializes the configuration and the round-by-round proof sizes to JSON.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::json!({
+            "config": self.config,
+            "protocol": self.protocol.to_json_value(),
+        })
+        .to_string()
+    }
+}
+
+impl LowDegreeTest for BasefoldProtocol {
+    fn protocol(&self) -> &Protocol {
+        &self.protocol
+    }
+}
+
+impl Report for BasefoldProtocol {
+    fn report(&self) -> ProtocolReport {
+        ProtocolReport::new(
+            &self.protocol,
+            self.config.queries,
+            self.config.round_parameters.len(),
+            self.config.final_poly_log_degree,
+        )
     }
 }
 
 impl Display for BasefoldProtocol {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.config.fmt(f)?;
-        self.protocol.fmt(f)
+        self.protocol.fmt(f)?;
+        print_final_poly_warning(
+            f,
+            self.config.final_poly_size_bits(),
+            self.protocol.proof_size_bits(),
+        )
     }
 }
 
 /// A fully expanded Basefold configuration.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct BasefoldConfig {
     /// The configuration for the LDT desired.
     pub ldt_parameters: LowDegreeParameters,
@@ -339,9 +631,55 @@ pub struct BasefoldConfig {
     /// The maximum number of pow bits allowed (over and we throw a warning, as probably then we are misconfigured.)
     pub max_pow_bits: usize,
 
+    /// Whether grinding was forbidden.
+    pub no_grinding: bool,
+
+    /// The maximum number of pow bits used to reduce proximity gaps errors, if capped.
+    pub max_folding_pow_bits: Option<usize>,
+
+    /// The cap on the pow bits of any grind, if given.
+    pub grinding_cap: Option<usize>,
+
+    /// How many bits the round-by-round soundness falls short of the security level, i.e. `security_level - rbr_error`
+    /// (if positive). This includes any shortfall due to `grinding_cap`, `max_folding_pow_bits` or `no_grinding`.
+    pub security_shortfall: f64,
+
+    /// The divisor used to set η.
+    pub eta_factor: f64,
+
+    /// The cap on the (log) list size, if any.
+    pub max_list_size_bits: Option<f64>,
+
+    /// The size of the digest for the Merkle trees, unless sized for the security level.
+    pub digest_size_bits: Option<usize>,
+
+    /// The hash used for the Merkle digests, if given.
+    pub digest: Option<DigestConfig>,
+
+    /// Whether the commitments are zero-knowledge.
+    pub zero_knowledge: bool,
+
+    /// Whether the statement was over the base field.
+    pub base_field_statement: bool,
+
+    /// The smallest (log) degree the final polynomial was allowed to have.
+    pub min_final_log_degree: usize,
+
+    /// How field elements are serialized in the proof.
+    pub encoding: Encoding,
+
+    /// How the batched polynomials were committed to in the starting commitment.
+    pub interleaving: InterleavingMode,
+
+    /// How the final polynomial was checked.
+    pub final_phase: FinalPhase,
+
     /// The rate of the RS codes used during the protocol.    
     pub log_inv_rate: usize,
 
+    /// Whether the starting commitment is over the extension field.
+    pub commit_over_extension: bool,
+
     /// The pow bits used in the batching phase.
     pub batching_pow_bits: f64,
 
@@ -366,7 +704,8 @@ pub struct BasefoldConfig {
 }
 
 /// Round specific configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct RoundConfig {
     /// Folding factor for this round.
     pub folding_factor: usize,
@@ -377,8 +716,113 @@ pub struct RoundConfig {
 }
 
 impl BasefoldConfig {
+    /// Reconstructs the parameters this configuration was built from, such that `BasefoldProtocol::new` with these
+    /// parameters (and the same LDT parameters) yields this configuration again.
+    /// NOTE: The digest size is the one used, i.e. after capping it for the security level.
+    pub fn to_parameters(&self) -> BasefoldParameters {
+        BasefoldParameters {
+            starting_log_inv_rate: self.log_inv_rate,
+            starting_folding_factor: self.starting_folding_factor,
+            folding_factors: self
+                .round_parameters
+                .iter()
+                .map(|round| round.folding_factor)
+                .collect(),
+            security_assumption: self.security_assumption,
+            eta_factor: Some(self.eta_factor),
+            max_list_size_bits: self.max_list_size_bits,
+            security_level: self.security_level,
+            pow_bits: self.max_pow_bits,
+            folding_pow_bits: self.max_folding_pow_bits,
+            grinding_cap: self.grinding_cap,
+            digest_size_bits: self.digest_size_bits,
+            digest: self.digest,
+            no_grinding: self.no_grinding,
+            zero_knowledge: self.zero_knowledge,
+            base_field_statement: self.base_field_statement,
+            encoding: self.encoding,
+            interleaving: self.interleaving,
+            final_phase: self.final_phase,
+            min_final_log_degree: self.min_final_log_degree,
+            commit_over_extension: self.commit_over_extension,
+        }
+    }
+
+    /// The total size in bits of the oracles committed to by the prover, see `protocol::total_oracle_bits`.
+    pub fn total_oracle_bits(&self) -> usize {
+        total_oracle_bits(
+            self.ldt_parameters,
+            std::iter::once(self.starting_domain_log_size).chain(
+                self.round_parameters
+                    .iter()
+                    .map(|r| r.evaluation_domain_log_size),
+            ),
+        )
+    }
+
+    /// The size in bits spent on the final polynomial, i.e. its coefficients when sent in the clear,
+    /// or the sumcheck polynomials and the final evaluation when checked with a sumcheck.
+    pub fn final_poly_size_bits(&self) -> usize {
+        let num_elements = self.final_phase.num_elements(
+            self.final_poly_log_degree,
+            self.ldt_parameters.constraint_degree,
+        );
+        FieldElements::extension(self.ldt_parameters.field, num_elements)
+            .with_encoding(self.encoding)
+            .size_bits()
+    }
+
+    /// The number of commitments opened in the query phase (the starting one and one per round).
+    pub fn num_commitments(&self) -> usize {
+        1 + self.round_parameters.len()
+    }
+
+    /// Returns the (labeled) PoW values exceeding `max_pow_bits`, which likely signal a misconfiguration.
+    pub fn pow_bits_exceeded(&self) -> Vec<(String, f64)> {
+        let mut pow_bits = vec![("batching".to_string(), self.batching_pow_bits)];
+        for (j, &bits) in self.starting_folding_pow_bits.iter().enumerate() {
+            pow_bits.push((format!("starting folding {j}"), bits));
+        }
+        for (i, r) in self.round_parameters.iter().enumerate() {
+            for (j, &bits) in r.folding_pow_bits.iter().enumerate() {
+                pow_bits.push((format!("round {i} folding {j}"), bits));
+            }
+        }
+        pow_bits.push(("final".to_string(), self.pow_bits));
+
+        pow_bits.retain(|&(_, bits)| bits > self.max_pow_bits as f64);
+        pow_bits
+    }
+
+    /// Exports the round-specific parameters as CSV, with one row per round followed by the final round.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        writeln!(csv, "{ROUNDS_CSV_HEADER}").unwrap();
+        // The queries are all made in the final round
+        for (i, r) in self.round_parameters.iter().enumerate() {
+            writeln!(
+                csv,
+                "{},{},{},{},0,0,{:.1}",
+                i,
+                r.folding_factor,
+                r.evaluation_domain_log_size,
+                self.log_inv_rate,
+                r.folding_pow_bits.iter().sum::<f64>()
+            )
+            .unwrap();
+        }
+        writeln!(
+            csv,
+            "final,,,{},{},0,{:.1}",
+            self.log_inv_rate, self.queries, self.pow_bits
+        )
+        .unwrap();
+        csv
+    }
+
     // Prints a summary of the configuration for Basefold.
     pub fn print_config_summary(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let precision = float_precision(f);
         writeln!(f, "{}", self.ldt_parameters)?;
         writeln!(
             f,
@@ -388,14 +832,14 @@ impl BasefoldConfig {
 
         writeln!(
             f,
-            "Initial domain size: 2^{}, initial rate 2^-{}, queries: {}, pow_bits: {:.1}",
+            "Initial domain size: 2^{}, initial rate 2^-{}, queries: {}, pow_bits: {:.precision$}",
             self.starting_domain_log_size, self.log_inv_rate, self.queries, self.pow_bits
         )?;
 
         if self.ldt_parameters.batch_size > 1 {
             writeln!(
                 f,
-                "Batch size: {}, batching_pow_bits: {:.1}",
+                "Batch size: {}, batching_pow_bits: {:.precision$}",
                 self.ldt_parameters.batch_size, self.batching_pow_bits
             )?;
         }
@@ -405,7 +849,7 @@ impl BasefoldConfig {
             "Initial folding factor: {}, initial_folding_pow_bits: ",
             self.starting_folding_factor,
         )?;
-        pretty_print_float_slice(f, &self.starting_folding_pow_bits)?;
+        pretty_print_float_slice(f, &self.starting_folding_pow_bits, precision)?;
 
         for r in &self.round_parameters {
             r.fmt(f)?;
@@ -417,6 +861,8 @@ impl BasefoldConfig {
             self.queries, self.final_poly_log_degree,
         )?;
 
+        print_pow_warning(f, self.max_pow_bits, &self.pow_bits_exceeded())?;
+
         Ok(())
     }
 }
@@ -429,11 +875,239 @@ impl Display for BasefoldConfig {
 
 impl Display for RoundConfig {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let precision = float_precision(f);
         write!(
             f,
             "Folding factor: {}, domain_size: 2^{}, folding_pow_bits: ",
             self.folding_factor, self.evaluation_domain_log_size,
         )?;
-        pretty_print_float_slice(f, &self.folding_pow_bits)
+        pretty_print_float_slice(f, &self.folding_pow_bits, precision)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BasefoldParameters, BasefoldProtocol};
+    use crate::{
+        errors::SecurityAssumption,
+        field::GOLDILOCKS_2,
+        hash::DigestConfig,
+        protocol::proof_size::{FieldElements, ProofElement},
+        protocol::proof_size::{FinalPhase, InterleavingMode},
+        LowDegreeParameters,
+    };
+
+    #[test]
+    fn test_initial_claim() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 20,
+            batch_size: 1,
+            constraint_degree: 2,
+            num_constraints: 1,
+        };
+        let basefold_parameters = BasefoldParameters::fixed_folding(
+            1,
+            4,
+            3,
+            SecurityAssumption::CapacityBound,
+            100,
+            20,
+            256,
+        );
+        let proof = BasefoldProtocol::new(ldt_parameters, basefold_parameters)
+            .unwrap()
+            .protocol
+            .to_proof();
+        let initial_iteration = proof
+            .rounds
+            .iter()
+            .find(|round| round.name == "initial_iteration")
+            .unwrap();
+
+        // The claimed evaluation is a single extension field element, sent before the first sumcheck polynomial
+        match initial_iteration.elements[..2] {
+            [ProofElement::FieldElements(claim), ProofElement::FieldElements(sumcheck_polynomial)] =>
+            {
+                assert_eq!(claim, FieldElements::extension(GOLDILOCKS_2, 1));
+                assert_eq!(claim.size_bits(), 128);
+                assert_eq!(sumcheck_polynomial.num_elements, 3);
+            }
+            _ => panic!("expected the claimed evaluation and the sumcheck polynomial"),
+        }
+    }
+
+    #[test]
+    fn test_query_phase_grows_with_rounds() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 20,
+            batch_size: 1,
+            constraint_degree: 2,
+            num_constraints: 1,
+        };
+
+        let query_phase_sizes: Vec<_> = (1..=4)
+            .map(|num_rounds| {
+                let basefold_parameters = BasefoldParameters::fixed_folding(
+                    1,
+                    2,
+                    num_rounds,
+                    SecurityAssumption::CapacityBound,
+                    100,
+                    20,
+                    256,
+                );
+                let basefold_protocol =
+                    BasefoldProtocol::new(ldt_parameters, basefold_parameters).unwrap();
+                assert_eq!(basefold_protocol.config.num_commitments(), num_rounds + 1);
+                assert!(
+                    basefold_protocol.query_phase_size_bits()
+                        < basefold_protocol.protocol.proof_size_bits()
+                );
+                basefold_protocol.query_phase_size_bits()
+            })
+            .collect();
+
+        // Each round adds another (slightly shallower) commitment to open
+        let increments: Vec<_> = query_phase_sizes.windows(2).map(|w| w[1] - w[0]).collect();
+        assert!(increments.iter().all(|&increment| increment > 0));
+        assert!(increments.windows(2).all(|w| w[1] <= w[0]));
+    }
+
+    #[test]
+    fn test_zero_rounds() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 16,
+            batch_size: 1,
+            constraint_degree: 0,
+            num_constraints: 1,
+        };
+        let basefold_parameters = |starting_folding_factor| BasefoldParameters {
+            starting_folding_factor,
+            ..BasefoldParameters::fixed_folding(
+                1,
+                4,
+                0,
+                SecurityAssumption::CapacityBound,
+                100,
+                20,
+                256,
+            )
+        };
+
+        // Without any fold, the whole polynomial is sent and checked against the starting commitment
+        let basefold_protocol =
+            BasefoldProtocol::new(ldt_parameters, basefold_parameters(0)).unwrap();
+        assert_eq!(basefold_protocol.config.num_commitments(), 1);
+        assert_eq!(basefold_protocol.config.final_poly_log_degree, 16);
+        assert_eq!(basefold_protocol.protocol.num_classical_rounds(), 1);
+        let final_poly_size_bits = (1 << 16) * GOLDILOCKS_2.extension_bit_size();
+        assert!(10 * final_poly_size_bits > 9 * basefold_protocol.protocol.proof_size_bits());
+
+        // Otherwise, only the starting fold is done
+        let basefold_protocol =
+            BasefoldProtocol::new(ldt_parameters, basefold_parameters(4)).unwrap();
+        assert_eq!(basefold_protocol.config.final_poly_log_degree, 12);
+        assert_eq!(basefold_protocol.protocol.num_classical_rounds(), 4 + 1);
+    }
+
+    #[test]
+    fn test_commit_over_extension() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 20,
+            batch_size: 1,
+            constraint_degree: 2,
+            num_constraints: 1,
+        };
+        let basefold_protocol = |commit_over_extension| {
+            let basefold_parameters = BasefoldParameters {
+                commit_over_extension,
+                ..BasefoldParameters::fixed_folding(
+                    1,
+                    4,
+                    3,
+                    SecurityAssumption::CapacityBound,
+                    100,
+                    20,
+                    256,
+                )
+            };
+            BasefoldProtocol::new(ldt_parameters, basefold_parameters).unwrap()
+        };
+        // The size of the openings of the starting commitment
+        let starting_opening_size = |basefold_protocol: &BasefoldProtocol| {
+            basefold_protocol
+                .protocol
+                .prover_messages()
+                .find_map(|message| match message.element() {
+                    ProofElement::MerkleQueries(merkle_queries) => {
+                        Some(merkle_queries.opening_size())
+                    }
+                    _ => None,
+                })
+                .unwrap()
+        };
+
+        let over_base = basefold_protocol(false);
+        let over_extension = basefold_protocol(true);
+        assert!(over_extension.config.commit_over_extension);
+
+        // The opened leaves grow by the extension degree, the authentication paths are unchanged
+        let extension_degree = GOLDILOCKS_2.extension_degree;
+        assert_eq!(
+            starting_opening_size(&over_extension),
+            extension_degree * starting_opening_size(&over_base)
+        );
+        assert_eq!(
+            over_extension.query_phase_size_bits() - over_base.query_phase_size_bits(),
+            (extension_degree - 1) * starting_opening_size(&over_base)
+        );
+        assert!(over_extension.protocol.proof_size_bits() > over_base.protocol.proof_size_bits());
+    }
+
+    #[test]
+    fn test_to_parameters() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 20,
+            batch_size: 2,
+            constraint_degree: 2,
+            num_constraints: 1,
+        };
+        let round_trip = |basefold_parameters: BasefoldParameters| {
+            let config = BasefoldProtocol::new(ldt_parameters, basefold_parameters)
+                .unwrap()
+                .config;
+            let rebuilt = BasefoldProtocol::new(config.ldt_parameters, config.to_parameters())
+                .unwrap()
+                .config;
+            assert_eq!(rebuilt, config);
+        };
+
+        let basefold_parameters = BasefoldParameters::fixed_folding(
+            1,
+            4,
+            3,
+            SecurityAssumption::CapacityBound,
+            100,
+            20,
+            256,
+        );
+        round_trip(basefold_parameters.clone());
+        round_trip(BasefoldParameters {
+            eta_factor: Some(4.),
+            folding_pow_bits: Some(10),
+            digest: Some(DigestConfig::Sha3_256),
+            no_grinding: true,
+            zero_knowledge: true,
+            base_field_statement: true,
+            interleaving: InterleavingMode::Separate,
+            final_phase: FinalPhase::Sumcheck,
+            commit_over_extension: true,
+            ..basefold_parameters
+        });
     }
 }
@@ -1,76 +1,283 @@
+use std::{error::Error, fmt::Display, str::FromStr};
+
 use stir_whir_estimation::{
     basefold::{BasefoldParameters, BasefoldProtocol},
+    compare::DEFAULT_LOG_RO_QUERIES,
     errors::SecurityAssumption,
     field::*,
     fri::{FriParameters, FriProtocol},
+    presets::Preset,
+    protocol::Protocol,
     stir::{StirParameters, StirProtocol},
     whir::{WhirParameters, WhirProtocol},
     LowDegreeParameters,
 };
 
-fn main() {
-    let ldt_parameters = LowDegreeParameters {
-        field: GOLDILOCKS_2,
-        log_degree: 26,
-        batch_size: 1,
-        constraint_degree: 0,
-    };
+/// The protocols that the binary can print.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProtocolSelection {
+    Stir,
+    Fri,
+    Basefold,
+    Whir,
+}
+
+impl ProtocolSelection {
+    const ALL: [ProtocolSelection; 4] = [
+        ProtocolSelection::Stir,
+        ProtocolSelection::Fri,
+        ProtocolSelection::Basefold,
+        ProtocolSelection::Whir,
+    ];
+}
+
+impl FromStr for ProtocolSelection {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "stir" => Ok(ProtocolSelection::Stir),
+            "fri" => Ok(ProtocolSelection::Fri),
+            "basefold" => Ok(ProtocolSelection::Basefold),
+            "whir" => Ok(ProtocolSelection::Whir),
+            _ => Err(format!(
+                "Invalid protocol: {s} (expected one of stir, fri, basefold, whir)"
+            )),
+        }
+    }
+}
+
+/// A single scalar that the binary can print instead of the full summary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Metric {
+    /// The proof size in bits.
+    Bits,
+    /// The proof size in bytes.
+    Bytes,
+    /// The classical compiled security against 2^DEFAULT_LOG_RO_QUERIES queries.
+    Security,
+    /// The total number of Merkle openings.
+    Queries,
+}
+
+impl Metric {
+    /// Computes the value of the metric for the given protocol.
+    fn value(&self, protocol: &Protocol) -> String {
+        match self {
+            Metric::Bits => protocol.proof_size_bits().to_string(),
+            Metric::Bytes => protocol.proof_size_bits().div_ceil(8).to_string(),
+            Metric::Security => format!(
+                "{:.1}",
+                protocol.compiled_classical_security(DEFAULT_LOG_RO_QUERIES)
+            ),
+            Metric::Queries => protocol.element_stats().merkle_openings.to_string(),
+        }
+    }
+}
+
+impl FromStr for Metric {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "bits" => Ok(Metric::Bits),
+            "bytes" => Ok(Metric::Bytes),
+            "security" => Ok(Metric::Security),
+            "queries" => Ok(Metric::Queries),
+            _ => Err(format!(
+                "Invalid metric: {s} (expected one of bits, bytes, security, queries)"
+            )),
+        }
+    }
+}
+
+/// Prints either the full summary of a protocol or, if a metric is selected, only its value.
+fn print_protocol(summary: &impl Display, protocol: &Protocol, metric: Option<Metric>) {
+    match metric {
+        Some(metric) => println!("{}", metric.value(protocol)),
+        None => println!("{summary}"),
+    }
+}
+
+/// The command line arguments of the binary.
+struct Args {
+    protocols: Vec<ProtocolSelection>,
+    field: Field,
+    preset: Option<Preset>,
+    metric: Option<Metric>,
+}
+
+/// Parses `--protocol <name>` (defaulting to all protocols), `--field <name>` (defaulting to Goldilocks),
+/// `--preset <name>` (overriding the protocols) and `--metric <name>`, also accepting the `--flag=<value>` form.
+/// `--quiet` (or `--size-only`) is a shorthand for `--metric bits`.
+fn parse_args() -> Result<Args, String> {
+    let mut args = std::env::args().skip(1);
+    let mut protocols = Vec::new();
+    let mut field = GOLDILOCKS_2;
+    let mut preset = None;
+    let mut metric = None;
+    let mut size_only = false;
+    while let Some(arg) = args.next() {
+        if arg == "--quiet" || arg == "--size-only" {
+            size_only = true;
+            continue;
+        }
+        let (flag, value) = match arg.split_once('=') {
+            Some((flag, value)) => (flag.to_owned(), value.to_owned()),
+            None => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("Missing value for {arg}"))?;
+                (arg, value)
+            }
+        };
+        match flag.as_str() {
+            "--protocol" => protocols.push(value.parse()?),
+            "--field" => {
+                field = from_name(&value).ok_or_else(|| format!("Unknown field: {value}"))?
+            }
+            "--preset" => preset = Some(value.parse()?),
+            "--metric" => metric = Some(value.parse()?),
+            _ => return Err(format!("Unknown argument: {flag}")),
+        }
+    }
+
+    if protocols.is_empty() {
+        protocols.extend(ProtocolSelection::ALL);
+    }
+    if size_only {
+        metric.get_or_insert(Metric::Bits);
+    }
+    Ok(Args {
+        protocols,
+        field,
+        preset,
+        metric,
+    })
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = parse_args()?;
 
-    let stir_parameters = StirParameters::fixed_domain_shift(
-        1,                                 // log_inv_rate
-        4,                                 // folding_factor
-        4,                                 // num_rounds
-        SecurityAssumption::CapacityBound, // security_assumption
-        100,                               // security_level
-        20,                                // pow_bits
-        256,                               // digest_size_bits
-    );
-    let stir_protocol = StirProtocol::new(ldt_parameters, stir_parameters);
-
-    let fri_parameters = FriParameters::fixed_folding(
-        1,                                 // log_inv_rate
-        4,                                 // folding_factor
-        4,                                 // num_rounds
-        SecurityAssumption::CapacityBound, // security_assumption
-        100,                               // security_level
-        20,                                // pow_bits
-        256,                               // digest_size_bits
-    );
-
-    let fri_protocol = FriProtocol::new(ldt_parameters, fri_parameters);
-
-    let pcs_parameters = LowDegreeParameters {
-        field: GOLDILOCKS_2,
-        log_degree: 26,
-        batch_size: 1,
-        constraint_degree: 2,
+    // STIR and FRI only support proximity testing
+    let ldt_parameters = LowDegreeParameters::new(args.field, 26, 1, 0);
+
+    // Basefold and WHIR are used as PCS
+    let pcs_parameters = LowDegreeParameters::new(args.field, 26, 1, 2);
+
+    if let Some(preset) = args.preset {
+        match preset {
+            Preset::Stir(stir_parameters) => {
+                let stir_protocol = StirProtocol::new(ldt_parameters, stir_parameters)?;
+                print_protocol(&stir_protocol, &stir_protocol.protocol, args.metric);
+            }
+            Preset::Fri(fri_parameters) => {
+                let fri_protocol = FriProtocol::new(ldt_parameters, fri_parameters)?;
+                print_protocol(&fri_protocol, &fri_protocol.protocol, args.metric);
+            }
+            Preset::Basefold(basefold_parameters) => {
+                let basefold_protocol = BasefoldProtocol::new(pcs_parameters, basefold_parameters)?;
+                print_protocol(&basefold_protocol, &basefold_protocol.protocol, args.metric);
+            }
+            Preset::Whir(whir_parameters) => {
+                let whir_protocol = WhirProtocol::new(pcs_parameters, whir_parameters)?;
+                print_protocol(&whir_protocol, &whir_protocol.protocol, args.metric);
+            }
+        }
+        return Ok(());
+    }
+
+    for protocol in args.protocols {
+        match protocol {
+            ProtocolSelection::Stir => {
+                let stir_parameters = StirParameters::fixed_domain_shift(
+                    1,                                 // log_inv_rate
+                    4,                                 // folding_factor
+                    4,                                 // num_rounds
+                    SecurityAssumption::CapacityBound, // security_assumption
+                    100,                               // security_level
+                    20,                                // pow_bits
+                    256,                               // digest_size_bits
+                );
+                let stir_protocol = StirProtocol::new(ldt_parameters, stir_parameters)?;
+                print_protocol(&stir_protocol, &stir_protocol.protocol, args.metric);
+            }
+            ProtocolSelection::Fri => {
+                let fri_parameters = FriParameters::fixed_folding(
+                    1,                                 // log_inv_rate
+                    4,                                 // folding_factor
+                    4,                                 // num_rounds
+                    SecurityAssumption::CapacityBound, // security_assumption
+                    100,                               // security_level
+                    20,                                // pow_bits
+                    256,                               // digest_size_bits
+                );
+                let fri_protocol = FriProtocol::new(ldt_parameters, fri_parameters)?;
+                print_protocol(&fri_protocol, &fri_protocol.protocol, args.metric);
+            }
+            ProtocolSelection::Basefold => {
+                let basefold_parameters = BasefoldParameters::fixed_folding(
+                    1,                                 // log_inv_rate
+                    4,                                 // folding_factor
+                    4,                                 // num_rounds
+                    SecurityAssumption::CapacityBound, // security_assumption
+                    100,                               // security_level
+                    20,                                // pow_bits
+                    256,                               // digest_size_bits
+                );
+                let basefold_protocol = BasefoldProtocol::new(pcs_parameters, basefold_parameters)?;
+                print_protocol(&basefold_protocol, &basefold_protocol.protocol, args.metric);
+            }
+            ProtocolSelection::Whir => {
+                let whir_parameters = WhirParameters::fixed_domain_shift(
+                    1,                                 // log_inv_rate
+                    4,                                 // folding_factor
+                    4,                                 // num_rounds
+                    SecurityAssumption::CapacityBound, // security_assumption
+                    100,                               // security_level
+                    20,                                // pow_bits
+                    256,                               // digest_size_bits
+                );
+                let whir_protocol = WhirProtocol::new(pcs_parameters, whir_parameters)?;
+                print_protocol(&whir_protocol, &whir_protocol.protocol, args.metric);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Metric;
+    use stir_whir_estimation::{
+        errors::SecurityAssumption,
+        field::GOLDILOCKS_2,
+        whir::{WhirParameters, WhirProtocol},
+        LowDegreeParameters,
     };
 
-    let basefold_parameters = BasefoldParameters::fixed_folding(
-        1,                                 // log_inv_rate
-        4,                                 // folding_factor
-        4,                                 // num_rounds
-        SecurityAssumption::CapacityBound, // security_assumption
-        100,                               // security_level
-        20,                                // pow_bits
-        256,                               // digest_size_bits
-    );
-
-    let basefold_protocol = BasefoldProtocol::new(pcs_parameters, basefold_parameters);
-
-    let whir_parameters = WhirParameters::fixed_domain_shift(
-        1,                                 // log_inv_rate
-        4,                                 // folding_factor
-        4,                                 // num_rounds
-        SecurityAssumption::CapacityBound, // security_assumption
-        100,                               // security_level
-        20,                                // pow_bits
-        256,                               // digest_size_bits
-    );
-    let whir_protocol = WhirProtocol::new(pcs_parameters, whir_parameters);
-
-    println!("{stir_protocol}");
-    println!("{fri_protocol}");
-    println!("{basefold_protocol}");
-    println!("{whir_protocol}");
+    #[test]
+    fn test_metric_value() {
+        let ldt_parameters = LowDegreeParameters::new(GOLDILOCKS_2, 20, 1, 2);
+        let whir_parameters = WhirParameters::fixed_domain_shift(
+            1,
+            4,
+            3,
+            SecurityAssumption::CapacityBound,
+            100,
+            20,
+            256,
+        );
+        let protocol = WhirProtocol::new(ldt_parameters, whir_parameters)
+            .unwrap()
+            .protocol;
+
+        let bits: usize = Metric::Bits.value(&protocol).parse().unwrap();
+        let bytes: usize = Metric::Bytes.value(&protocol).parse().unwrap();
+        assert_eq!(bits, protocol.proof_size_bits());
+        assert_eq!(bytes, bits.div_ceil(8));
+        assert!(Metric::Queries.value(&protocol).parse::<usize>().unwrap() > 0);
+        assert!(Metric::Security.value(&protocol).parse::<f64>().is_ok());
+        assert_eq!("Bytes".parse::<Metric>(), Ok(Metric::Bytes));
+        assert!("size".parse::<Metric>().is_err());
+    }
 }
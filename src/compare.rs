@@ -0,0 +1,208 @@
+//! Side-by-side comparison of the protocols
+use crate::{
+    basefold::{BasefoldParameters, BasefoldProtocol},
+    errors::SecurityAssumption,
+    fri::{FriParameters, FriProtocol},
+    stir::{StirParameters, StirProtocol},
+    whir::{WhirParameters, WhirProtocol},
+    LowDegreeParameters, LowDegreeTest,
+};
+
+/// The number of (log) random oracle queries against which the compiled security is reported by default.
+pub const DEFAULT_LOG_RO_QUERIES: usize = 64;
+
+/// Configures FRI, STIR, WHIR and Basefold with the same folding schedule and returns
+/// `(protocol_name, proof_size_bits, compiled_security)` for each, sorted by proof size.
+/// The compiled security is the classical one against 2^`DEFAULT_LOG_RO_QUERIES` queries.
+/// FRI and Basefold are configured with `fixed_folding`, STIR and WHIR with `fixed_domain_shift`.
+/// Protocols that do not apply (e.g. FRI and STIR for constraints) or cannot be configured are skipped.
+#[allow(clippy::too_many_arguments)]
+pub fn compare_protocols(
+    ldt_parameters: LowDegreeParameters,
+    log_inv_rate: usize,
+    folding_factor: usize,
+    num_rounds: usize,
+    security_assumption: SecurityAssumption,
+    security_level: usize,
+    pow_bits: usize,
+    digest_size_bits: usize,
+) -> Vec<(String, usize, f64)> {
+    let fri = FriProtocol::new(
+        ldt_parameters,
+        FriParameters::fixed_folding(
+            log_inv_rate,
+            folding_factor,
+            num_rounds,
+            security_assumption,
+            security_level,
+            pow_bits,
+            digest_size_bits,
+        ),
+    )
+    .ok()
+    .map(|fri_protocol| ("FRI", Box::new(fri_protocol) as Box<dyn LowDegreeTest>));
+
+    // STIR only supports proximity testing
+    let stir = (ldt_parameters.constraint_degree == 0)
+        .then(|| {
+            StirProtocol::new(
+                ldt_parameters,
+                StirParameters::fixed_domain_shift(
+                    log_inv_rate,
+                    folding_factor,
+                    num_rounds,
+                    security_assumption,
+                    security_level,
+                    pow_bits,
+                    digest_size_bits,
+                ),
+            )
+            .ok()
+        })
+        .flatten()
+        .map(|stir_protocol| ("STIR", Box::new(stir_protocol) as Box<dyn LowDegreeTest>));
+
+    let whir = WhirProtocol::new(
+        ldt_parameters,
+        WhirParameters::fixed_domain_shift(
+            log_inv_rate,
+            folding_factor,
+            num_rounds,
+            security_assumption,
+            security_level,
+            pow_bits,
+            digest_size_bits,
+        ),
+    )
+    .ok()
+    .map(|whir_protocol| ("WHIR", Box::new(whir_protocol) as Box<dyn LowDegreeTest>));
+
+    let basefold = BasefoldProtocol::new(
+        ldt_parameters,
+        BasefoldParameters::fixed_folding(
+            log_inv_rate,
+            folding_factor,
+            num_rounds,
+            security_assumption,
+            security_level,
+            pow_bits,
+            digest_size_bits,
+        ),
+    )
+    .ok()
+    .map(|basefold_protocol| {
+        (
+            "Basefold",
+            Box::new(basefold_protocol) as Box<dyn LowDegreeTest>,
+        )
+    });
+
+    let mut results: Vec<_> = [fri, stir, whir, basefold]
+        .into_iter()
+        .flatten()
+        .map(|(name, ldt)| {
+            (
+                name.to_owned(),
+                ldt.proof_size_bits(),
+                ldt.compiled_classical_security(DEFAULT_LOG_RO_QUERIES),
+            )
+        })
+        .collect();
+
+    results.sort_by_key(|&(_, proof_size_bits, _)| proof_size_bits);
+    results
+}
+
+/// The security assumptions compared by `assumption_comparison`, from the unconditional to the most conjectural.
+pub const COMPARED_ASSUMPTIONS: [SecurityAssumption; 3] = [
+    SecurityAssumption::UniqueDecoding,
+    SecurityAssumption::JohnsonBound,
+    SecurityAssumption::CapacityBound,
+];
+
+/// Builds the same protocol (with `build_fn`) under each of `COMPARED_ASSUMPTIONS` and returns
+/// `(assumption, proof_size_bits, compiled_security)` for each, i.e. how much the conjectures buy.
+/// Unique decoding needs no conjecture but gives the largest proofs, the capacity bound the smallest ones.
+/// The compiled security is the classical one against 2^`DEFAULT_LOG_RO_QUERIES` queries.
+/// Assumptions under which the protocol cannot be configured are skipped.
+pub fn assumption_comparison<L: LowDegreeTest, E>(
+    ldt_parameters: LowDegreeParameters,
+    build_fn: impl Fn(LowDegreeParameters, SecurityAssumption) -> Result<L, E>,
+) -> Vec<(SecurityAssumption, usize, f64)> {
+    COMPARED_ASSUMPTIONS
+        .into_iter()
+        .filter_map(|security_assumption| {
+            let ldt = build_fn(ldt_parameters, security_assumption).ok()?;
+            Some((
+                security_assumption,
+                ldt.proof_size_bits(),
+                ldt.compiled_classical_security(DEFAULT_LOG_RO_QUERIES),
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{assumption_comparison, compare_protocols, COMPARED_ASSUMPTIONS};
+    use crate::{
+        errors::SecurityAssumption,
+        field::GOLDILOCKS_2,
+        whir::{WhirParameters, WhirProtocol},
+        LowDegreeParameters,
+    };
+
+    fn compared_names(constraint_degree: usize) -> Vec<String> {
+        let ldt_parameters = LowDegreeParameters::new(GOLDILOCKS_2, 20, 1, constraint_degree);
+        let results = compare_protocols(
+            ldt_parameters,
+            1,
+            4,
+            3,
+            SecurityAssumption::CapacityBound,
+            100,
+            20,
+            256,
+        );
+        assert!(results.windows(2).all(|w| w[0].1 <= w[1].1));
+        results.into_iter().map(|(name, _, _)| name).collect()
+    }
+
+    #[test]
+    fn test_compare_pcs() {
+        let names = compared_names(2);
+        assert_eq!(names.len(), 2);
+        assert!(names.contains(&"WHIR".to_owned()));
+        assert!(names.contains(&"Basefold".to_owned()));
+    }
+
+    #[test]
+    fn test_compare_ldt() {
+        assert_eq!(compared_names(0).len(), 4);
+    }
+
+    #[test]
+    fn test_assumption_comparison() {
+        let ldt_parameters = LowDegreeParameters::new(GOLDILOCKS_2, 20, 1, 2);
+        let results =
+            assumption_comparison(ldt_parameters, |ldt_parameters, security_assumption| {
+                WhirProtocol::new(
+                    ldt_parameters,
+                    WhirParameters::fixed_domain_shift(1, 4, 3, security_assumption, 100, 20, 256),
+                )
+            });
+
+        let assumptions: Vec<_> = results
+            .iter()
+            .map(|&(assumption, _, _)| assumption)
+            .collect();
+        assert_eq!(assumptions, COMPARED_ASSUMPTIONS);
+
+        // The more is conjectured, the smaller the proof, at the same compiled security
+        assert!(results.windows(2).all(|w| w[0].1 >= w[1].1));
+        assert!(results[0].1 > results[2].1);
+        assert!(results
+            .iter()
+            .all(|&(_, _, security)| security == results[0].2));
+    }
+}
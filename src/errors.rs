@@ -1,7 +1,10 @@
 use std::{f64::consts::LOG2_10, fmt::Display, str::FromStr};
 
+use crate::utils::rate_from_log_inv;
+
 /// Security assumptions determines which proximity parameters and conjectures are assumed by the error computation.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum SecurityAssumption {
     /// Unique decoding assumes that the distance of each oracle is within the UDR of the code.
     /// We refer to this configuration as UD for short.
@@ -18,41 +21,265 @@ pub enum SecurityAssumption {
     /// We refer to this configuration as CB for short.
     /// In both STIR and WHIR this requires conjecturing that RS codes are decodable up to capacity and have correlated agreement (mutual in WHIR) up to capacity.
     CapacityBound,
+
+    /// An idealized (and insecure) assumption where the list size is 1, the proximity gaps error is 1/|F| per function
+    /// and δ = 1, so that a single query suffices.
+    /// This is only meant for testing, as it makes the protocol structure easy to compute by hand.
+    Ideal,
 }
 
+/// How the verifier samples the out-of-domain points used to bind the prover to a single codeword of the list.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum OodStrategy {
+    /// The verifier samples `ood_samples` independent points in the extension field,
+    /// and the prover answers with the evaluation at each of them.
+    /// The error is list_size^2 * (degree/|F|)^ood_samples.
+    #[default]
+    Repeated,
+
+    /// The verifier samples a single point in the degree `ood_samples` extension of the extension field,
+    /// and the prover answers with its evaluation (sent as `ood_samples` extension field elements).
+    /// The degree is only paid once, i.e. the error is list_size^2 * degree/|F|^ood_samples.
+    SinglePoint,
+}
+
+/// The (exclusive) bound on the number of OOD samples searched by `determine_ood_samples`.
+pub const MAX_OOD_SAMPLES: usize = 64;
+
+/// The default divisor used to set η, i.e. η = √ρ/20 in JB and η = ρ/20 in CB.
+pub const DEFAULT_ETA_FACTOR: f64 = 20.;
+
 impl SecurityAssumption {
     /// In both JB and CB theorems such as list-size only hold for proximity parameters slightly below the bound.
     /// E.g. in JB proximity gaps holds for every δ ∈ (0, 1 - √ρ).
     /// η is the distance between the chosen proximity parameter and the bound.
     /// I.e. in JB δ = 1 - √ρ - η and in CB δ = 1 - ρ - η.
-    // TODO: maybe it makes more sense to be multiplicative. I think this can be set in a better way.
+    /// Here η is set using `DEFAULT_ETA_FACTOR`, see `log_eta_with_factor`.
     pub fn log_eta(&self, log_inv_rate: usize) -> f64 {
+        self.log_eta_with_factor(log_inv_rate, DEFAULT_ETA_FACTOR)
+    }
+
+    /// Computes log(η) where η is set as a fraction of the decoding bound, i.e. √ρ/eta_factor in JB and ρ/eta_factor in CB.
+    /// Larger factors bring δ closer to the bound, at the cost of larger lists.
+    pub fn log_eta_with_factor(&self, log_inv_rate: usize, eta_factor: f64) -> f64 {
         // Ask me how I did this? At the time, only God and I knew. Now only God knows
         // I joke, I actually know but this is left for posterity.
         match self {
             // We don't use η in UD
             Self::UniqueDecoding => 0., // TODO: maybe just panic and avoid calling it in UD?
-            // Set as √ρ/20
-            Self::JohnsonBound => -(0.5 * log_inv_rate as f64 + LOG2_10 + 1.),
-            // Set as ρ/20
-            Self::CapacityBound => -(log_inv_rate as f64 + LOG2_10 + 1.),
+            // Nor in the ideal case
+            Self::Ideal => 0.,
+            // Set as √ρ/eta_factor
+            Self::JohnsonBound => -(0.5 * log_inv_rate as f64 + eta_factor.log2()),
+            // Set as ρ/eta_factor
+            Self::CapacityBound => -(log_inv_rate as f64 + eta_factor.log2()),
+        }
+    }
+
+    /// Pairs the assumption with the choice of η (given as the divisor of the decoding bound) used to compute errors.
+    pub fn with_eta_factor(self, eta_factor: f64) -> SecurityAssumptionWithEta {
+        SecurityAssumptionWithEta {
+            assumption: self,
+            eta_factor,
+            max_list_size_bits: None,
+        }
+    }
+
+    /// Pairs the assumption (with the default η) with a cap on the list size, see [`SecurityAssumptionWithEta::with_max_list_size_bits`].
+    pub fn with_max_list_size_bits(self, max_list_size_bits: f64) -> SecurityAssumptionWithEta {
+        self.with_eta_factor(DEFAULT_ETA_FACTOR)
+            .with_max_list_size_bits(max_list_size_bits)
+    }
+
+    /// Same as [`SecurityAssumptionWithEta::list_size_bits`], using the default η.
+    pub fn list_size_bits(&self, log_degree: usize, log_inv_rate: usize) -> f64 {
+        self.with_eta_factor(DEFAULT_ETA_FACTOR)
+            .list_size_bits(log_degree, log_inv_rate)
+    }
+
+    /// Same as [`SecurityAssumptionWithEta::constraint_folding_error`], using the default η.
+    pub fn constraint_folding_error(
+        &self,
+        log_degree: usize,
+        log_inv_rate: usize,
+        field_size_bits: usize,
+        constraint_degree: usize,
+    ) -> f64 {
+        self.with_eta_factor(DEFAULT_ETA_FACTOR)
+            .constraint_folding_error(log_degree, log_inv_rate, field_size_bits, constraint_degree)
+    }
+
+    /// Same as [`SecurityAssumptionWithEta::prox_gaps_error`], using the default η.
+    pub fn prox_gaps_error(
+        &self,
+        log_degree: usize,
+        log_inv_rate: usize,
+        field_size_bits: usize,
+        num_functions: usize,
+    ) -> f64 {
+        self.with_eta_factor(DEFAULT_ETA_FACTOR).prox_gaps_error(
+            log_degree,
+            log_inv_rate,
+            field_size_bits,
+            num_functions,
+        )
+    }
+
+    /// Same as [`SecurityAssumptionWithEta::log_1_delta`], using the default η.
+    pub fn log_1_delta(&self, log_inv_rate: usize) -> f64 {
+        self.with_eta_factor(DEFAULT_ETA_FACTOR)
+            .log_1_delta(log_inv_rate)
+    }
+
+    /// Same as [`SecurityAssumptionWithEta::queries`], using the default η.
+    pub fn queries(
+        &self,
+        protocol_security_level: usize,
+        log_inv_rate: usize,
+    ) -> Result<usize, SecurityError> {
+        self.with_eta_factor(DEFAULT_ETA_FACTOR)
+            .queries(protocol_security_level, log_inv_rate)
+    }
+
+    /// Same as [`SecurityAssumptionWithEta::queries_error`], using the default η.
+    pub fn queries_error(
+        &self,
+        log_inv_rate: usize,
+        num_queries: usize,
+    ) -> Result<f64, SecurityError> {
+        self.with_eta_factor(DEFAULT_ETA_FACTOR)
+            .queries_error(log_inv_rate, num_queries)
+    }
+
+    /// Computes the number of queries needed at each of the given rates, as `(log_inv_rate, num_queries)` pairs.
+    pub fn query_schedule(
+        &self,
+        protocol_security_level: usize,
+        rates: &[usize],
+    ) -> Result<Vec<(usize, usize)>, SecurityError> {
+        rates
+            .iter()
+            .map(|&log_inv_rate| {
+                Ok((
+                    log_inv_rate,
+                    self.queries(protocol_security_level, log_inv_rate)?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Computes the error achieved by the queries at each of the given rates, as `(log_inv_rate, error)` pairs.
+    pub fn queries_error_schedule(
+        &self,
+        protocol_security_level: usize,
+        rates: &[usize],
+    ) -> Result<Vec<(usize, f64)>, SecurityError> {
+        self.query_schedule(protocol_security_level, rates)?
+            .into_iter()
+            .map(|(log_inv_rate, num_queries)| {
+                Ok((log_inv_rate, self.queries_error(log_inv_rate, num_queries)?))
+            })
+            .collect()
+    }
+
+    /// Same as [`SecurityAssumptionWithEta::ood_error`], using the default η.
+    pub fn ood_error(
+        &self,
+        log_degree: usize,
+        log_inv_rate: usize,
+        field_size_bits: usize,
+        ood_samples: usize,
+        strategy: OodStrategy,
+    ) -> f64 {
+        self.with_eta_factor(DEFAULT_ETA_FACTOR).ood_error(
+            log_degree,
+            log_inv_rate,
+            field_size_bits,
+            ood_samples,
+            strategy,
+        )
+    }
+
+    /// Same as [`SecurityAssumptionWithEta::determine_ood_samples`], using the default η.
+    pub fn determine_ood_samples(
+        &self,
+        security_level: usize,
+        log_degree: usize,
+        log_inv_rate: usize,
+        field_size_bits: usize,
+        strategy: OodStrategy,
+    ) -> Result<usize, SecurityError> {
+        self.with_eta_factor(DEFAULT_ETA_FACTOR)
+            .determine_ood_samples(
+                security_level,
+                log_degree,
+                log_inv_rate,
+                field_size_bits,
+                strategy,
+            )
+    }
+}
+
+/// A security assumption together with the choice of η used when computing errors.
+#[derive(Debug, Clone, Copy)]
+pub struct SecurityAssumptionWithEta {
+    /// The underlying security assumption.
+    pub assumption: SecurityAssumption,
+
+    /// η is set as √ρ/eta_factor in JB and ρ/eta_factor in CB.
+    pub eta_factor: f64,
+
+    /// If set, the (log) list size is clamped to this value.
+    pub max_list_size_bits: Option<f64>,
+}
+
+impl SecurityAssumptionWithEta {
+    /// Caps the (log) list size, which otherwise can be unrealistically large in CB (e.g. for small fields such as BabyBear).
+    /// The cap is propagated to every error depending on the list size (OOD, constraint folding and the CB proximity gaps error).
+    pub fn with_max_list_size_bits(self, max_list_size_bits: f64) -> Self {
+        SecurityAssumptionWithEta {
+            max_list_size_bits: Some(max_list_size_bits),
+            ..self
         }
     }
 
+    /// Same as `with_max_list_size_bits`, leaving the list size uncapped if `max_list_size_bits` is `None`.
+    pub fn with_list_size_cap(self, max_list_size_bits: Option<f64>) -> Self {
+        SecurityAssumptionWithEta {
+            max_list_size_bits,
+            ..self
+        }
+    }
+
+    /// Computes log(η), see `SecurityAssumption::log_eta_with_factor`.
+    pub fn log_eta(&self, log_inv_rate: usize) -> f64 {
+        self.assumption
+            .log_eta_with_factor(log_inv_rate, self.eta_factor)
+    }
+
     /// Given a RS code (specified by the log of the degree and log inv of the rate), compute the list size at the specified distance δ.
+    /// The result is clamped to `max_list_size_bits` (if set).
     pub fn list_size_bits(&self, log_degree: usize, log_inv_rate: usize) -> f64 {
         let log_eta = self.log_eta(log_inv_rate);
-        match self {
+        let list_size_bits = match self.assumption {
             // In UD the list size is 1
-            Self::UniqueDecoding => 0.,
+            SecurityAssumption::UniqueDecoding => 0.,
 
             // By the JB, RS codes are (1 - √ρ - η, (2*η*√ρ)^-1)-list decodable.
-            Self::JohnsonBound => {
+            SecurityAssumption::JohnsonBound => {
                 let log_inv_sqrt_rate: f64 = log_inv_rate as f64 / 2.;
                 log_inv_sqrt_rate - (1. + log_eta)
             }
             // In CB we assume that RS codes are (1 - ρ - η, d/ρ*η)-list decodable (see Conjecture 5.6 in STIR).
-            Self::CapacityBound => (log_degree + log_inv_rate) as f64 - log_eta,
+            SecurityAssumption::CapacityBound => (log_degree + log_inv_rate) as f64 - log_eta,
+            // Ideally, the list size is 1
+            SecurityAssumption::Ideal => 0.,
+        };
+
+        match self.max_list_size_bits {
+            Some(max_list_size_bits) => list_size_bits.min(max_list_size_bits),
+            None => list_size_bits,
         }
     }
 
@@ -80,25 +307,36 @@ impl SecurityAssumption {
         // The error computed here is from [BCIKS20] for the combination of two functions. Then we multiply it by the folding factor.
         let log_eta = self.log_eta(log_inv_rate);
         // Note that this does not include the field_size
-        let error = match self {
+        let error = match self.assumption {
             // In UD the error is |L|/|F| = d/ρ*|F|
-            Self::UniqueDecoding => (log_degree + log_inv_rate) as f64,
+            SecurityAssumption::UniqueDecoding => (log_degree + log_inv_rate) as f64,
 
             // In JB the error is degree^2/|F| * (2 * min{ 1 - √ρ - δ, √ρ/20 })^7
             // Since δ = 1 - √ρ - η then 1 - √ρ - δ = η
             // Thus the error is degree^2/|F| * (2 * min { η, √ρ/20 })^7
-            Self::JohnsonBound => {
+            SecurityAssumption::JohnsonBound => {
                 let numerator = (2 * log_degree) as f64;
                 let sqrt_rho_20 = 1. + LOG2_10 + 0.5 * log_inv_rate as f64;
                 numerator + 7. * (sqrt_rho_20.min(-log_eta) - 1.)
             }
 
-            // In CB we assume the error is degree/η*ρ^2
-            Self::CapacityBound => (log_degree + 2 * log_inv_rate) as f64 - log_eta,
+            // In CB we assume the error is degree/η*ρ^2, i.e. the list size divided by ρ
+            SecurityAssumption::CapacityBound => {
+                self.list_size_bits(log_degree, log_inv_rate) + log_inv_rate as f64
+            }
+
+            // Ideally, the error is 1/|F|
+            SecurityAssumption::Ideal => 0.,
         };
 
         // Error is  (num_functions - 1) * error/|F|;
-        let num_functions_1_log = (num_functions as f64 - 1.).log2();
+        // A single function is not combined with anything, and so incurs (only) the error of a single function,
+        // rather than log2(0) = -∞.
+        let num_functions_1_log = if num_functions <= 1 {
+            0.
+        } else {
+            (num_functions as f64 - 1.).log2()
+        };
         field_size_bits as f64 - (error + num_functions_1_log)
     }
 
@@ -107,15 +345,17 @@ impl SecurityAssumption {
     /// In UD, δ is (1 - ρ)/2
     /// In JB, δ is (1 - √ρ - η)
     /// In CB, δ is (1 - ρ - η)
+    /// Ideally, δ is 1 (and the result is -∞)
     pub fn log_1_delta(&self, log_inv_rate: usize) -> f64 {
         let log_eta = self.log_eta(log_inv_rate);
         let eta = 2_f64.powf(log_eta);
-        let rate = 1. / (1 << log_inv_rate) as f64;
+        let rate = rate_from_log_inv(log_inv_rate);
 
-        let delta = match self {
-            Self::UniqueDecoding => 0.5 * (1. - rate),
-            Self::JohnsonBound => 1. - rate.sqrt() - eta,
-            Self::CapacityBound => 1. - rate - eta,
+        let delta = match self.assumption {
+            SecurityAssumption::UniqueDecoding => 0.5 * (1. - rate),
+            SecurityAssumption::JohnsonBound => 1. - rate.sqrt() - eta,
+            SecurityAssumption::CapacityBound => 1. - rate - eta,
+            SecurityAssumption::Ideal => 1.,
         };
 
         (1. - delta).log2()
@@ -124,24 +364,49 @@ impl SecurityAssumption {
     /// Compute the number of queries to match the security level
     /// The error to drive down is (1-δ)^t < 2^-λ.
     /// Where δ is set as in the `log_1_delta` function.
-    pub fn queries(&self, protocol_security_level: usize, log_inv_rate: usize) -> usize {
+    /// Fails for a code of rate 1, for which δ is 0 and no number of queries suffices.
+    pub fn queries(
+        &self,
+        protocol_security_level: usize,
+        log_inv_rate: usize,
+    ) -> Result<usize, SecurityError> {
+        Self::check_redundancy(log_inv_rate)?;
         let num_queries_f = -(protocol_security_level as f64) / self.log_1_delta(log_inv_rate);
 
-        num_queries_f.ceil() as usize
+        // At least one query is always made (in the ideal case, one suffices)
+        Ok((num_queries_f.ceil() as usize).max(1))
     }
 
     /// Compute the error for the given number of queries
     /// The error to drive down is (1-δ)^t < 2^-λ.
     /// Where δ is set as in the `log_1_delta` function.
-    pub fn queries_error(&self, log_inv_rate: usize, num_queries: usize) -> f64 {
+    /// Fails for a code of rate 1, for which δ is 0 and the queries yield no soundness.
+    pub fn queries_error(
+        &self,
+        log_inv_rate: usize,
+        num_queries: usize,
+    ) -> Result<f64, SecurityError> {
+        Self::check_redundancy(log_inv_rate)?;
+        if num_queries == 0 {
+            return Ok(0.);
+        }
         let num_queries = num_queries as f64;
 
-        -num_queries * self.log_1_delta(log_inv_rate)
+        Ok(-num_queries * self.log_1_delta(log_inv_rate))
+    }
+
+    /// Rejects codes of rate 1 (i.e. without redundancy), for which the proximity parameter δ is 0.
+    fn check_redundancy(log_inv_rate: usize) -> Result<(), SecurityError> {
+        if log_inv_rate == 0 {
+            return Err(SecurityError::NoRedundancy);
+        }
+        Ok(())
     }
 
     /// Compute the error for the OOD samples of the protocol
     /// See Lemma 4.5 in STIR.
-    /// The error is list_size^2 * (degree/field_size_bits)^reps
+    /// The error is list_size^2 * (degree/field_size_bits)^reps, or list_size^2 * degree/field_size_bits^reps
+    /// when sampling a single point (see `OodStrategy`).
     /// NOTE: Here we are discounting the domain size as we assume it is negligible compared to the size of the field.
     pub fn ood_error(
         &self,
@@ -149,40 +414,59 @@ impl SecurityAssumption {
         log_inv_rate: usize,
         field_size_bits: usize,
         ood_samples: usize,
+        strategy: OodStrategy,
     ) -> f64 {
-        if matches!(self, Self::UniqueDecoding) {
+        if matches!(
+            self.assumption,
+            SecurityAssumption::UniqueDecoding | SecurityAssumption::Ideal
+        ) {
             return 0.;
         }
 
         let list_size_bits = self.list_size_bits(log_degree, log_inv_rate);
 
-        let error = 2. * list_size_bits + (log_degree * ood_samples) as f64;
+        let degree_reps = match strategy {
+            OodStrategy::Repeated => ood_samples,
+            OodStrategy::SinglePoint => 1,
+        };
+
+        let error = 2. * list_size_bits + (log_degree * degree_reps) as f64;
         (ood_samples * field_size_bits) as f64 + 1. - error
     }
 
     /// Computes the number of OOD samples required to achieve security_level bits of security
     /// We note that in both STIR and WHIR there are various strategies to set OOD samples.
-    /// In this case, we are just sampling one element from the extension field
+    /// The strategy determines whether the samples are independent points or the coordinates of a single point, see `OodStrategy`.
+    /// Fails if no number of samples below `MAX_OOD_SAMPLES` suffices (e.g. if the field is too small).
     pub fn determine_ood_samples(
         &self,
         security_level: usize,
         log_degree: usize,
         log_inv_rate: usize,
         field_size_bits: usize,
-    ) -> usize {
-        if matches!(self, Self::UniqueDecoding) {
-            return 0;
+        strategy: OodStrategy,
+    ) -> Result<usize, SecurityError> {
+        if matches!(
+            self.assumption,
+            SecurityAssumption::UniqueDecoding | SecurityAssumption::Ideal
+        ) {
+            return Ok(0);
         }
 
-        for ood_samples in 1..64 {
-            if self.ood_error(log_degree, log_inv_rate, field_size_bits, ood_samples)
-                >= security_level as f64
-            {
-                return ood_samples;
-            }
-        }
-
-        panic!("Could not find an appropriate number of OOD samples");
+        (1..MAX_OOD_SAMPLES)
+            .find(|&ood_samples| {
+                self.ood_error(
+                    log_degree,
+                    log_inv_rate,
+                    field_size_bits,
+                    ood_samples,
+                    strategy,
+                ) >= security_level as f64
+            })
+            .ok_or(SecurityError::InsufficientOodSamples {
+                security_level,
+                field_size_bits,
+            })
     }
 }
 
@@ -195,6 +479,7 @@ impl Display for SecurityAssumption {
                 SecurityAssumption::UniqueDecoding => "UniqueDecoding",
                 SecurityAssumption::JohnsonBound => "JohnsonBound",
                 SecurityAssumption::CapacityBound => "CapacityBound",
+                SecurityAssumption::Ideal => "Ideal",
             }
         )
     }
@@ -209,15 +494,171 @@ impl FromStr for SecurityAssumption {
             Ok(SecurityAssumption::JohnsonBound)
         } else if s == "CapacityBound" {
             Ok(SecurityAssumption::CapacityBound)
+        } else if s == "Ideal" {
+            Ok(SecurityAssumption::Ideal)
         } else {
             Err(format!("Invalid soundness specification: {s}"))
         }
     }
 }
 
+/// Errors raised when a protocol is configured with inconsistent parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParameterError {
+    /// Some folding factor (starting or per-round) is zero, so the protocol never folds.
+    ZeroFoldingFactor,
+
+    /// The protocol has no folding rounds after the starting fold.
+    NoRounds,
+
+    /// The folding factors reduce the degree by more than it actually is.
+    OverFolded {
+        total_reduction: usize,
+        log_degree: usize,
+    },
+
+    /// The per-round vectors (folding factors, rates and security assumptions) have different lengths.
+    MismatchedRoundVectors,
+
+    /// The folding factors reduce the degree below the minimum final degree.
+    FinalDegreeTooSmall {
+        final_log_degree: usize,
+        min_final_log_degree: usize,
+    },
+
+    /// The protocol does not support constraints of this degree.
+    UnsupportedConstraintDegree { constraint_degree: usize },
+
+    /// The constraint degree is non zero, but there are no constraints to prove.
+    NoConstraints { constraint_degree: usize },
+
+    /// The protocol does not support this field (e.g. circle FRI outside of Mersenne31).
+    UnsupportedField { field_name: &'static str },
+
+    /// The matrix has more columns than the polynomial has coefficients.
+    InvalidMatrixDimensions {
+        log_num_cols: usize,
+        log_degree: usize,
+    },
+
+    /// The security level cannot be achieved with these parameters.
+    Security(SecurityError),
+}
+
+/// Errors raised when configuring WHIR.
+pub type WhirError = ParameterError;
+
+impl Display for ParameterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParameterError::ZeroFoldingFactor => write!(f, "folding factors should be non zero"),
+            ParameterError::NoRounds => write!(f, "at least one folding round is required"),
+            ParameterError::OverFolded {
+                total_reduction,
+                log_degree,
+            } => write!(
+                f,
+                "total reduction 2^{total_reduction} exceeds the degree 2^{log_degree}"
+            ),
+            ParameterError::FinalDegreeTooSmall {
+                final_log_degree,
+                min_final_log_degree,
+            } => write!(
+                f,
+                "final degree 2^{final_log_degree} is below the minimum 2^{min_final_log_degree}"
+            ),
+            ParameterError::MismatchedRoundVectors => {
+                write!(f, "per-round parameters have different lengths")
+            }
+            ParameterError::UnsupportedConstraintDegree { constraint_degree } => {
+                write!(f, "unsupported constraint degree: {constraint_degree}")
+            }
+            ParameterError::NoConstraints { constraint_degree } => write!(
+                f,
+                "constraint degree {constraint_degree} given without any constraints"
+            ),
+            ParameterError::UnsupportedField { field_name } => {
+                write!(f, "unsupported field: {field_name}")
+            }
+            ParameterError::InvalidMatrixDimensions {
+                log_num_cols,
+                log_degree,
+            } => write!(
+                f,
+                "2^{log_num_cols} columns exceed the degree 2^{log_degree}"
+            ),
+            ParameterError::Security(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ParameterError {}
+
+impl From<SecurityError> for ParameterError {
+    fn from(error: SecurityError) -> Self {
+        ParameterError::Security(error)
+    }
+}
+
+/// Errors raised when an error term cannot be driven below the required security level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecurityError {
+    /// No number of OOD samples (below `MAX_OOD_SAMPLES`) achieves the security level over this field.
+    InsufficientOodSamples {
+        security_level: usize,
+        field_size_bits: usize,
+    },
+
+    /// The code has rate 1 (`log_inv_rate` is 0), so queries cannot detect words far from it.
+    NoRedundancy,
+}
+
+impl Display for SecurityError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SecurityError::InsufficientOodSamples {
+                security_level,
+                field_size_bits,
+            } => write!(
+                f,
+                "no number of OOD samples achieves {security_level} bits of security over a {field_size_bits}-bit field"
+            ),
+            SecurityError::NoRedundancy => {
+                write!(f, "the code has rate 1 (log_inv_rate = 0), so queries yield no soundness")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SecurityError {}
+
+/// Errors raised when building a protocol in strict mode (see `ProtocolBuilder::strict`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuilderError {
+    /// A round was started (or the protocol built) without ending the previous round.
+    UnendedRound { name: String },
+    /// A round was started without the verifier having sent a message in the previous round.
+    NoPriorVerifierMessage { name: String },
+}
+
+impl Display for BuilderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuilderError::UnendedRound { name } => write!(f, "round {name} was never ended"),
+            BuilderError::NoPriorVerifierMessage { name } => {
+                write!(f, "round {name} does not follow a verifier message")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuilderError {}
+
 #[cfg(test)]
 mod tests {
-    use super::SecurityAssumption;
+    use std::f64::consts::LOG2_10;
+
+    use super::{OodStrategy, SecurityAssumption, SecurityError};
 
     #[test]
     fn test_ud_errors() {
@@ -300,4 +741,215 @@ mod tests {
 
         assert!((computed_error - real_error).abs() < 0.01);
     }
+
+    #[test]
+    fn test_eta_factor() {
+        let (log_degree, log_inv_rate, field_size_bits) = (20, 2, 128);
+
+        for assumption in [
+            SecurityAssumption::JohnsonBound,
+            SecurityAssumption::CapacityBound,
+        ] {
+            // η = √ρ/20 resp. ρ/20
+            let log_eta = match assumption {
+                SecurityAssumption::JohnsonBound => -(0.5 * log_inv_rate as f64 + LOG2_10 + 1.),
+                _ => -(log_inv_rate as f64 + LOG2_10 + 1.),
+            };
+            assert!((assumption.log_eta_with_factor(log_inv_rate, 20.) - log_eta).abs() < 0.01);
+
+            let default_error =
+                assumption.prox_gaps_error(log_degree, log_inv_rate, field_size_bits, 2);
+            let factor_error = assumption.with_eta_factor(20.).prox_gaps_error(
+                log_degree,
+                log_inv_rate,
+                field_size_bits,
+                2,
+            );
+            assert!((default_error - factor_error).abs() < 0.01);
+
+            // A smaller η yields larger lists
+            assert!(
+                assumption
+                    .with_eta_factor(100.)
+                    .list_size_bits(log_degree, log_inv_rate)
+                    > assumption.list_size_bits(log_degree, log_inv_rate)
+            );
+        }
+    }
+
+    #[test]
+    fn test_max_list_size_bits() {
+        // BabyBear with a quintic extension
+        let (log_degree, log_inv_rate, field_size_bits) = (20, 1, 135);
+        let assumption = SecurityAssumption::CapacityBound;
+
+        // A cap above the list size changes nothing
+        let loose = assumption.with_max_list_size_bits(100.);
+        assert_eq!(
+            loose.list_size_bits(log_degree, log_inv_rate),
+            assumption.list_size_bits(log_degree, log_inv_rate)
+        );
+        assert_eq!(
+            loose.prox_gaps_error(log_degree, log_inv_rate, field_size_bits, 2),
+            assumption.prox_gaps_error(log_degree, log_inv_rate, field_size_bits, 2)
+        );
+
+        let capped = assumption.with_max_list_size_bits(5.);
+        assert_eq!(capped.list_size_bits(log_degree, log_inv_rate), 5.);
+        assert!(
+            capped.prox_gaps_error(log_degree, log_inv_rate, field_size_bits, 2)
+                > assumption.prox_gaps_error(log_degree, log_inv_rate, field_size_bits, 2)
+        );
+        assert!(
+            capped.ood_error(
+                log_degree,
+                log_inv_rate,
+                field_size_bits,
+                1,
+                OodStrategy::Repeated
+            ) > assumption.ood_error(
+                log_degree,
+                log_inv_rate,
+                field_size_bits,
+                1,
+                OodStrategy::Repeated
+            )
+        );
+        assert_eq!(
+            assumption.determine_ood_samples(
+                100,
+                log_degree,
+                log_inv_rate,
+                field_size_bits,
+                OodStrategy::Repeated
+            ),
+            Ok(2)
+        );
+        assert_eq!(
+            capped.determine_ood_samples(
+                100,
+                log_degree,
+                log_inv_rate,
+                field_size_bits,
+                OodStrategy::Repeated
+            ),
+            Ok(1)
+        );
+    }
+
+    #[test]
+    fn test_determine_ood_samples() {
+        let (log_degree, log_inv_rate) = (20, 1);
+
+        assert_eq!(
+            SecurityAssumption::UniqueDecoding.determine_ood_samples(
+                100,
+                log_degree,
+                log_inv_rate,
+                128,
+                OodStrategy::Repeated
+            ),
+            Ok(0)
+        );
+
+        for assumption in [
+            SecurityAssumption::JohnsonBound,
+            SecurityAssumption::CapacityBound,
+        ] {
+            let ood_samples = assumption
+                .determine_ood_samples(100, log_degree, log_inv_rate, 128, OodStrategy::Repeated)
+                .unwrap();
+            assert!(ood_samples > 0);
+            assert!(
+                assumption.ood_error(
+                    log_degree,
+                    log_inv_rate,
+                    128,
+                    ood_samples,
+                    OodStrategy::Repeated
+                ) >= 100.
+            );
+
+            // Each sample costs more (degree) than it gains (field size)
+            assert_eq!(
+                assumption.determine_ood_samples(
+                    100,
+                    log_degree,
+                    log_inv_rate,
+                    8,
+                    OodStrategy::Repeated
+                ),
+                Err(SecurityError::InsufficientOodSamples {
+                    security_level: 100,
+                    field_size_bits: 8
+                })
+            );
+
+            // Sampling a single point pays for the degree only once, so it never needs more samples
+            let single_point_samples = assumption
+                .determine_ood_samples(100, log_degree, log_inv_rate, 128, OodStrategy::SinglePoint)
+                .unwrap();
+            assert!(single_point_samples <= ood_samples);
+            assert!(assumption
+                .determine_ood_samples(100, log_degree, log_inv_rate, 8, OodStrategy::SinglePoint)
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn test_prox_gaps_error_single_function() {
+        let (log_degree, log_inv_rate, field_size_bits) = (20, 1, 128);
+        for assumption in [
+            SecurityAssumption::UniqueDecoding,
+            SecurityAssumption::JohnsonBound,
+            SecurityAssumption::CapacityBound,
+        ] {
+            let single = assumption.prox_gaps_error(log_degree, log_inv_rate, field_size_bits, 1);
+            assert!(single.is_finite());
+            assert_eq!(
+                single,
+                assumption.prox_gaps_error(log_degree, log_inv_rate, field_size_bits, 2)
+            );
+        }
+    }
+
+    #[test]
+    fn test_query_schedule() {
+        let rates = [1, 2, 3, 4];
+        let ud_schedule = SecurityAssumption::UniqueDecoding
+            .query_schedule(100, &rates)
+            .unwrap();
+        let cb_schedule = SecurityAssumption::CapacityBound
+            .query_schedule(100, &rates)
+            .unwrap();
+
+        for ((rate, ud_queries), (_, cb_queries)) in ud_schedule.into_iter().zip(cb_schedule) {
+            assert!(ud_queries > cb_queries, "rate 2^-{rate}");
+        }
+
+        let errors = SecurityAssumption::CapacityBound
+            .queries_error_schedule(100, &rates)
+            .unwrap();
+        assert!(errors.iter().all(|&(_, error)| error >= 100.));
+    }
+
+    #[test]
+    fn test_no_redundancy() {
+        for assumption in [
+            SecurityAssumption::UniqueDecoding,
+            SecurityAssumption::JohnsonBound,
+            SecurityAssumption::CapacityBound,
+        ] {
+            assert_eq!(assumption.queries(100, 0), Err(SecurityError::NoRedundancy));
+            assert_eq!(
+                assumption.queries_error(0, 100),
+                Err(SecurityError::NoRedundancy)
+            );
+            assert_eq!(
+                assumption.query_schedule(100, &[1, 0]),
+                Err(SecurityError::NoRedundancy)
+            );
+            assert!(assumption.queries(100, 1).is_ok());
+        }
+    }
 }
@@ -1,7 +1,8 @@
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
 /// Field represents a field that we are working over.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Field {
     /// The name of the field for displaying.
     pub name: &'static str,
@@ -34,7 +35,78 @@ pub const BABYBEAR_5: Field = Field {
     extension_degree: 5,
 };
 
+/// The BabyBear field, using a quartic extension (usually combined with grinding)
+pub const BABYBEAR_4: Field = Field {
+    name: "Babybear",
+    field_size_bits: 27,
+    extension_degree: 4,
+};
+
+/// The Mersenne31 field, using a quartic extension for security
+pub const MERSENNE31: Field = Field {
+    name: "Mersenne31",
+    field_size_bits: 31,
+    extension_degree: 4,
+};
+
+/// The KoalaBear field, using a quartic extension for security
+pub const KOALABEAR_4: Field = Field {
+    name: "KoalaBear",
+    field_size_bits: 31,
+    extension_degree: 4,
+};
+
+/// The Stark-252 prime field, large enough to not need an extension
+pub const STARK_252: Field = Field {
+    name: "Stark252",
+    field_size_bits: 252,
+    extension_degree: 1,
+};
+
+/// The fields that can be looked up by name, see `from_name`.
+/// The first entry for a given field is also registered under the bare field name.
+const REGISTRY: [(&str, Field); 7] = [
+    ("goldilocks2", GOLDILOCKS_2),
+    ("goldilocks3", GOLDILOCKS_3),
+    ("babybear5", BABYBEAR_5),
+    ("babybear4", BABYBEAR_4),
+    ("mersenne31", MERSENNE31),
+    ("koalabear4", KOALABEAR_4),
+    ("stark252", STARK_252),
+];
+
+/// Looks up a field by name (case-insensitive), e.g. `babybear4` or `Goldilocks`.
+pub fn from_name(name: &str) -> Option<Field> {
+    let name = name.to_lowercase();
+    REGISTRY
+        .iter()
+        .find(|(key, field)| *key == name || field.name.to_lowercase() == name)
+        .map(|&(_, field)| field)
+}
+
 impl Field {
+    /// Creates a field at runtime, rejecting degenerate sizes.
+    pub fn new(
+        name: &'static str,
+        field_size_bits: usize,
+        extension_degree: usize,
+    ) -> Result<Self, String> {
+        if field_size_bits == 0 {
+            return Err(format!("Invalid field size for {name}: must be non-zero"));
+        }
+        if extension_degree == 0 {
+            return Err(format!(
+                "Invalid extension degree for {name}: must be non-zero"
+            ));
+        }
+
+        Ok(Field {
+            name,
+            field_size_bits,
+            extension_degree,
+        })
+    }
+
     pub fn extension_bit_size(&self) -> usize {
         self.extension_degree * self.field_size_bits
     }
@@ -49,3 +121,107 @@ impl Display for Field {
         )
     }
 }
+
+impl FromStr for Field {
+    type Err = String;
+
+    /// Parses the `Display` format, e.g. "2-extension of Goldilocks - 64 bits base".
+    /// The name must be one of the known fields, as it is stored as a `&'static str`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let malformed = || {
+            format!(
+                "Invalid field: {s} (expected \"<degree>-extension of <name> - <bits> bits base\")"
+            )
+        };
+
+        let (extension_degree, rest) = s
+            .trim()
+            .split_once("-extension of ")
+            .ok_or_else(malformed)?;
+        let (name, field_size_bits) = rest.rsplit_once(" - ").ok_or_else(malformed)?;
+        let field_size_bits = field_size_bits
+            .trim()
+            .strip_suffix("bits base")
+            .ok_or_else(malformed)?;
+
+        let extension_degree = extension_degree.trim().parse().map_err(|_| malformed())?;
+        let field_size_bits = field_size_bits.trim().parse().map_err(|_| malformed())?;
+        let name = name.trim();
+        let name = REGISTRY
+            .iter()
+            .map(|(_, field)| field.name)
+            .find(|known| known.eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("Unknown field name: {name}"))?;
+
+        Field::new(name, field_size_bits, extension_degree)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        from_name, Field, BABYBEAR_4, GOLDILOCKS_2, GOLDILOCKS_3, KOALABEAR_4, MERSENNE31,
+        REGISTRY, STARK_252,
+    };
+
+    #[test]
+    fn test_field_new() {
+        let field = Field::new("Mersenne31", 31, 4).unwrap();
+        assert_eq!(field.extension_bit_size(), MERSENNE31.extension_bit_size());
+    }
+
+    #[test]
+    fn test_field_new_zero_size() {
+        assert!(Field::new("Empty", 0, 2).is_err());
+    }
+
+    #[test]
+    fn test_field_new_zero_extension() {
+        assert!(Field::new("Goldilocks", 64, 0).is_err());
+    }
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(from_name("Goldilocks"), Some(GOLDILOCKS_2));
+        assert_eq!(from_name("GOLDILOCKS3"), Some(GOLDILOCKS_3));
+        assert_eq!(from_name("babybear4"), Some(BABYBEAR_4));
+        assert_eq!(from_name("mersenne31"), Some(MERSENNE31));
+        assert_eq!(from_name("KoalaBear"), Some(KOALABEAR_4));
+        assert_eq!(from_name("stark252"), Some(STARK_252));
+        assert_eq!(from_name("bn254"), None);
+    }
+
+    #[test]
+    fn test_prime_field_extension_bit_size() {
+        assert_eq!(STARK_252.extension_bit_size(), 252);
+    }
+
+    #[test]
+    fn test_from_str_roundtrip() {
+        for (_, field) in REGISTRY {
+            assert_eq!(field.to_string().parse::<Field>(), Ok(field));
+        }
+        assert_eq!(
+            "  3-extension of goldilocks -  64 bits base ".parse::<Field>(),
+            Ok(GOLDILOCKS_3)
+        );
+    }
+
+    #[test]
+    fn test_from_str_malformed() {
+        assert!("Goldilocks".parse::<Field>().is_err());
+        assert!("two-extension of Goldilocks - 64 bits base"
+            .parse::<Field>()
+            .is_err());
+        assert!("2-extension of Goldilocks - 64 bits"
+            .parse::<Field>()
+            .is_err());
+        assert!("2-extension of Goldilocks - 0 bits base"
+            .parse::<Field>()
+            .is_err());
+        assert_eq!(
+            "2-extension of BN254 - 254 bits base".parse::<Field>(),
+            Err("Unknown field name: BN254".to_owned())
+        );
+    }
+}
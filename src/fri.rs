@@ -1,14 +1,25 @@
-use std::fmt::Display;
+use std::{
+    fmt::{Display, Write},
+    ops::Range,
+};
 
 use crate::{
-    errors::SecurityAssumption,
+    errors::{OodStrategy, ParameterError, SecurityAssumption, SecurityError, DEFAULT_ETA_FACTOR},
+    field::MERSENNE31,
+    hash::DigestConfig,
     protocol::{
         builder::ProtocolBuilder,
-        proof_size::{FieldElements, MerkleQueries, MerkleTree, ProofElement},
-        Protocol, ProverMessage, RbRError, VerifierMessage,
+        proof_size::{
+            merkle_digest_bits, Encoding, FieldElements, InterleavingMode, MerkleQueries,
+            MerkleTree, ProofElement,
+        },
+        total_oracle_bits, Protocol, ProtocolReport, ProverMessage, RbRError, Report,
+        VerifierMessage,
+    },
+    utils::{
+        float_precision, pow_util, print_final_poly_warning, print_pow_warning, ROUNDS_CSV_HEADER,
     },
-    utils::pow_util,
-    LowDegreeParameters,
+    LowDegreeParameters, LowDegreeTest,
 };
 
 /// Parameters parametrizing an instance of FRI.
@@ -24,24 +35,186 @@ pub struct FriParameters {
 
     /// The folding factors in the remaining rounds.
     /// Given in log form, i.e. folding_factors[i] = 2 implies that the degree in round i is reduced by a factor of 4.
+    /// If empty, only the starting fold is done, and with a starting folding factor of 0 the polynomial is sent whole.
     pub folding_factors: Vec<usize>,
 
     /// The security assumption under which to configure FRI.
     pub security_assumption: SecurityAssumption,
 
+    /// Optionally, the security assumption to use in each round (falls back to `security_assumption` if empty).
+    pub security_assumptions: Vec<SecurityAssumption>,
+
+    /// Optionally, the divisor used to set η (see `SecurityAssumption::log_eta_with_factor`), defaults to `DEFAULT_ETA_FACTOR`.
+    pub eta_factor: Option<f64>,
+
+    /// Optionally, a cap on the (log) list size, see `SecurityAssumptionWithEta::with_max_list_size_bits`.
+    pub max_list_size_bits: Option<f64>,
+
     /// The security level desired.
     pub security_level: usize,
 
     /// The number of pow bits to use to reduce query error.
     /// Traditionally called also "grinding".
-    /// NOTE: This does not affect the pow bits used to reduce proximity gaps errors.
+    /// NOTE: This does not bound the pow bits used to reduce proximity gaps errors, see `folding_pow_bits`.
     pub pow_bits: usize,
 
-    /// The size of the digest for the Merkle tree
-    pub digest_size_bits: usize,
+    /// Optionally, the maximum number of pow bits used to reduce proximity gaps (folding and batching) errors.
+    /// If unset, these are ground up to the security level.
+    pub folding_pow_bits: Option<usize>,
+
+    /// Optionally, a hard cap on the pow bits of any grind (e.g. the most that is practical on the prover's hardware).
+    /// Unlike `pow_bits`, the queries are not increased to compensate, and so the protocol may fall short of
+    /// `security_level` (see `security_shortfall` in the config).
+    pub grinding_cap: Option<usize>,
+
+    /// The size of the digest for the Merkle tree. If `None`, digests are sized for the security level
+    /// (see `digest_size_bits_for_security_level`).
+    pub digest_size_bits: Option<usize>,
+
+    /// Optionally, the hash used for the Merkle digests, overriding `digest_size_bits` with its output size
+    /// and bounding the collision resistance of the digests.
+    pub digest: Option<DigestConfig>,
+
+    /// Whether grinding is forbidden, in which case no PoW is used and `pow_bits` is ignored.
+    pub no_grinding: bool,
+
+    /// Whether the commitments are zero-knowledge, in which case the Merkle leaves are salted.
+    pub zero_knowledge: bool,
+
+    /// How field elements are serialized in the proof.
+    pub encoding: Encoding,
+
+    /// How the batched polynomials are committed to in the starting commitment, see `InterleavingMode`.
+    pub interleaving: InterleavingMode,
+
+    /// The smallest (log) degree the final polynomial may have, guarding against folding all the way down by accident.
+    pub min_final_log_degree: usize,
+
+    /// Whether the queries to consecutive oracles are correlated (i.e. each query follows the folded index).
+    /// If so, the verifier recomputes one entry of each opened leaf (beyond the first oracle) from the previous
+    /// opening, and so that entry is not sent.
+    pub correlated_queries: bool,
+
+    /// Whether to run DEEP-FRI, i.e. sample out-of-domain evaluations of each new oracle (as STIR does).
+    pub deep: bool,
+
+    /// How the out-of-domain samples are drawn when running DEEP-FRI, see `OodStrategy`.
+    pub ood_strategy: OodStrategy,
+
+    /// Whether to run circle FRI (as in circle STARKs), over a coset of the circle group of Mersenne31 rather than
+    /// a multiplicative subgroup. Challenges are drawn from the extension, as in the line case.
+    /// This diverges from the line case only in the first fold, which maps the circle to the line by combining the
+    /// halves f(x, y) = f_0(x) + y * f_1(x): an extra halving of the domain and degree, with its own proximity gaps
+    /// error. It is opened together with the starting fold, so each leaf of the starting tree holds twice as many
+    /// evaluations. NOTE: The degree is that of the line polynomials after this fold, plus one (in log form).
+    pub circle: bool,
+
+    /// Optionally, the raw arity of each fold (the starting fold first), overriding the folding factors.
+    /// This allows arities which are not powers of two (e.g. 3 over a smooth multiplicative subgroup).
+    /// NOTE: The degree is tracked exactly (i.e. folding by 3 reduces the log degree by log2(3)), but the sizes
+    /// of the domains, trees and final polynomial (and the degrees used for the errors) are rounded up to powers of two.
+    pub folding_arities: Vec<usize>,
 }
 
 impl FriParameters {
+    /// The arity of each fold (the starting fold first), either given explicitly or derived from the folding factors.
+    /// Without folding rounds (and explicit arities) and a starting fold there are no folds at all,
+    /// see `FriProtocol::new`.
+    pub fn arities(&self) -> Vec<usize> {
+        if !self.folding_arities.is_empty() {
+            return self.folding_arities.clone();
+        }
+        if self.starting_folding_factor == 0 && self.folding_factors.is_empty() {
+            return vec![];
+        }
+        std::iter::once(self.starting_folding_factor)
+            .chain(self.folding_factors.iter().copied())
+            .map(|folding_factor| 1 << folding_factor)
+            .collect()
+    }
+
+    /// Checks the parameters against the LDT parameters, reporting every violation rather than only the first.
+    /// These are the checks done when constructing the protocol, see `FriProtocol::new`.
+    pub fn validate(
+        &self,
+        ldt_parameters: &LowDegreeParameters,
+    ) -> Result<(), Vec<ParameterError>> {
+        let mut errors = vec![];
+        errors.extend(ldt_parameters.validate().err());
+
+        // FRI only supports proximity testing
+        if ldt_parameters.constraint_degree != 0 {
+            errors.push(ParameterError::UnsupportedConstraintDegree {
+                constraint_degree: ldt_parameters.constraint_degree,
+            });
+        }
+
+        // Each fold needs to reduce the degree
+        let arities = self.arities();
+        if arities.iter().any(|&arity| arity < 2) {
+            errors.push(ParameterError::ZeroFoldingFactor);
+        }
+        let num_rounds = arities.len().saturating_sub(1);
+        if !self.security_assumptions.is_empty() && self.security_assumptions.len() != num_rounds {
+            errors.push(ParameterError::MismatchedRoundVectors);
+        }
+
+        // The circle group has order p + 1, which is smooth (i.e. 2^31) only for Mersenne31
+        if self.circle
+            && (
+                ldt_parameters.field.name,
+                ldt_parameters.field.field_size_bits,
+            ) != (MERSENNE31.name, MERSENNE31.field_size_bits)
+        {
+            errors.push(ParameterError::UnsupportedField {
+                field_name: ldt_parameters.field.name,
+            });
+        }
+
+        // We cannot fold too much
+        let total_reduction = self.log_degree_reduction();
+        if total_reduction > ldt_parameters.log_degree as f64 {
+            errors.push(ParameterError::OverFolded {
+                total_reduction: total_reduction.ceil() as usize,
+                log_degree: ldt_parameters.log_degree,
+            });
+        } else {
+            let final_log_degree =
+                (ldt_parameters.log_degree as f64 - total_reduction).ceil() as usize;
+            if final_log_degree < self.min_final_log_degree {
+                errors.push(ParameterError::FinalDegreeTooSmall {
+                    final_log_degree,
+                    min_final_log_degree: self.min_final_log_degree,
+                });
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// The (log) reduction in degree over all the folds, including the one from the circle to the line in circle FRI.
+    pub fn log_degree_reduction(&self) -> f64 {
+        let circle_reduction = if self.circle { 1. } else { 0. };
+        circle_reduction
+            + self
+                .arities()
+                .iter()
+                .map(|&arity| log_arity(arity))
+                .sum::<f64>()
+    }
+
+    /// The security assumption used in the given round.
+    pub fn round_security_assumption(&self, round: usize) -> SecurityAssumption {
+        self.security_assumptions
+            .get(round)
+            .copied()
+            .unwrap_or(self.security_assumption)
+    }
+
     /// Instantiate a FRI configuration where each round does a fixed amount of folding.
     pub fn fixed_folding(
         log_inv_rate: usize,
@@ -57,9 +230,25 @@ impl FriParameters {
             starting_folding_factor: folding_factor,
             folding_factors: vec![folding_factor; num_rounds],
             security_assumption,
+            security_assumptions: vec![],
+            eta_factor: None,
+            max_list_size_bits: None,
             security_level,
             pow_bits,
-            digest_size_bits,
+            digest_size_bits: Some(digest_size_bits),
+            digest: None,
+            no_grinding: false,
+            folding_pow_bits: None,
+            grinding_cap: None,
+            zero_knowledge: false,
+            encoding: Encoding::default(),
+            interleaving: InterleavingMode::default(),
+            min_final_log_degree: 0,
+            correlated_queries: false,
+            deep: false,
+            ood_strategy: OodStrategy::default(),
+            circle: false,
+            folding_arities: vec![],
         }
     }
 }
@@ -73,107 +262,206 @@ pub struct FriProtocol {
 
 impl FriProtocol {
     /// Given a LDT parameter and some parameters for FRI, populate the config.
-    pub fn new(ldt_parameters: LowDegreeParameters, fri_parameters: FriParameters) -> Self {
-        // FRI only supports proximity testing
-        assert_eq!(ldt_parameters.constraint_degree, 0);
-
-        // We need to fold at least some time
-        assert!(
-            fri_parameters.starting_folding_factor > 0
-                && fri_parameters.folding_factors.iter().all(|&x| x > 0),
-            "folding factors should be non zero"
-        );
-
-        // We cannot fold too much
-        let total_reduction = fri_parameters.starting_folding_factor
-            + fri_parameters.folding_factors.iter().sum::<usize>();
-        assert!(total_reduction <= ldt_parameters.log_degree);
-
-        // If less, just send the damn polynomials
-        assert!(ldt_parameters.log_degree >= fri_parameters.folding_factors[0]);
+    /// Without any folds (i.e. no folding rounds), this is the trivial LDT: the prover sends the whole polynomial,
+    /// which the verifier checks against the committed evaluations at the queried points.
+    pub fn new(
+        ldt_parameters: LowDegreeParameters,
+        fri_parameters: FriParameters,
+    ) -> Result<Self, ParameterError> {
+        // Check the parameters, reporting the first violation
+        if let Err(errors) = fri_parameters.validate(&ldt_parameters) {
+            return Err(errors[0]);
+        }
+        let arities = fri_parameters.arities();
+        let final_log_degree = (ldt_parameters.log_degree as f64
+            - fri_parameters.log_degree_reduction())
+        .ceil() as usize;
 
-        // Compute the number of rounds and the leftover
-        let final_log_degree = ldt_parameters.log_degree - total_reduction;
-        let num_rounds = fri_parameters.folding_factors.len();
+        // Compute the number of rounds
+        let num_rounds = arities.len().saturating_sub(1);
 
         // Compute the security level
         let security_level = fri_parameters.security_level;
-        let protocol_security_level =
-            0.max(fri_parameters.security_level - fri_parameters.pow_bits);
+        // Without grinding, the queries alone reach the security level and no PoW is used anywhere
+        let max_pow_bits = if fri_parameters.no_grinding {
+            0
+        } else {
+            fri_parameters.pow_bits
+        };
+        let protocol_security_level = security_level.saturating_sub(max_pow_bits);
+        let pow_security_level = if fri_parameters.no_grinding {
+            0
+        } else {
+            security_level
+        };
+        // The PoW for the proximity gaps errors is capped separately from the one for the queries
+        let folding_pow_cap = fri_parameters
+            .folding_pow_bits
+            .map_or(f64::INFINITY, |bits| bits as f64);
+        let grinding_cap = fri_parameters.grinding_cap;
+        let folding_pow_util =
+            |error: f64| pow_util(pow_security_level, error, grinding_cap).min(folding_pow_cap);
+
+        // The security assumption (and choice of η) used to compute the errors
+        let eta_factor = fri_parameters.eta_factor.unwrap_or(DEFAULT_ETA_FACTOR);
+        let security_assumption = fri_parameters
+            .security_assumption
+            .with_eta_factor(eta_factor)
+            .with_list_size_cap(fri_parameters.max_list_size_bits);
 
         // Initial domain size (the trace domain)
-        let starting_folding_factor = fri_parameters.starting_folding_factor;
+        // An arity of 1 stands for not folding at all
+        let starting_arity = arities.first().copied().unwrap_or(1);
+        // In circle FRI, the fold from the circle to the line halves the domain before the starting fold
+        let circle_arity = if fri_parameters.circle { 2 } else { 1 };
         let starting_domain_log_size =
             ldt_parameters.log_degree + fri_parameters.starting_log_inv_rate;
 
-        let mut protocol_builder =
-            ProtocolBuilder::new("FRI protocol", fri_parameters.digest_size_bits);
+        let (digest_size_bits, collision_bits) = merkle_digest_bits(
+            fri_parameters.digest,
+            fri_parameters.digest_size_bits,
+            security_level,
+        );
+
+        // In zero-knowledge mode, each leaf is salted with randomness
+        let salt_bits = if fri_parameters.zero_knowledge {
+            2 * security_level
+        } else {
+            0
+        };
+
+        // How field elements are serialized in the proof
+        let encoding = fri_parameters.encoding;
+        let interleaving = fri_parameters.interleaving;
+        let ood_strategy = fri_parameters.ood_strategy;
+
+        // Each verifier challenge (other than the query indices) is an extension field element
+        let challenge_bits = ldt_parameters.field.extension_bit_size();
+
+        let mut protocol_builder = ProtocolBuilder::new("FRI protocol", digest_size_bits)
+            .with_collision_bits(collision_bits);
 
         // Pow bits for the batching steps
         let mut batching_pow_bits = 0.;
         if ldt_parameters.batch_size > 1 {
-            let prox_gaps_error_batching = fri_parameters.security_assumption.prox_gaps_error(
+            let prox_gaps_error_batching = security_assumption.prox_gaps_error(
                 ldt_parameters.log_degree,
                 fri_parameters.starting_log_inv_rate,
                 ldt_parameters.field.extension_bit_size(),
                 ldt_parameters.batch_size,
             ); // we now start, the initial folding pow bits
-            batching_pow_bits = pow_util(security_level, prox_gaps_error_batching);
+            batching_pow_bits = folding_pow_util(prox_gaps_error_batching);
 
-            // Add the round for the batching
+            // Add the round for the batching.
+            // NOTE: No commitment to the combined codeword is needed, as the starting tree stores the evaluations
+            // of all the batched functions in each leaf, from which the verifier derives the combination.
             protocol_builder = protocol_builder
                 .start_round("batching_round")
-                .verifier_message(VerifierMessage::new(
-                    vec![RbRError::new("batching_error", prox_gaps_error_batching)],
-                    batching_pow_bits,
-                ))
+                .verifier_message(
+                    VerifierMessage::new(
+                        vec![RbRError::new("batching_error", prox_gaps_error_batching)],
+                        batching_pow_bits,
+                    )
+                    .with_challenge_bits(challenge_bits),
+                )
                 .end_round();
         }
 
         // Merkle tree committed to
-        let starting_merkle_tree = MerkleTree::new(
-            starting_domain_log_size - starting_folding_factor,
+        let starting_merkle_tree = MerkleTree::with_digest_size(
+            (starting_domain_log_size as f64 - log_arity(circle_arity * starting_arity)).ceil()
+                as usize,
             ldt_parameters.field,
-            (1 << starting_folding_factor) * ldt_parameters.batch_size,
+            circle_arity * starting_arity * interleaving.polys_per_tree(ldt_parameters.batch_size),
             false, // first tree is over the base
-        );
+            digest_size_bits,
+        )
+        .with_salt_bits(salt_bits)
+        .with_encoding(encoding);
         let mut commitments = vec![starting_merkle_tree];
 
         // Degree of next polynomial to send
-        let mut current_log_degree = ldt_parameters.log_degree - starting_folding_factor;
+        // This is tracked exactly, and rounded up wherever a size is needed
+        let mut current_log_degree = ldt_parameters.log_degree as f64 - log_arity(circle_arity);
+
+        // In circle FRI, the verifier first folds the circle to the line
+        let mut circle_folding_pow_bits = 0.;
+        if fri_parameters.circle {
+            let circle_folding_prox_gaps_error = security_assumption.prox_gaps_error(
+                current_log_degree.ceil() as usize,
+                fri_parameters.starting_log_inv_rate,
+                ldt_parameters.field.extension_bit_size(),
+                circle_arity,
+            );
+            circle_folding_pow_bits = folding_pow_util(circle_folding_prox_gaps_error);
+            protocol_builder = protocol_builder
+                .start_round("circle_fold")
+                .verifier_message(
+                    VerifierMessage::new(
+                        vec![RbRError::new(
+                            "circle_folding_error",
+                            circle_folding_prox_gaps_error,
+                        )],
+                        circle_folding_pow_bits,
+                    )
+                    .with_challenge_bits(challenge_bits),
+                )
+                .end_round();
+        }
+        current_log_degree -= log_arity(starting_arity);
 
         // we now start, the initial folding pow bits
-        let starting_folding_prox_gaps_error = fri_parameters.security_assumption.prox_gaps_error(
-            current_log_degree,
-            fri_parameters.starting_log_inv_rate,
-            ldt_parameters.field.extension_bit_size(),
-            1 << starting_folding_factor,
-        );
-        let starting_folding_pow_bits = pow_util(security_level, starting_folding_prox_gaps_error);
-        protocol_builder = protocol_builder
-            .start_round("initial_iteration")
-            .verifier_message(VerifierMessage::new(
-                vec![RbRError::new(
-                    "folding_error",
-                    starting_folding_prox_gaps_error,
-                )],
-                starting_folding_pow_bits,
-            ))
-            .end_round();
+        let mut starting_folding_pow_bits = 0.;
+        if starting_arity > 1 {
+            let starting_folding_prox_gaps_error = security_assumption.prox_gaps_error(
+                current_log_degree.ceil() as usize,
+                fri_parameters.starting_log_inv_rate,
+                ldt_parameters.field.extension_bit_size(),
+                starting_arity,
+            );
+            starting_folding_pow_bits = folding_pow_util(starting_folding_prox_gaps_error);
+            protocol_builder = protocol_builder
+                .start_round("initial_iteration")
+                .verifier_message(
+                    VerifierMessage::new(
+                        vec![RbRError::new(
+                            "folding_error",
+                            starting_folding_prox_gaps_error,
+                        )],
+                        starting_folding_pow_bits,
+                    )
+                    .with_challenge_bits(challenge_bits),
+                )
+                .end_round();
+        }
 
         let mut round_parameters = Vec::with_capacity(num_rounds);
 
-        for folding_factor in fri_parameters.folding_factors.into_iter() {
+        let round_security_assumptions: Vec<_> = (0..num_rounds)
+            .map(|i| {
+                fri_parameters
+                    .round_security_assumption(i)
+                    .with_eta_factor(eta_factor)
+                    .with_list_size_cap(fri_parameters.max_list_size_bits)
+            })
+            .collect();
+
+        for (&arity, round_security_assumption) in
+            arities.iter().skip(1).zip(round_security_assumptions)
+        {
             let new_evaluation_domain_size =
-                current_log_degree + fri_parameters.starting_log_inv_rate;
+                current_log_degree + fri_parameters.starting_log_inv_rate as f64;
 
             // Send the new oracle
-            let current_merkle_tree = MerkleTree::new(
-                new_evaluation_domain_size - folding_factor,
+            let current_merkle_tree = MerkleTree::with_digest_size(
+                (new_evaluation_domain_size - log_arity(arity)).ceil() as usize,
                 ldt_parameters.field,
-                1 << folding_factor,
+                arity,
                 true,
-            );
+                digest_size_bits,
+            )
+            .with_salt_bits(salt_bits)
+            .with_encoding(encoding);
             protocol_builder = protocol_builder
                 .start_round("fri_iteration")
                 .prover_message(ProverMessage::new(ProofElement::MerkleRoot(
@@ -181,79 +469,160 @@ impl FriProtocol {
                 )));
             commitments.push(current_merkle_tree);
 
-            let prox_gaps_error = fri_parameters.security_assumption.prox_gaps_error(
-                current_log_degree - folding_factor,
+            // In DEEP-FRI, the verifier samples OOD evaluations of the new oracle
+            let ood_samples = if fri_parameters.deep {
+                round_security_assumption.determine_ood_samples(
+                    security_level,
+                    current_log_degree.ceil() as usize,
+                    fri_parameters.starting_log_inv_rate,
+                    ldt_parameters.field.extension_bit_size(),
+                    ood_strategy,
+                )?
+            } else {
+                0
+            };
+
+            // Add OOD rounds to protocol
+            if ood_samples > 0 {
+                let ood_error = round_security_assumption.ood_error(
+                    current_log_degree.ceil() as usize,
+                    fri_parameters.starting_log_inv_rate,
+                    ldt_parameters.field.extension_bit_size(),
+                    ood_samples,
+                    ood_strategy,
+                );
+
+                protocol_builder = protocol_builder
+                    .verifier_message(
+                        VerifierMessage::new(vec![RbRError::new("ood_error", ood_error)], 0.)
+                            .with_challenge_bits(ood_samples * challenge_bits),
+                    )
+                    .prover_message(ProverMessage::new(ProofElement::FieldElements(
+                        FieldElements::extension(ldt_parameters.field, ood_samples)
+                            .with_encoding(encoding),
+                    )));
+            }
+
+            let prox_gaps_error = round_security_assumption.prox_gaps_error(
+                (current_log_degree - log_arity(arity)).ceil() as usize,
                 fri_parameters.starting_log_inv_rate,
                 ldt_parameters.field.extension_bit_size(),
-                1 << folding_factor,
+                arity,
             );
 
             // Now compute the PoW
-            let pow_bits = pow_util(security_level, prox_gaps_error);
+            let pow_bits = folding_pow_util(prox_gaps_error);
 
             protocol_builder = protocol_builder
-                .verifier_message(VerifierMessage::new(
-                    vec![RbRError::new("folding_error", prox_gaps_error)],
-                    pow_bits,
-                ))
+                .verifier_message(
+                    VerifierMessage::new(
+                        vec![RbRError::new("folding_error", prox_gaps_error)],
+                        pow_bits,
+                    )
+                    .with_challenge_bits(challenge_bits),
+                )
                 .end_round();
 
             let round_config = RoundConfig {
-                evaluation_domain_log_size: new_evaluation_domain_size,
-                folding_factor,
+                evaluation_domain_log_size: new_evaluation_domain_size.ceil() as usize,
+                folding_factor: log_arity(arity).ceil() as usize,
+                arity,
+                security_assumption: round_security_assumption.assumption,
                 folding_pow_bits: pow_bits,
+                ood_samples,
             };
             round_parameters.push(round_config);
 
-            current_log_degree -= folding_factor;
+            current_log_degree -= log_arity(arity);
         }
 
+        // The queries are indices into the starting tree, from which the indices into the later trees are derived
+        let query_index_bits = commitments[0].tree_depth;
+
         // Compute the number of queries required
-        let final_queries = fri_parameters.security_assumption.queries(
+        let final_queries = security_assumption.queries(
             protocol_security_level,
             fri_parameters.starting_log_inv_rate,
-        );
+        )?;
 
-        // We need to compute the errors, to compute the according PoW
-        let query_error = fri_parameters
-            .security_assumption
-            .queries_error(fri_parameters.starting_log_inv_rate, final_queries);
+        // We need to compute the errors, to compute the according PoW.
+        let query_error = security_assumption
+            .queries_error(fri_parameters.starting_log_inv_rate, final_queries)?;
 
         // Now compute the PoW
-        let final_pow_bits = pow_util(security_level, query_error);
+        let final_pow_bits = pow_util(pow_security_level, query_error, grinding_cap);
 
         protocol_builder = protocol_builder
             .start_round("query_round")
-            .verifier_message(VerifierMessage::new(
-                vec![RbRError::new("query_error", query_error)],
-                final_pow_bits,
-            ))
-            .prover_message(ProverMessage::new(ProofElement::FieldElements(
-                FieldElements {
-                    field: ldt_parameters.field,
-                    num_elements: 1 << final_log_degree,
-                    is_extension: true,
-                },
-            )));
-
-        for current_merkle_tree in commitments {
-            // The queries
-            protocol_builder = protocol_builder.prover_message(ProverMessage::new(
-                ProofElement::MerkleQueries(MerkleQueries {
-                    merkle_tree: current_merkle_tree,
-                    num_openings: final_queries,
-                }),
+            .verifier_message(
+                VerifierMessage::new(
+                    vec![RbRError::new("query_error", query_error)],
+                    final_pow_bits,
+                )
+                .with_challenge_bits(final_queries * query_index_bits),
+            )
+            .prover_message(ProverMessage::final_polynomial(
+                FieldElements::extension(ldt_parameters.field, 1 << final_log_degree)
+                    .with_encoding(encoding),
             ));
+
+        let mut committed_trees = Vec::with_capacity(commitments.len());
+        for (i, mut current_merkle_tree) in commitments.into_iter().enumerate() {
+            // With correlated queries, the query to oracle i + 1 lands on the coset containing the fold of
+            // the coset opened in oracle i. Hence, of the 2^k entries of each opened leaf (after the first oracle)
+            // only 2^k - 1 are sent, i.e. size = q * ((2^k - 1) * |F| + copath) for each such oracle.
+            if fri_parameters.correlated_queries && i > 0 {
+                current_merkle_tree.leaf.num_elements -= 1;
+            }
+            committed_trees.push(current_merkle_tree);
+
+            // The queries, to each of the starting trees if the batched polynomials are committed to separately
+            let num_trees = if i == 0 {
+                interleaving.num_trees(ldt_parameters.batch_size)
+            } else {
+                1
+            };
+            for _ in 0..num_trees {
+                protocol_builder = protocol_builder.prover_message(ProverMessage::new(
+                    ProofElement::MerkleQueries(MerkleQueries {
+                        merkle_tree: current_merkle_tree,
+                        num_openings: final_queries,
+                        shared_leaves: 1,
+                    }),
+                ));
+            }
         }
 
-        FriProtocol {
+        let protocol = protocol_builder.end_round().build();
+        // Unless some grind is capped or forbidden, every round reaches the security level
+        let security_shortfall = 0f64.max(security_level as f64 - protocol.rbr_error());
+
+        Ok(FriProtocol {
             config: FriConfig {
                 ldt_parameters,
                 security_assumption: fri_parameters.security_assumption,
                 security_level,
-                max_pow_bits: fri_parameters.pow_bits,
+                max_pow_bits,
+                no_grinding: fri_parameters.no_grinding,
+                max_folding_pow_bits: fri_parameters.folding_pow_bits,
+                grinding_cap: fri_parameters.grinding_cap,
+                security_shortfall,
+                eta_factor,
+                max_list_size_bits: fri_parameters.max_list_size_bits,
+                digest_size_bits: fri_parameters.digest_size_bits,
+                digest: fri_parameters.digest,
+                zero_knowledge: fri_parameters.zero_knowledge,
+                min_final_log_degree: fri_parameters.min_final_log_degree,
+                correlated_queries: fri_parameters.correlated_queries,
+                deep: fri_parameters.deep,
+                ood_strategy,
+                circle: fri_parameters.circle,
+                circle_folding_pow_bits,
+                encoding,
+                interleaving,
                 batching_pow_bits,
-                starting_folding_factor,
+                starting_folding_factor: log_arity(starting_arity).ceil() as usize,
+                starting_arity,
                 starting_domain_log_size,
                 log_inv_rate: fri_parameters.starting_log_inv_rate,
                 starting_folding_pow_bits,
@@ -261,21 +630,89 @@ impl FriProtocol {
                 queries: final_queries,
                 pow_bits: final_pow_bits,
                 final_poly_log_degree: final_log_degree,
+                committed_trees,
             },
-            protocol: protocol_builder.end_round().build(),
-        }
+            protocol,
+        })
+    }
+}
+
+/// The (log) reduction in degree of a fold of the given arity.
+fn log_arity(arity: usize) -> f64 {
+    (arity as f64).log2()
+}
+
+/// Configures FRI at each of the given starting rates, returning `(log_inv_rate, proof_size_bits, achieved_security)`
+/// for each rate sorted by proof size.
+/// Rates at which FRI cannot be configured are skipped.
+pub fn rate_sweep(
+    ldt_parameters: LowDegreeParameters,
+    base: FriParameters,
+    rates: Range<usize>,
+) -> Vec<(usize, usize, f64)> {
+    let mut results: Vec<_> = rates
+        .filter_map(|log_inv_rate| {
+            let fri_parameters = FriParameters {
+                starting_log_inv_rate: log_inv_rate,
+                ..base.clone()
+            };
+            let fri_protocol = FriProtocol::new(ldt_parameters, fri_parameters).ok()?;
+            Some((
+                log_inv_rate,
+                fri_protocol.protocol.proof_size_bits(),
+                fri_protocol.protocol.rbr_error(),
+            ))
+        })
+        .collect();
+
+    results.sort_by_key(|&(_, proof_size_bits, _)| proof_size_bits);
+    results
+}
+
+impl FriProtocol {
+    /// Serializes the configuration and the round-by-round proof sizes to JSON.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::json!({
+            "config": self.config,
+            "protocol": self.protocol.to_json_value(),
+        })
+        .to_string()
+    }
+}
+
+impl LowDegreeTest for FriProtocol {
+    fn protocol(&self) -> &Protocol {
+        &self.protocol
+    }
+}
+
+impl Report for FriProtocol {
+    fn report(&self) -> ProtocolReport {
+        ProtocolReport::new(
+            &self.protocol,
+            self.config.queries,
+            self.config.round_parameters.len(),
+            self.config.final_poly_log_degree,
+        )
     }
 }
 
 impl Display for FriProtocol {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.config.fmt(f)?;
-        self.protocol.fmt(f)
+        self.protocol.fmt(f)?;
+        print_final_poly_warning(
+            f,
+            self.config.final_poly_size_bits(),
+            self.protocol.proof_size_bits(),
+        )
     }
 }
 
 /// A fully expanded FRI configuration.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct FriConfig {
     /// The configuration for the LDT desired.
     pub ldt_parameters: LowDegreeParameters,
@@ -289,14 +726,68 @@ pub struct FriConfig {
     /// The maximum number of pow bits allowed (over and we throw a warning, as probably then we are misconfigured.)
     pub max_pow_bits: usize,
 
+    /// Whether grinding was forbidden.
+    pub no_grinding: bool,
+
+    /// The maximum number of pow bits used to reduce proximity gaps errors, if capped.
+    pub max_folding_pow_bits: Option<usize>,
+
+    /// The cap on the pow bits of any grind, if given.
+    pub grinding_cap: Option<usize>,
+
+    /// How many bits the round-by-round soundness falls short of the security level, i.e. `security_level - rbr_error`
+    /// (if positive). This includes any shortfall due to `grinding_cap`, `max_folding_pow_bits` or `no_grinding`.
+    pub security_shortfall: f64,
+
+    /// The divisor used to set η.
+    pub eta_factor: f64,
+
+    /// The cap on the (log) list size, if any.
+    pub max_list_size_bits: Option<f64>,
+
+    /// The size of the digest for the Merkle trees, unless sized for the security level.
+    pub digest_size_bits: Option<usize>,
+
+    /// The hash used for the Merkle digests, if given.
+    pub digest: Option<DigestConfig>,
+
+    /// Whether the commitments are zero-knowledge.
+    pub zero_knowledge: bool,
+
+    /// The smallest (log) degree the final polynomial was allowed to have.
+    pub min_final_log_degree: usize,
+
+    /// Whether the queries to consecutive oracles were correlated.
+    pub correlated_queries: bool,
+
+    /// Whether DEEP-FRI was run.
+    pub deep: bool,
+
+    /// How the out-of-domain samples were drawn when running DEEP-FRI.
+    pub ood_strategy: OodStrategy,
+
+    /// Whether circle FRI was run.
+    pub circle: bool,
+
+    /// The pow bits used in the fold from the circle to the line (only in circle FRI).
+    pub circle_folding_pow_bits: f64,
+
+    /// How field elements are serialized in the proof.
+    pub encoding: Encoding,
+
+    /// How the batched polynomials were committed to in the starting commitment.
+    pub interleaving: InterleavingMode,
+
     /// The rate of the RS codes used during the protocol.    
     pub log_inv_rate: usize,
 
     /// The pow bits used in the batching phase.
     pub batching_pow_bits: f64,
 
-    /// The initial folding factor.
+    /// The initial folding factor (rounded up if the arity is not a power of two).
     pub starting_folding_factor: usize,
+    /// The initial arity, i.e. 2^starting_folding_factor unless given explicitly.
+    pub starting_arity: usize,
     /// The initial domain size
     pub starting_domain_log_size: usize,
     /// The initial pow bits used in the first fold.
@@ -313,22 +804,205 @@ pub struct FriConfig {
 
     /// Number of bits of proof of work (for the queries).
     pub pow_bits: f64,
+
+    /// The Merkle trees committed to (the starting one first), as opened in the query phase.
+    pub committed_trees: Vec<MerkleTree>,
 }
 
 /// Round specific configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct RoundConfig {
-    /// Folding factor for this round.
+    /// Folding factor for this round (rounded up if the arity is not a power of two).
     pub folding_factor: usize,
+    /// Arity of the fold in this round, i.e. 2^folding_factor unless given explicitly.
+    pub arity: usize,
+    /// The security assumption used in this round.
+    pub security_assumption: SecurityAssumption,
     /// Size of evaluation domain.
     pub evaluation_domain_log_size: usize,
     /// Number of folding pow_bits.
     pub folding_pow_bits: f64,
+    /// Number of OOD samples in this round (only in DEEP-FRI).
+    pub ood_samples: usize,
 }
 
 impl FriConfig {
+    /// Reconstructs the parameters this configuration was built from, such that `FriProtocol::new` with these
+    /// parameters (and the same LDT parameters) yields this configuration again.
+    /// The folds are given as explicit arities, as a single fold (without folding rounds) cannot be given by factors.
+    /// NOTE: The digest size is the one used, i.e. after capping it for the security level.
+    pub fn to_parameters(&self) -> FriParameters {
+        let folding_arities = if self.starting_arity > 1 {
+            std::iter::once(self.starting_arity)
+                .chain(self.round_parameters.iter().map(|round| round.arity))
+                .collect()
+        } else {
+            vec![]
+        };
+
+        FriParameters {
+            starting_log_inv_rate: self.log_inv_rate,
+            starting_folding_factor: self.starting_folding_factor,
+            folding_factors: self
+                .round_parameters
+                .iter()
+                .map(|round| round.folding_factor)
+                .collect(),
+            security_assumption: self.security_assumption,
+            security_assumptions: self
+                .round_parameters
+                .iter()
+                .map(|round| round.security_assumption)
+                .collect(),
+            eta_factor: Some(self.eta_factor),
+            max_list_size_bits: self.max_list_size_bits,
+            security_level: self.security_level,
+            pow_bits: self.max_pow_bits,
+            folding_pow_bits: self.max_folding_pow_bits,
+            grinding_cap: self.grinding_cap,
+            digest_size_bits: self.digest_size_bits,
+            digest: self.digest,
+            no_grinding: self.no_grinding,
+            zero_knowledge: self.zero_knowledge,
+            encoding: self.encoding,
+            interleaving: self.interleaving,
+            min_final_log_degree: self.min_final_log_degree,
+            correlated_queries: self.correlated_queries,
+            deep: self.deep,
+            ood_strategy: self.ood_strategy,
+            circle: self.circle,
+            folding_arities,
+        }
+    }
+
+    /// The (log) reduction in degree over all the folds (including the one from the circle to the line in circle FRI),
+    /// which is fractional if some arity is not a power of two.
+    pub fn log_degree_reduction(&self) -> f64 {
+        let circle_arity = if self.circle { 2 } else { 1 };
+        std::iter::once(circle_arity * self.starting_arity)
+            .chain(self.round_parameters.iter().map(|r| r.arity))
+            .map(log_arity)
+            .sum()
+    }
+
+    /// The total size in bits of the oracles committed to by the prover, see `protocol::total_oracle_bits`.
+    pub fn total_oracle_bits(&self) -> usize {
+        total_oracle_bits(
+            self.ldt_parameters,
+            std::iter::once(self.starting_domain_log_size).chain(
+                self.round_parameters
+                    .iter()
+                    .map(|r| r.evaluation_domain_log_size),
+            ),
+        )
+    }
+
+    /// Computes the error (in bits, before PoW) of the final queries against each committed tree (the starting one first).
+    /// The queries to every tree are derived from the same indices, so each tree gets `queries` queries at its own rate.
+    /// As the domain shrinks with the degree, every tree is at (least at) the starting rate, for which the queries
+    /// were configured. The rate exceeds it only if some arity is not a power of two, as the domains are then rounded up.
+    /// The (fractional) rate of each tree is rounded down, so that the error is a lower bound.
+    pub fn query_error_per_tree(&self) -> Result<Vec<f64>, SecurityError> {
+        let security_assumption = self.security_assumption.with_eta_factor(self.eta_factor);
+
+        // The circle fold happens before the starting fold
+        let circle_arity = if self.circle { 2 } else { 1 };
+        let mut current_log_degree =
+            self.ldt_parameters.log_degree as f64 - log_arity(circle_arity * self.starting_arity);
+        let mut log_inv_rates = vec![self.log_inv_rate];
+        for round in &self.round_parameters {
+            log_inv_rates.push(
+                (round.evaluation_domain_log_size as f64 - current_log_degree).floor() as usize,
+            );
+            current_log_degree -= log_arity(round.arity);
+        }
+
+        log_inv_rates
+            .into_iter()
+            .map(|log_inv_rate| security_assumption.queries_error(log_inv_rate, self.queries))
+            .collect()
+    }
+
+    /// The size in bits contributed to the query phase by each committed tree (the starting one first).
+    /// The starting tree is over the base field (with a leaf per batched function), the others over the extension.
+    /// If the batched functions are committed to separately, the starting entry accounts for all of their trees.
+    pub fn commitment_sizes(&self) -> Vec<usize> {
+        self.committed_trees
+            .iter()
+            .enumerate()
+            .map(|(i, &merkle_tree)| {
+                let num_trees = if i == 0 {
+                    self.interleaving.num_trees(self.ldt_parameters.batch_size)
+                } else {
+                    1
+                };
+                num_trees
+                    * MerkleQueries {
+                        merkle_tree,
+                        num_openings: self.queries,
+                        shared_leaves: 1,
+                    }
+                    .estimate_size_bits()
+            })
+            .collect()
+    }
+
+    /// The size in bits of the final polynomial, which is sent in the clear.
+    pub fn final_poly_size_bits(&self) -> usize {
+        FieldElements::extension(self.ldt_parameters.field, 1 << self.final_poly_log_degree)
+            .with_encoding(self.encoding)
+            .size_bits()
+    }
+
+    /// Returns the (labeled) PoW values exceeding `max_pow_bits`, which likely signal a misconfiguration.
+    pub fn pow_bits_exceeded(&self) -> Vec<(String, f64)> {
+        let mut pow_bits = vec![
+            ("batching".to_string(), self.batching_pow_bits),
+            (
+                "starting folding".to_string(),
+                self.starting_folding_pow_bits,
+            ),
+        ];
+        for (i, r) in self.round_parameters.iter().enumerate() {
+            pow_bits.push((format!("round {i} folding"), r.folding_pow_bits));
+        }
+        pow_bits.push(("final".to_string(), self.pow_bits));
+
+        pow_bits.retain(|&(_, bits)| bits > self.max_pow_bits as f64);
+        pow_bits
+    }
+
+    /// Exports the round-specific parameters as CSV, with one row per round followed by the final round.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        writeln!(csv, "{ROUNDS_CSV_HEADER}").unwrap();
+        // The queries are all made in the final round
+        for (i, r) in self.round_parameters.iter().enumerate() {
+            writeln!(
+                csv,
+                "{},{},{},{},0,{},{:.1}",
+                i,
+                r.folding_factor,
+                r.evaluation_domain_log_size,
+                self.log_inv_rate,
+                r.ood_samples,
+                r.folding_pow_bits
+            )
+            .unwrap();
+        }
+        writeln!(
+            csv,
+            "final,,,{},{},0,{:.1}",
+            self.log_inv_rate, self.queries, self.pow_bits
+        )
+        .unwrap();
+        csv
+    }
+
     // Prints a summary of the configuration for FRI.
     pub fn print_config_summary(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let precision = float_precision(f);
         writeln!(f, "{}", self.ldt_parameters)?;
         writeln!(
             f,
@@ -338,21 +1012,29 @@ impl FriConfig {
 
         writeln!(
             f,
-            "Initial domain size: 2^{}, initial rate 2^-{}, queries: {}, pow_bits: {:.1}",
+            "Initial domain size: 2^{}, initial rate 2^-{}, queries: {}, pow_bits: {:.precision$}",
             self.starting_domain_log_size, self.log_inv_rate, self.queries, self.pow_bits
         )?;
 
         if self.ldt_parameters.batch_size > 1 {
             writeln!(
                 f,
-                "Batch size: {}, batching_pow_bits: {:.1}",
+                "Batch size: {}, batching_pow_bits: {:.precision$}",
                 self.ldt_parameters.batch_size, self.batching_pow_bits
             )?;
         }
 
+        if self.circle {
+            writeln!(
+                f,
+                "Circle fold, circle_folding_pow_bits: {:.precision$}",
+                self.circle_folding_pow_bits
+            )?;
+        }
+
         writeln!(
             f,
-            "Initial folding factor: {}, initial_folding_pow_bits: {:.1}",
+            "Initial folding factor: {}, initial_folding_pow_bits: {:.precision$}",
             self.starting_folding_factor, self.starting_folding_pow_bits
         )?;
         for r in &self.round_parameters {
@@ -365,6 +1047,8 @@ impl FriConfig {
             self.queries, self.final_poly_log_degree,
         )?;
 
+        print_pow_warning(f, self.max_pow_bits, &self.pow_bits_exceeded())?;
+
         Ok(())
     }
 }
@@ -377,10 +1061,858 @@ impl Display for FriConfig {
 
 impl Display for RoundConfig {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let precision = float_precision(f);
         writeln!(
             f,
-            "Folding factor: {}, domain_size: 2^{}, folding_pow_bits: {:.1}",
-            self.folding_factor, self.evaluation_domain_log_size, self.folding_pow_bits,
+            "Folding factor: {}, domain_size: 2^{}, ood_samples: {}, folding_pow_bits: {:.precision$}",
+            self.folding_factor,
+            self.evaluation_domain_log_size,
+            self.ood_samples,
+            self.folding_pow_bits,
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{rate_sweep, FriParameters, FriProtocol};
+    use crate::protocol::{
+        proof_size::{InterleavingMode, MerkleQueries, ProofElement},
+        ElementStats,
+    };
+    use crate::{
+        errors::{ParameterError, SecurityAssumption},
+        field::{GOLDILOCKS_2, KOALABEAR_4, MERSENNE31, STARK_252},
+        hash::DigestConfig,
+        LowDegreeParameters,
+    };
+
+    #[test]
+    fn test_verifier_cost() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 10,
+            batch_size: 1,
+            constraint_degree: 0,
+            num_constraints: 1,
+        };
+        let fri_parameters =
+            FriParameters::fixed_folding(1, 2, 1, SecurityAssumption::CapacityBound, 100, 20, 256);
+        let fri_protocol = FriProtocol::new(ldt_parameters, fri_parameters).unwrap();
+
+        // Trees of depth 11 - 2 = 9 and 9 - 2 = 7 are both opened at every query
+        let queries = SecurityAssumption::CapacityBound.queries(80, 1).unwrap();
+        let verifier_cost = fri_protocol.protocol.verifier_cost();
+        assert_eq!(verifier_cost.hashes, queries * (9 + 7));
+        // The final polynomial has degree 2^(10 - 4), and is the only field work of the verifier
+        assert_eq!(verifier_cost.field_muls, 1 << 6);
+        assert_eq!(verifier_cost.final_eval_muls, 1 << 6);
+    }
+
+    #[test]
+    fn test_final_eval_cost() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 20,
+            batch_size: 1,
+            constraint_degree: 0,
+            num_constraints: 1,
+        };
+        let fri_protocol = |num_rounds| {
+            FriProtocol::new(
+                ldt_parameters,
+                FriParameters::fixed_folding(
+                    1,
+                    1,
+                    num_rounds,
+                    SecurityAssumption::CapacityBound,
+                    100,
+                    20,
+                    256,
+                ),
+            )
+            .unwrap()
+        };
+
+        // Folding once less doubles the degree of the final polynomial, and so the cost of evaluating it
+        let folded = fri_protocol(3);
+        let under_folded = fri_protocol(2);
+        assert_eq!(
+            under_folded.config.final_poly_log_degree,
+            folded.config.final_poly_log_degree + 1
+        );
+        let final_eval_muls =
+            |fri_protocol: &FriProtocol| fri_protocol.protocol.verifier_cost().final_eval_muls;
+        assert_eq!(final_eval_muls(&folded), 1 << 16);
+        assert_eq!(final_eval_muls(&under_folded), 2 * final_eval_muls(&folded));
+        assert!(under_folded.protocol.verifier_cost().field_muls >= final_eval_muls(&under_folded));
+    }
+
+    #[test]
+    fn test_rate_sweep() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 20,
+            batch_size: 1,
+            constraint_degree: 0,
+            num_constraints: 1,
+        };
+        let fri_parameters =
+            FriParameters::fixed_folding(1, 4, 3, SecurityAssumption::CapacityBound, 100, 20, 256);
+
+        let results = rate_sweep(ldt_parameters, fri_parameters.clone(), 1..6);
+        assert_eq!(results.len(), 5);
+        assert!(results.windows(2).all(|w| w[0].1 <= w[1].1));
+
+        let queries: Vec<_> = (1..6)
+            .map(|log_inv_rate| {
+                let fri_parameters = FriParameters {
+                    starting_log_inv_rate: log_inv_rate,
+                    ..fri_parameters.clone()
+                };
+                FriProtocol::new(ldt_parameters, fri_parameters)
+                    .unwrap()
+                    .config
+                    .queries
+            })
+            .collect();
+        assert!(queries.windows(2).all(|w| w[0] > w[1]));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_to_json_keys() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 20,
+            batch_size: 1,
+            constraint_degree: 0,
+            num_constraints: 1,
+        };
+        let fri_parameters =
+            FriParameters::fixed_folding(1, 4, 3, SecurityAssumption::CapacityBound, 100, 20, 256);
+        let fri_protocol = FriProtocol::new(ldt_parameters, fri_parameters).unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&fri_protocol.to_json()).unwrap();
+        assert!(json["config"]["ldt_parameters"].is_object());
+        assert!(json["config"]["round_parameters"].is_array());
+        assert!(json["protocol"]["rounds"].is_array());
+        assert!(json["protocol"]["proof_size_bits"].is_u64());
+    }
+
+    #[test]
+    fn test_correlated_queries() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 20,
+            batch_size: 1,
+            constraint_degree: 0,
+            num_constraints: 1,
+        };
+        let fri_parameters =
+            FriParameters::fixed_folding(1, 4, 3, SecurityAssumption::CapacityBound, 100, 20, 256);
+        let independent = FriProtocol::new(ldt_parameters, fri_parameters.clone()).unwrap();
+        let correlated = FriProtocol::new(
+            ldt_parameters,
+            FriParameters {
+                correlated_queries: true,
+                ..fri_parameters
+            },
+        )
+        .unwrap();
+
+        assert_eq!(independent.config.queries, correlated.config.queries);
+        assert!(correlated.protocol.proof_size_bits() < independent.protocol.proof_size_bits());
+    }
+
+    #[test]
+    fn test_no_grinding() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 20,
+            batch_size: 1,
+            constraint_degree: 0,
+            num_constraints: 1,
+        };
+        let fri_parameters =
+            FriParameters::fixed_folding(1, 4, 3, SecurityAssumption::CapacityBound, 100, 20, 256);
+        let grinding = FriProtocol::new(ldt_parameters, fri_parameters.clone()).unwrap();
+        let no_grinding = FriProtocol::new(
+            ldt_parameters,
+            FriParameters {
+                no_grinding: true,
+                ..fri_parameters
+            },
+        )
+        .unwrap();
+
+        assert!(no_grinding.config.queries > grinding.config.queries);
+        assert_eq!(no_grinding.config.pow_bits, 0.);
+        assert_eq!(no_grinding.config.starting_folding_pow_bits, 0.);
+        assert!(no_grinding.config.pow_bits_exceeded().is_empty());
+    }
+
+    #[test]
+    fn test_recursion_cost() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 20,
+            batch_size: 1,
+            constraint_degree: 0,
+            num_constraints: 1,
+        };
+        let fri_parameters =
+            FriParameters::fixed_folding(1, 4, 3, SecurityAssumption::CapacityBound, 100, 20, 256);
+        let fri_protocol = FriProtocol::new(ldt_parameters, fri_parameters.clone()).unwrap();
+
+        let sha3_cost = fri_protocol.protocol.recursion_cost(DigestConfig::Sha3_256);
+        let poseidon2_cost = fri_protocol
+            .protocol
+            .recursion_cost(DigestConfig::Poseidon2Goldilocks);
+        assert!(poseidon2_cost > 0);
+        assert!(100 * poseidon2_cost < sha3_cost);
+
+        // Wider leaves take more compressions to hash, for the same paths
+        let batched = FriProtocol::new(
+            LowDegreeParameters {
+                batch_size: 8,
+                ..ldt_parameters
+            },
+            fri_parameters,
+        )
+        .unwrap();
+        assert_eq!(batched.config.queries, fri_protocol.config.queries);
+        assert!(
+            batched
+                .protocol
+                .recursion_cost(DigestConfig::Poseidon2Goldilocks)
+                > poseidon2_cost
+        );
+    }
+
+    /// The batching round only consists of the verifier's combination challenge (see the NOTE in `FriProtocol::new`),
+    /// the batched functions are instead paid for by the larger leaves of the starting tree.
+    #[test]
+    fn test_batching_round_is_free() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 20,
+            batch_size: 4,
+            constraint_degree: 0,
+            num_constraints: 1,
+        };
+        let fri_parameters =
+            FriParameters::fixed_folding(1, 4, 3, SecurityAssumption::CapacityBound, 100, 20, 256);
+        let fri_protocol = FriProtocol::new(ldt_parameters, fri_parameters.clone()).unwrap();
+
+        let proof = fri_protocol.protocol.to_proof();
+        let batching_round = &proof.rounds[0];
+        assert_eq!(batching_round.name, "batching_round");
+        assert!(batching_round.elements.is_empty());
+
+        let unbatched_protocol = FriProtocol::new(
+            LowDegreeParameters {
+                batch_size: 1,
+                ..ldt_parameters
+            },
+            fri_parameters,
+        )
+        .unwrap();
+        assert!(
+            fri_protocol.protocol.proof_size_bits() > unbatched_protocol.protocol.proof_size_bits()
+        );
+    }
+
+    #[test]
+    fn test_non_power_of_two_batch_size() {
+        let fri_parameters =
+            FriParameters::fixed_folding(1, 4, 3, SecurityAssumption::CapacityBound, 100, 20, 256);
+        let fri_protocol = |batch_size| {
+            let ldt_parameters = LowDegreeParameters {
+                field: GOLDILOCKS_2,
+                log_degree: 20,
+                batch_size,
+                constraint_degree: 0,
+                num_constraints: 1,
+            };
+            FriProtocol::new(ldt_parameters, fri_parameters.clone()).unwrap()
+        };
+        let batching_error = |fri_protocol: &FriProtocol| {
+            fri_protocol
+                .protocol
+                .rbr_breakdown()
+                .into_iter()
+                .flat_map(|round| round.terms)
+                .find(|(name, _)| name == "batching_error")
+                .map(|(_, error)| error)
+                .unwrap()
+        };
+
+        let batch_3 = fri_protocol(3);
+
+        // Each leaf of the starting tree holds the 16 evaluations of each of the 3 functions
+        let starting_leaf = batch_3.config.committed_trees[0].leaf;
+        assert_eq!(starting_leaf.num_elements, 16 * 3);
+        assert!(!starting_leaf.is_extension);
+
+        // The combination error scales with the number of functions minus one
+        let error = batching_error(&batch_3);
+        assert!(error.is_finite());
+        assert_eq!(
+            error,
+            SecurityAssumption::CapacityBound.prox_gaps_error(20, 1, 128, 3)
+        );
+        assert_eq!(batching_error(&fri_protocol(2)) - error, 1.);
+        assert_eq!(error - batching_error(&fri_protocol(5)), 1.);
+
+        let proof_size = |batch_size| fri_protocol(batch_size).protocol.proof_size_bits();
+        assert!(proof_size(2) < proof_size(3) && proof_size(3) < proof_size(4));
+    }
+
+    #[test]
+    fn test_deep() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 20,
+            batch_size: 1,
+            constraint_degree: 0,
+            num_constraints: 1,
+        };
+        let fri_protocol = |security_assumption, deep| {
+            FriProtocol::new(
+                ldt_parameters,
+                FriParameters {
+                    deep,
+                    ..FriParameters::fixed_folding(1, 4, 3, security_assumption, 100, 20, 256)
+                },
+            )
+            .unwrap()
+        };
+        // Plain FRI is only proven sound up to the Johnson bound, while the OOD samples of DEEP-FRI
+        // let it be configured at the capacity bound
+        let plain = fri_protocol(SecurityAssumption::JohnsonBound, false);
+        let deep = fri_protocol(SecurityAssumption::CapacityBound, true);
+
+        assert!(deep
+            .config
+            .round_parameters
+            .iter()
+            .all(|r| r.ood_samples > 0));
+        assert!(deep.config.queries < plain.config.queries);
+        assert!(deep.protocol.rbr_error() >= plain.protocol.rbr_error());
+
+        // Trading the OOD samples for the saved queries shrinks the proof
+        let ood_bits = |fri_protocol: &FriProtocol| -> usize {
+            fri_protocol
+                .config
+                .round_parameters
+                .iter()
+                .map(|r| r.ood_samples * GOLDILOCKS_2.extension_bit_size())
+                .sum()
+        };
+        assert!(ood_bits(&deep) > 0);
+        assert!(deep.protocol.proof_size_bits() < plain.protocol.proof_size_bits());
+
+        // Up to the Johnson bound the OOD samples are pure overhead
+        let plain = fri_protocol(SecurityAssumption::JohnsonBound, false);
+        let deep = fri_protocol(SecurityAssumption::JohnsonBound, true);
+        assert_eq!(deep.config.queries, plain.config.queries);
+        assert_eq!(
+            deep.protocol.proof_size_bits(),
+            plain.protocol.proof_size_bits() + ood_bits(&deep)
+        );
+    }
+
+    #[test]
+    fn test_element_stats() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 10,
+            batch_size: 1,
+            constraint_degree: 0,
+            num_constraints: 1,
+        };
+        let fri_parameters =
+            FriParameters::fixed_folding(1, 2, 1, SecurityAssumption::CapacityBound, 100, 20, 256);
+        let fri_protocol = FriProtocol::new(ldt_parameters, fri_parameters).unwrap();
+
+        // Two trees with leaves of 4 elements (the first over the base field) opened at every query,
+        // one new root and a final polynomial of degree 2^(10 - 4)
+        let queries = fri_protocol.config.queries;
+        assert_eq!(
+            fri_protocol.protocol.element_stats(),
+            ElementStats {
+                base_field_elements: 4 * queries,
+                extension_field_elements: 4 * queries + (1 << 6),
+                merkle_roots: 1,
+                merkle_openings: 2 * queries,
+            }
+        );
+    }
+
+    #[test]
+    fn test_committed_field_report() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 20,
+            batch_size: 1,
+            constraint_degree: 0,
+            num_constraints: 1,
+        };
+        let fri_parameters =
+            FriParameters::fixed_folding(1, 4, 3, SecurityAssumption::CapacityBound, 100, 20, 256);
+        let fri_protocol = FriProtocol::new(ldt_parameters, fri_parameters).unwrap();
+        let report = fri_protocol.protocol.committed_field_report();
+        assert_eq!(report.len(), fri_protocol.config.round_parameters.len() + 1);
+
+        // Only the starting oracle is over the base field
+        assert!(!report[0].is_extension);
+        assert_eq!(report[0].element_size_bits, 64);
+        assert!(report[1..]
+            .iter()
+            .all(|oracle| oracle.is_extension && oracle.element_size_bits == 128));
+    }
+
+    #[test]
+    fn test_merkle_summary() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 10,
+            batch_size: 1,
+            constraint_degree: 0,
+            num_constraints: 1,
+        };
+        let fri_parameters =
+            FriParameters::fixed_folding(1, 2, 1, SecurityAssumption::CapacityBound, 100, 20, 256);
+        let fri_protocol = FriProtocol::new(ldt_parameters, fri_parameters).unwrap();
+
+        // The folded tree is committed and later opened, the starting tree is only opened.
+        assert_eq!(
+            fri_protocol.protocol.merkle_summary(),
+            vec![(7, 4, 256), (9, 4, 256)]
+        );
+    }
+
+    #[test]
+    fn test_zero_rounds() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 16,
+            batch_size: 1,
+            constraint_degree: 0,
+            num_constraints: 1,
+        };
+        let fri_parameters = |starting_folding_factor| FriParameters {
+            starting_folding_factor,
+            ..FriParameters::fixed_folding(1, 2, 0, SecurityAssumption::CapacityBound, 100, 20, 256)
+        };
+        let fri_protocol = FriProtocol::new(ldt_parameters, fri_parameters(0)).unwrap();
+        let config = &fri_protocol.config;
+
+        // Nothing is folded, the whole polynomial is sent and checked against the starting commitment
+        assert!(config.round_parameters.is_empty());
+        assert_eq!(config.starting_arity, 1);
+        assert_eq!(config.final_poly_log_degree, 16);
+
+        // One verifier message per fold (none here), then the queries
+        let num_folds = config.round_parameters.len() + usize::from(config.starting_arity > 1);
+        assert_eq!(fri_protocol.protocol.num_classical_rounds(), num_folds + 1);
+        assert_eq!(config.committed_trees.len(), 1);
+
+        let final_poly_size_bits = (1 << 16) * GOLDILOCKS_2.extension_bit_size();
+        assert_eq!(config.final_poly_size_bits(), final_poly_size_bits);
+        assert!(10 * final_poly_size_bits > 9 * fri_protocol.protocol.proof_size_bits());
+
+        // Otherwise, only the starting fold is done
+        let fri_protocol = FriProtocol::new(ldt_parameters, fri_parameters(2)).unwrap();
+        assert_eq!(fri_protocol.config.starting_arity, 4);
+        assert_eq!(fri_protocol.config.final_poly_log_degree, 14);
+        assert_eq!(fri_protocol.protocol.num_classical_rounds(), 2);
+    }
+
+    #[test]
+    fn test_query_error_per_tree() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 20,
+            batch_size: 1,
+            constraint_degree: 0,
+            num_constraints: 1,
+        };
+        let fri_parameters = |folding_arities| FriParameters {
+            folding_arities,
+            ..FriParameters::fixed_folding(1, 2, 3, SecurityAssumption::CapacityBound, 100, 20, 256)
+        };
+
+        for folding_arities in [vec![], vec![3, 4, 3, 4]] {
+            let fri_protocol =
+                FriProtocol::new(ldt_parameters, fri_parameters(folding_arities)).unwrap();
+            let config = &fri_protocol.config;
+            let protocol_security_level = (config.security_level - config.max_pow_bits) as f64;
+
+            let query_errors = config.query_error_per_tree().unwrap();
+            assert_eq!(query_errors.len(), config.committed_trees.len());
+            assert!(query_errors
+                .iter()
+                .all(|&query_error| query_error >= protocol_security_level));
+            // The starting tree is exactly at the rate the queries were configured for
+            assert!(query_errors
+                .iter()
+                .all(|&query_error| query_error >= query_errors[0]));
+        }
+    }
+
+    #[test]
+    fn test_commitment_sizes() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 10,
+            batch_size: 1,
+            constraint_degree: 0,
+            num_constraints: 1,
+        };
+        let fri_parameters =
+            FriParameters::fixed_folding(1, 2, 1, SecurityAssumption::CapacityBound, 100, 20, 256);
+        let fri_protocol = FriProtocol::new(ldt_parameters, fri_parameters).unwrap();
+        let config = &fri_protocol.config;
+
+        let commitment_sizes = config.commitment_sizes();
+        assert_eq!(commitment_sizes.len(), 2);
+        assert_eq!(
+            commitment_sizes.iter().sum::<usize>(),
+            fri_protocol.protocol.merkle_queries_size_bits()
+        );
+
+        // The starting leaves hold 4 base field elements, the later ones 4 extension field elements
+        let [starting_tree, folded_tree] = config.committed_trees[..] else {
+            panic!("expected two commitments");
+        };
+        assert_eq!(starting_tree.leaf.size_bits(), 4 * 64);
+        assert_eq!(folded_tree.leaf.size_bits(), 4 * 128);
+
+        // Were the starting tree over the extension, each opened leaf would cost 4 more base field elements
+        let mut extension_tree = starting_tree;
+        extension_tree.leaf.is_extension = true;
+        let extension_size = MerkleQueries {
+            merkle_tree: extension_tree,
+            num_openings: config.queries,
+            shared_leaves: 1,
+        }
+        .estimate_size_bits();
+        assert_eq!(
+            extension_size - commitment_sizes[0],
+            config.queries * 4 * 64
+        );
+    }
+
+    #[test]
+    fn test_verifier_randomness_bits() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 10,
+            batch_size: 1,
+            constraint_degree: 0,
+            num_constraints: 1,
+        };
+        let fri_parameters =
+            FriParameters::fixed_folding(1, 2, 1, SecurityAssumption::CapacityBound, 100, 20, 256);
+        let fri_protocol = FriProtocol::new(ldt_parameters, fri_parameters).unwrap();
+        // Two folding challenges (starting and one round) of one Goldilocks^2 element each,
+        // and 87 query indices into the 2^9 leaves of the starting tree (2^11 domain, folded by 4)
+        assert_eq!(fri_protocol.config.queries, 87);
+        assert_eq!(
+            fri_protocol.protocol.verifier_randomness_bits(),
+            2 * 128 + 87 * 9
+        );
+    }
+
+    #[test]
+    fn test_prime_field_element_size() {
+        let fri_parameters =
+            FriParameters::fixed_folding(1, 4, 3, SecurityAssumption::CapacityBound, 100, 20, 256);
+        let element_size_bits = |field| {
+            let ldt_parameters = LowDegreeParameters {
+                field,
+                log_degree: 20,
+                batch_size: 1,
+                constraint_degree: 0,
+                num_constraints: 1,
+            };
+            let fri_protocol = FriProtocol::new(ldt_parameters, fri_parameters.clone()).unwrap();
+            fri_protocol
+                .protocol
+                .committed_field_report()
+                .iter()
+                .map(|oracle| oracle.element_size_bits)
+                .collect::<Vec<_>>()
+        };
+
+        // Over Stark-252 the base field is the extension, so every oracle has 252-bit elements
+        let stark = element_size_bits(STARK_252);
+        let koalabear = element_size_bits(KOALABEAR_4);
+        assert!(stark.iter().all(|&size| size == 252));
+        assert_eq!(stark.len(), koalabear.len());
+        assert!(stark.iter().zip(&koalabear).all(|(s, k)| s > k));
+    }
+
+    #[test]
+    fn test_ideal_assumption() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 20,
+            batch_size: 1,
+            constraint_degree: 0,
+            num_constraints: 1,
+        };
+        for log_inv_rate in 1..6 {
+            let fri_parameters = FriParameters::fixed_folding(
+                log_inv_rate,
+                4,
+                3,
+                SecurityAssumption::Ideal,
+                100,
+                20,
+                256,
+            );
+            let fri_protocol = FriProtocol::new(ldt_parameters, fri_parameters).unwrap();
+            assert_eq!(fri_protocol.config.queries, 1);
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 20,
+            batch_size: 1,
+            constraint_degree: 2,
+            num_constraints: 1,
+        };
+        let fri_parameters =
+            FriParameters::fixed_folding(1, 4, 6, SecurityAssumption::CapacityBound, 100, 20, 256);
+        assert_eq!(
+            fri_parameters.validate(&ldt_parameters),
+            Err(vec![
+                ParameterError::UnsupportedConstraintDegree {
+                    constraint_degree: 2
+                },
+                ParameterError::OverFolded {
+                    total_reduction: 28,
+                    log_degree: 20
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_folding_arities() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 20,
+            batch_size: 1,
+            constraint_degree: 0,
+            num_constraints: 1,
+        };
+        let fri_parameters =
+            FriParameters::fixed_folding(1, 2, 2, SecurityAssumption::CapacityBound, 100, 20, 256);
+
+        // Powers of two arities are the same as the corresponding folding factors
+        let power_of_two_arities = FriParameters {
+            folding_arities: vec![4, 4, 4],
+            ..fri_parameters.clone()
+        };
+        assert_eq!(
+            FriProtocol::new(ldt_parameters, power_of_two_arities)
+                .unwrap()
+                .protocol
+                .proof_size_bits(),
+            FriProtocol::new(ldt_parameters, fri_parameters.clone())
+                .unwrap()
+                .protocol
+                .proof_size_bits()
+        );
+
+        // Folding by 3 three times reduces the degree by a factor of 27
+        let ternary = FriParameters {
+            folding_arities: vec![3, 3, 3],
+            ..fri_parameters
+        };
+        let fri_protocol = FriProtocol::new(ldt_parameters, ternary).unwrap();
+        let log_degree_reduction = fri_protocol.config.log_degree_reduction();
+        assert!((log_degree_reduction - 27_f64.log2()).abs() < 1e-9);
+        assert!((log_degree_reduction - 4.75).abs() < 0.01);
+        // The final degree 2^20 / 27 is rounded up to 2^16
+        assert_eq!(fri_protocol.config.final_poly_log_degree, 16);
+        assert!(fri_protocol
+            .config
+            .round_parameters
+            .iter()
+            .all(|r| r.arity == 3 && r.folding_factor == 2));
+    }
+
+    #[test]
+    fn test_interleaving_mode() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 20,
+            batch_size: 4,
+            constraint_degree: 0,
+            num_constraints: 1,
+        };
+        let fri_parameters =
+            FriParameters::fixed_folding(1, 4, 3, SecurityAssumption::CapacityBound, 100, 20, 256);
+        let fri_protocol = |interleaving| {
+            FriProtocol::new(
+                ldt_parameters,
+                FriParameters {
+                    interleaving,
+                    ..fri_parameters.clone()
+                },
+            )
+            .unwrap()
+        };
+        let interleaved = fri_protocol(InterleavingMode::Interleaved);
+        let separate = fri_protocol(InterleavingMode::Separate);
+
+        // One tree with leaves of 4 * 16 elements, versus four trees with leaves of 16 elements
+        assert_eq!(interleaved.config.committed_trees[0].leaf.num_elements, 64);
+        assert_eq!(separate.config.committed_trees[0].leaf.num_elements, 16);
+        let merkle_queries = |fri_protocol: &FriProtocol| {
+            fri_protocol
+                .protocol
+                .prover_messages()
+                .filter(|message| matches!(message.element(), ProofElement::MerkleQueries(_)))
+                .count()
+        };
+        assert_eq!(merkle_queries(&separate), merkle_queries(&interleaved) + 3);
+
+        // The soundness is unaffected, but each separate tree needs its own authentication paths
+        assert_eq!(
+            interleaved.protocol.rbr_errors(),
+            separate.protocol.rbr_errors()
+        );
+        assert!(
+            interleaved.protocol.merkle_queries_size_bits()
+                < separate.protocol.merkle_queries_size_bits()
+        );
+        let interleaved_sizes = interleaved.config.commitment_sizes();
+        let separate_sizes = separate.config.commitment_sizes();
+        assert!(interleaved_sizes[0] < separate_sizes[0]);
+        assert_eq!(interleaved_sizes[1..], separate_sizes[1..]);
+        assert_eq!(
+            separate_sizes.iter().sum::<usize>(),
+            separate.protocol.merkle_queries_size_bits()
+        );
+    }
+
+    #[test]
+    fn test_to_parameters() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 20,
+            batch_size: 2,
+            constraint_degree: 0,
+            num_constraints: 1,
+        };
+        let round_trip = |fri_parameters: FriParameters| {
+            let config = FriProtocol::new(ldt_parameters, fri_parameters)
+                .unwrap()
+                .config;
+            let rebuilt = FriProtocol::new(config.ldt_parameters, config.to_parameters())
+                .unwrap()
+                .config;
+            assert_eq!(rebuilt, config);
+        };
+
+        let fri_parameters =
+            FriParameters::fixed_folding(1, 4, 3, SecurityAssumption::CapacityBound, 100, 20, 256);
+        round_trip(fri_parameters.clone());
+        round_trip(FriParameters {
+            security_assumptions: vec![
+                SecurityAssumption::JohnsonBound,
+                SecurityAssumption::CapacityBound,
+                SecurityAssumption::UniqueDecoding,
+            ],
+            eta_factor: Some(4.),
+            folding_pow_bits: Some(10),
+            digest: Some(DigestConfig::Sha3_256),
+            no_grinding: true,
+            zero_knowledge: true,
+            interleaving: InterleavingMode::Separate,
+            correlated_queries: true,
+            deep: true,
+            ..fri_parameters.clone()
+        });
+
+        // Explicit arities, a single fold without folding rounds, and no folds at all
+        for folding_arities in [vec![3, 3, 3], vec![16], vec![]] {
+            round_trip(FriParameters {
+                folding_factors: vec![],
+                folding_arities,
+                ..fri_parameters.clone()
+            });
+        }
+    }
+
+    #[test]
+    fn test_circle_fri() {
+        let ldt_parameters = LowDegreeParameters {
+            field: MERSENNE31,
+            log_degree: 20,
+            batch_size: 1,
+            constraint_degree: 0,
+            num_constraints: 1,
+        };
+        let fri_parameters =
+            FriParameters::fixed_folding(1, 4, 3, SecurityAssumption::CapacityBound, 100, 20, 256);
+        let circle_parameters = FriParameters {
+            circle: true,
+            ..fri_parameters.clone()
+        };
+        let line = FriProtocol::new(ldt_parameters, fri_parameters).unwrap();
+        let circle = FriProtocol::new(ldt_parameters, circle_parameters.clone()).unwrap();
+
+        // The fold from the circle to the line is one extra halving, opened with the starting fold
+        assert_eq!(circle.config.log_degree_reduction(), 17.);
+        assert_eq!(circle.config.final_poly_log_degree, 3);
+        assert_eq!(line.config.final_poly_log_degree, 4);
+        let starting_tree = circle.config.committed_trees[0];
+        assert_eq!(starting_tree.leaf.num_elements, 32);
+        assert_eq!(starting_tree.tree_depth, 21 - 5);
+        assert!(circle
+            .protocol
+            .error_term_names()
+            .contains("circle_folding_error"));
+        assert!(!line
+            .protocol
+            .error_term_names()
+            .contains("circle_folding_error"));
+
+        // Every tree stays at the starting rate, as the circle fold shrinks the domain with the degree
+        let query_errors = circle.config.query_error_per_tree().unwrap();
+        assert_eq!(query_errors, line.config.query_error_per_tree().unwrap());
+        assert_eq!(query_errors.len(), circle.config.committed_trees.len());
+
+        assert_eq!(
+            FriProtocol::new(ldt_parameters, circle.config.to_parameters())
+                .unwrap()
+                .config,
+            circle.config
+        );
+
+        // The circle group is only smooth over Mersenne31
+        assert_eq!(
+            FriProtocol::new(
+                LowDegreeParameters {
+                    field: GOLDILOCKS_2,
+                    ..ldt_parameters
+                },
+                circle_parameters
+            )
+            .unwrap_err(),
+            ParameterError::UnsupportedField {
+                field_name: "Goldilocks"
+            }
+        );
+    }
+}
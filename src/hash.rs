@@ -0,0 +1,77 @@
+//! The hashes used for the Merkle digests, with their collision resistance and cost models for recursion.
+use std::fmt::Display;
+
+/// A concrete hash (and output size) used for the Merkle digests, together with its collision resistance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum DigestConfig {
+    /// Blake3 with 256-bit outputs.
+    Blake3_256,
+    /// SHA3 with 256-bit outputs, a bit-oriented hash (built on the Keccak-f permutation) which is expensive to arithmetize.
+    Sha3_256,
+    /// Poseidon2 over Goldilocks, outputting 4 field elements.
+    /// Its collision resistance is taken to be 100 bits, as in instantiations whose security is set by algebraic attacks
+    /// rather than by the (generic) birthday bound.
+    Poseidon2Goldilocks,
+    /// Rescue-Prime over Goldilocks, outputting 4 field elements, with the same collision resistance as Poseidon2.
+    RescuePrimeGoldilocks,
+    /// A hash with the given output size, collision resistance (in bits) and cost per compression (in constraints).
+    Custom {
+        output_bits: usize,
+        collision_bits: usize,
+        cost_per_compression: usize,
+    },
+}
+
+impl DigestConfig {
+    /// The size of the digest output by the hash.
+    pub fn output_bits(&self) -> usize {
+        match self {
+            DigestConfig::Blake3_256 | DigestConfig::Sha3_256 => 256,
+            DigestConfig::Poseidon2Goldilocks | DigestConfig::RescuePrimeGoldilocks => 4 * 64,
+            DigestConfig::Custom { output_bits, .. } => *output_bits,
+        }
+    }
+
+    /// The bits of collision resistance of the hash, which for a generic hash is half of its output.
+    pub fn collision_bits(&self) -> usize {
+        match self {
+            DigestConfig::Blake3_256 | DigestConfig::Sha3_256 => 128,
+            DigestConfig::Poseidon2Goldilocks | DigestConfig::RescuePrimeGoldilocks => 100,
+            DigestConfig::Custom { collision_bits, .. } => *collision_bits,
+        }
+    }
+
+    /// The (approximate) number of R1CS constraints needed to verify one compression in a circuit.
+    pub fn cost_per_compression(&self) -> usize {
+        match self {
+            DigestConfig::Blake3_256 => 15_000,
+            DigestConfig::Sha3_256 => 150_000,
+            DigestConfig::Poseidon2Goldilocks => 240,
+            DigestConfig::RescuePrimeGoldilocks => 300,
+            DigestConfig::Custom {
+                cost_per_compression,
+                ..
+            } => *cost_per_compression,
+        }
+    }
+}
+
+impl Display for DigestConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DigestConfig::Blake3_256 => write!(f, "Blake3-256"),
+            DigestConfig::Sha3_256 => write!(f, "SHA3-256"),
+            DigestConfig::Poseidon2Goldilocks => write!(f, "Poseidon2-Goldilocks"),
+            DigestConfig::RescuePrimeGoldilocks => write!(f, "Rescue-Prime-Goldilocks"),
+            DigestConfig::Custom {
+                output_bits,
+                collision_bits,
+                ..
+            } => write!(
+                f,
+                "{output_bits}-bit digest ({collision_bits} bits of collision resistance)"
+            ),
+        }
+    }
+}
@@ -1,23 +1,34 @@
 use std::fmt::Display;
 
+use errors::ParameterError;
 use field::Field;
+use protocol::{Protocol, Report};
 
 pub mod basefold;
+pub mod compare;
 pub mod errors;
 pub mod field;
 pub mod fri;
+pub mod hash;
+pub mod ligero;
+pub mod presets;
 pub mod protocol;
 pub mod stir;
 pub(crate) mod utils;
 pub mod whir;
 
+pub use compare::{assumption_comparison, compare_protocols};
+pub use protocol::proof_size;
+pub use utils::{display_size_with_unit, log_inv_from_rate, rate_from_log_inv, UnitKind};
+
 /// Selects a default maximum number of PoW such that any values greater than it results in an error.
 pub fn default_max_pow(num_variables: usize, log_inv_rate: usize) -> usize {
     num_variables + log_inv_rate - 3
 }
 
 /// The parameters for a (batched) low-degree test.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct LowDegreeParameters {
     /// The field the low degree test is over
     pub field: Field,
@@ -26,7 +37,53 @@ pub struct LowDegreeParameters {
     /// How many functions are tested (NOTE: not in log form)
     pub batch_size: usize,
     /// The degree of constraints being proven on the committed words (0 for just proximity testing)
+    /// With several constraints, this is the maximum of their degrees, which determines the size of the sumcheck messages.
     pub constraint_degree: usize,
+    /// How many constraints are proven, which determines the error of combining them (see `with_constraint_degrees`)
+    /// This must be non zero whenever `constraint_degree` is (see `validate`), and is 1 when built with `new`.
+    pub num_constraints: usize,
+}
+
+impl LowDegreeParameters {
+    /// Parameters for testing `batch_size` functions of degree 2^log_degree,
+    /// proving a single constraint of degree `constraint_degree` (0 for just proximity testing).
+    /// Use `with_constraint_degrees` for several constraints.
+    pub const fn new(
+        field: Field,
+        log_degree: usize,
+        batch_size: usize,
+        constraint_degree: usize,
+    ) -> Self {
+        LowDegreeParameters {
+            field,
+            log_degree,
+            batch_size,
+            constraint_degree,
+            num_constraints: 1,
+        }
+    }
+
+    /// Checks the parameters shared by every protocol, which is done by each protocol's validation.
+    pub fn validate(&self) -> Result<(), ParameterError> {
+        // A constraint degree without constraints would silently send no claims
+        if self.constraint_degree > 0 && self.num_constraints == 0 {
+            return Err(ParameterError::NoConstraints {
+                constraint_degree: self.constraint_degree,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Sets the constraints from the degree of each: the sumcheck messages are sized by the maximum degree,
+    /// while the soundness of combining the constraints depends on their number.
+    pub fn with_constraint_degrees(self, constraint_degrees: &[usize]) -> Self {
+        LowDegreeParameters {
+            constraint_degree: constraint_degrees.iter().copied().max().unwrap_or(0),
+            num_constraints: constraint_degrees.len(),
+            ..self
+        }
+    }
 }
 
 impl Display for LowDegreeParameters {
@@ -38,3 +95,20 @@ impl Display for LowDegreeParameters {
         )
     }
 }
+
+/// A configured low-degree test (or PCS), so that generic tooling can handle the protocols through `&dyn LowDegreeTest`.
+pub trait LowDegreeTest: Report {
+    /// The structure of the configured protocol.
+    fn protocol(&self) -> &Protocol;
+
+    /// The size of the proof in bits.
+    fn proof_size_bits(&self) -> usize {
+        self.protocol().proof_size_bits()
+    }
+
+    /// The bits of security against an adversary performing 2^log_ro_queries classical queries,
+    /// see `Protocol::compiled_classical_security`.
+    fn compiled_classical_security(&self, log_ro_queries: usize) -> f64 {
+        self.protocol().compiled_classical_security(log_ro_queries)
+    }
+}
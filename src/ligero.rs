@@ -0,0 +1,304 @@
+use std::fmt::Display;
+
+use crate::{
+    errors::{ParameterError, SecurityAssumption},
+    hash::DigestConfig,
+    protocol::{
+        builder::ProtocolBuilder,
+        proof_size::{merkle_digest_bits, FieldElements, MerkleQueries, MerkleTree, ProofElement},
+        Protocol, ProverMessage, RbRError, VerifierMessage,
+    },
+    utils::{float_precision, pow_util, print_pow_warning},
+    LowDegreeParameters,
+};
+
+/// Parameters parametrizing an instance of a Ligero-style (tensor code) commitment.
+/// The coefficients of the polynomial are arranged in a matrix of 2^(log_degree - log_num_cols) rows
+/// and 2^log_num_cols columns, and each row is encoded with a RS code.
+#[derive(Clone)]
+pub struct LigeroParameters {
+    /// The number of columns of the matrix (in log form).
+    pub log_num_cols: usize,
+
+    /// The rate of the RS code used to encode the rows.
+    pub log_inv_rate: usize,
+
+    /// The security assumption under which to configure Ligero.
+    pub security_assumption: SecurityAssumption,
+
+    /// The security level desired.
+    pub security_level: usize,
+
+    /// The number of pow bits to use to reduce query error.
+    pub pow_bits: usize,
+
+    /// The size of the digest for the Merkle tree. If `None`, digests are sized for the security level
+    /// (see `digest_size_bits_for_security_level`).
+    pub digest_size_bits: Option<usize>,
+
+    /// Optionally, the hash used for the Merkle digests, overriding `digest_size_bits` with its output size
+    /// and bounding the collision resistance of the digests.
+    pub digest: Option<DigestConfig>,
+}
+
+impl LigeroParameters {
+    /// Instantiate a Ligero configuration with a square matrix (rounding the number of columns down).
+    pub fn square(
+        log_degree: usize,
+        log_inv_rate: usize,
+        security_assumption: SecurityAssumption,
+        security_level: usize,
+        pow_bits: usize,
+        digest_size_bits: usize,
+    ) -> Self {
+        LigeroParameters {
+            log_num_cols: log_degree / 2,
+            log_inv_rate,
+            security_assumption,
+            security_level,
+            pow_bits,
+            digest_size_bits: Some(digest_size_bits),
+            digest: None,
+        }
+    }
+}
+
+/// The configuration and structure of the Ligero protocol.
+#[derive(Debug, Clone)]
+pub struct LigeroProtocol {
+    pub config: LigeroConfig,
+    pub protocol: Protocol,
+}
+
+impl LigeroProtocol {
+    /// Given a LDT parameter and some parameters for Ligero, populate the config.
+    pub fn new(
+        ldt_parameters: LowDegreeParameters,
+        ligero_parameters: LigeroParameters,
+    ) -> Result<Self, ParameterError> {
+        ldt_parameters.validate()?;
+
+        // Ligero is only modelled for proximity testing
+        if ldt_parameters.constraint_degree != 0 {
+            return Err(ParameterError::UnsupportedConstraintDegree {
+                constraint_degree: ldt_parameters.constraint_degree,
+            });
+        }
+
+        if ligero_parameters.log_num_cols > ldt_parameters.log_degree {
+            return Err(ParameterError::InvalidMatrixDimensions {
+                log_num_cols: ligero_parameters.log_num_cols,
+                log_degree: ldt_parameters.log_degree,
+            });
+        }
+
+        let log_num_cols = ligero_parameters.log_num_cols;
+        let log_num_rows = ldt_parameters.log_degree - log_num_cols;
+        let log_inv_rate = ligero_parameters.log_inv_rate;
+
+        // Compute the security level
+        let security_level = ligero_parameters.security_level;
+        let protocol_security_level = security_level.saturating_sub(ligero_parameters.pow_bits);
+        let security_assumption = ligero_parameters.security_assumption;
+
+        let (digest_size_bits, collision_bits) = merkle_digest_bits(
+            ligero_parameters.digest,
+            ligero_parameters.digest_size_bits,
+            security_level,
+        );
+
+        // The tree commits to the columns of the encoded matrix (for all the functions in the batch)
+        let merkle_tree = MerkleTree::with_digest_size(
+            log_num_cols + log_inv_rate,
+            ldt_parameters.field,
+            (1 << log_num_rows) * ldt_parameters.batch_size,
+            false,
+            digest_size_bits,
+        );
+
+        // The verifier combines the rows, and the prover sends the combined (unencoded) row
+        let combination_error = security_assumption.prox_gaps_error(
+            log_num_cols,
+            log_inv_rate,
+            ldt_parameters.field.extension_bit_size(),
+            (1 << log_num_rows) * ldt_parameters.batch_size,
+        );
+        let combination_pow_bits = pow_util(security_level, combination_error, None);
+
+        // Compute the number of queries required
+        let queries = security_assumption.queries(protocol_security_level, log_inv_rate)?;
+
+        // We need to compute the errors, to compute the according PoW
+        let query_error = security_assumption.queries_error(log_inv_rate, queries)?;
+
+        // Now compute the PoW
+        let pow_bits = pow_util(security_level, query_error, None);
+
+        let protocol = ProtocolBuilder::new("Ligero protocol", digest_size_bits)
+            .with_collision_bits(collision_bits)
+            .start_round("proximity_test")
+            .verifier_message(
+                VerifierMessage::new(
+                    vec![RbRError::new("combination_error", combination_error)],
+                    combination_pow_bits,
+                )
+                .with_challenge_bits(ldt_parameters.field.extension_bit_size()),
+            )
+            .prover_message(ProverMessage::new(ProofElement::FieldElements(
+                FieldElements::extension(ldt_parameters.field, 1 << log_num_cols),
+            )))
+            .end_round()
+            .start_round("column_queries")
+            .verifier_message(
+                VerifierMessage::new(vec![RbRError::new("query_error", query_error)], pow_bits)
+                    .with_challenge_bits(queries * merkle_tree.tree_depth),
+            )
+            .prover_message(ProverMessage::new(ProofElement::MerkleQueries(
+                MerkleQueries {
+                    merkle_tree,
+                    num_openings: queries,
+                    shared_leaves: 1,
+                },
+            )))
+            .end_round()
+            .build();
+
+        Ok(LigeroProtocol {
+            config: LigeroConfig {
+                ldt_parameters,
+                security_assumption,
+                security_level,
+                max_pow_bits: ligero_parameters.pow_bits,
+                log_num_rows,
+                log_num_cols,
+                log_inv_rate,
+                combination_pow_bits,
+                queries,
+                pow_bits,
+            },
+            protocol,
+        })
+    }
+}
+
+impl Display for LigeroProtocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.config.fmt(f)?;
+        self.protocol.fmt(f)
+    }
+}
+
+/// A fully expanded Ligero configuration.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LigeroConfig {
+    /// The configuration for the LDT desired.
+    pub ldt_parameters: LowDegreeParameters,
+
+    /// The security assumption under which Ligero was configured.
+    pub security_assumption: SecurityAssumption,
+
+    /// The desired security level.
+    pub security_level: usize,
+
+    /// The maximum number of pow bits allowed (over and we throw a warning, as probably then we are misconfigured.)
+    pub max_pow_bits: usize,
+
+    /// The number of rows of the matrix (in log form).
+    pub log_num_rows: usize,
+
+    /// The number of columns of the matrix (in log form).
+    pub log_num_cols: usize,
+
+    /// The rate of the RS code used to encode the rows.
+    pub log_inv_rate: usize,
+
+    /// The pow bits used when combining the rows.
+    pub combination_pow_bits: f64,
+
+    /// Number of column queries
+    pub queries: usize,
+
+    /// Number of bits of proof of work (for the queries).
+    pub pow_bits: f64,
+}
+
+impl LigeroConfig {
+    /// Returns the (labeled) PoW values exceeding `max_pow_bits`, which likely signal a misconfiguration.
+    pub fn pow_bits_exceeded(&self) -> Vec<(String, f64)> {
+        let mut pow_bits = vec![
+            ("combination".to_string(), self.combination_pow_bits),
+            ("queries".to_string(), self.pow_bits),
+        ];
+        pow_bits.retain(|&(_, bits)| bits > self.max_pow_bits as f64);
+        pow_bits
+    }
+
+    /// Prints a summary of the configuration for Ligero.
+    pub fn print_config_summary(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let precision = float_precision(f);
+        writeln!(f, "{}", self.ldt_parameters)?;
+        writeln!(
+            f,
+            "Security level: {} bits using {} security and {} bits of PoW",
+            self.security_level, self.security_assumption, self.max_pow_bits
+        )?;
+
+        writeln!(
+            f,
+            "Matrix: 2^{} x 2^{}, rate 2^-{}, combination_pow_bits: {:.precision$}",
+            self.log_num_rows, self.log_num_cols, self.log_inv_rate, self.combination_pow_bits
+        )?;
+
+        writeln!(
+            f,
+            "queries: {}, pow_bits: {:.precision$}",
+            self.queries, self.pow_bits
+        )?;
+
+        print_pow_warning(f, self.max_pow_bits, &self.pow_bits_exceeded())?;
+
+        Ok(())
+    }
+}
+
+impl Display for LigeroConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.print_config_summary(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LigeroParameters, LigeroProtocol};
+    use crate::{errors::SecurityAssumption, field::GOLDILOCKS_2, LowDegreeParameters};
+
+    #[test]
+    fn test_square_root_scaling() {
+        let proof_size_bits = |log_degree| {
+            let ldt_parameters = LowDegreeParameters {
+                field: GOLDILOCKS_2,
+                log_degree,
+                batch_size: 1,
+                constraint_degree: 0,
+                num_constraints: 1,
+            };
+            let ligero_parameters = LigeroParameters::square(
+                log_degree,
+                2,
+                SecurityAssumption::CapacityBound,
+                100,
+                20,
+                256,
+            );
+            LigeroProtocol::new(ldt_parameters, ligero_parameters)
+                .unwrap()
+                .protocol
+                .proof_size_bits() as f64
+        };
+
+        // Multiplying the degree by 16 should (roughly) multiply the proof size by 4
+        let ratio = proof_size_bits(24) / proof_size_bits(20);
+        assert!((3. ..5.).contains(&ratio), "ratio {ratio}");
+    }
+}
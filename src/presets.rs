@@ -0,0 +1,161 @@
+//! Named parameter presets, e.g. `whir-cb-100-g20` for WHIR under the capacity bound
+//! conjecture at 100 bits of security with 20 bits of grinding.
+use std::str::FromStr;
+
+use crate::{
+    basefold::BasefoldParameters, errors::SecurityAssumption, fri::FriParameters,
+    stir::StirParameters, whir::WhirParameters,
+};
+
+/// The starting rate used by all presets.
+const LOG_INV_RATE: usize = 1;
+
+/// The folding factor used by all presets.
+const FOLDING_FACTOR: usize = 4;
+
+/// The number of rounds used by all presets.
+const NUM_ROUNDS: usize = 4;
+
+/// The digest size used by all presets.
+const DIGEST_SIZE_BITS: usize = 256;
+
+/// A fully populated set of parameters for one of the protocols.
+#[derive(Clone)]
+pub enum Preset {
+    Stir(StirParameters),
+    Fri(FriParameters),
+    Basefold(BasefoldParameters),
+    Whir(WhirParameters),
+}
+
+impl Preset {
+    /// The security level of the preset.
+    pub fn security_level(&self) -> usize {
+        match self {
+            Preset::Stir(parameters) => parameters.security_level,
+            Preset::Fri(parameters) => parameters.security_level,
+            Preset::Basefold(parameters) => parameters.security_level,
+            Preset::Whir(parameters) => parameters.security_level,
+        }
+    }
+
+    /// The security assumption of the preset.
+    pub fn security_assumption(&self) -> SecurityAssumption {
+        match self {
+            Preset::Stir(parameters) => parameters.security_assumption,
+            Preset::Fri(parameters) => parameters.security_assumption,
+            Preset::Basefold(parameters) => parameters.security_assumption,
+            Preset::Whir(parameters) => parameters.security_assumption,
+        }
+    }
+}
+
+impl FromStr for Preset {
+    type Err = String;
+
+    /// Parses `<protocol>-<assumption>-<security_level>-g<pow_bits>`, where the protocol is one of
+    /// stir, fri, basefold, whir and the assumption one of ud, jb, cb.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "Invalid preset: {s} (expected <protocol>-<ud|jb|cb>-<security_level>-g<pow_bits>)"
+            )
+        };
+
+        let parts: Vec<_> = s.to_lowercase().split('-').map(str::to_owned).collect();
+        let [protocol, assumption, security_level, pow_bits] = parts.as_slice() else {
+            return Err(invalid());
+        };
+
+        let security_assumption = match assumption.as_str() {
+            "ud" => SecurityAssumption::UniqueDecoding,
+            "jb" => SecurityAssumption::JohnsonBound,
+            "cb" => SecurityAssumption::CapacityBound,
+            _ => return Err(invalid()),
+        };
+        let security_level: usize = security_level.parse().map_err(|_| invalid())?;
+        let pow_bits: usize = pow_bits
+            .strip_prefix('g')
+            .and_then(|pow_bits| pow_bits.parse().ok())
+            .ok_or_else(invalid)?;
+
+        match protocol.as_str() {
+            "stir" => Ok(Preset::Stir(StirParameters::fixed_domain_shift(
+                LOG_INV_RATE,
+                FOLDING_FACTOR,
+                NUM_ROUNDS,
+                security_assumption,
+                security_level,
+                pow_bits,
+                DIGEST_SIZE_BITS,
+            ))),
+            "fri" => Ok(Preset::Fri(FriParameters::fixed_folding(
+                LOG_INV_RATE,
+                FOLDING_FACTOR,
+                NUM_ROUNDS,
+                security_assumption,
+                security_level,
+                pow_bits,
+                DIGEST_SIZE_BITS,
+            ))),
+            "basefold" => Ok(Preset::Basefold(BasefoldParameters::fixed_folding(
+                LOG_INV_RATE,
+                FOLDING_FACTOR,
+                NUM_ROUNDS,
+                security_assumption,
+                security_level,
+                pow_bits,
+                DIGEST_SIZE_BITS,
+            ))),
+            "whir" => Ok(Preset::Whir(WhirParameters::fixed_domain_shift(
+                LOG_INV_RATE,
+                FOLDING_FACTOR,
+                NUM_ROUNDS,
+                security_assumption,
+                security_level,
+                pow_bits,
+                DIGEST_SIZE_BITS,
+            ))),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Preset;
+    use crate::errors::SecurityAssumption;
+
+    #[test]
+    fn test_parse_presets() {
+        let preset: Preset = "whir-cb-100-g20".parse().unwrap();
+        assert!(matches!(preset, Preset::Whir(_)));
+        assert_eq!(preset.security_level(), 100);
+        assert!(matches!(
+            preset.security_assumption(),
+            SecurityAssumption::CapacityBound
+        ));
+
+        let preset: Preset = "FRI-ud-128-g0".parse().unwrap();
+        assert!(matches!(preset, Preset::Fri(_)));
+        assert_eq!(preset.security_level(), 128);
+        assert!(matches!(
+            preset.security_assumption(),
+            SecurityAssumption::UniqueDecoding
+        ));
+
+        let preset: Preset = "stir-jb-80-g16".parse().unwrap();
+        assert!(matches!(
+            preset.security_assumption(),
+            SecurityAssumption::JohnsonBound
+        ));
+    }
+
+    #[test]
+    fn test_invalid_presets() {
+        assert!("whir-cb-100".parse::<Preset>().is_err());
+        assert!("whir-xx-100-g20".parse::<Preset>().is_err());
+        assert!("whir-cb-100-20".parse::<Preset>().is_err());
+        assert!("ligero-cb-100-g20".parse::<Preset>().is_err());
+    }
+}
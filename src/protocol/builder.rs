@@ -1,10 +1,17 @@
-use super::{proof_size::ProofElement, Message, Protocol, ProverMessage, Round, VerifierMessage};
+use super::{
+    proof_size::{Encoding, FieldElements, FinalPhase, ProofElement},
+    Message, Protocol, ProverMessage, RbRError, Round, VerifierMessage,
+};
+use crate::{errors::BuilderError, field::Field};
 
 pub struct ProtocolBuilder {
     protocol_name: String,
     digest_size_bits: usize,
+    collision_bits: Option<usize>,
     rounds: Vec<Round>,
     current_round: Option<RoundBuilder>,
+    strict: bool,
+    unended_rounds: Vec<String>,
 }
 
 impl ProtocolBuilder {
@@ -12,13 +19,40 @@ impl ProtocolBuilder {
         Self {
             protocol_name: name.to_owned(),
             digest_size_bits,
+            collision_bits: None,
             rounds: Vec::new(),
             current_round: None,
+            strict: false,
+            unended_rounds: Vec::new(),
         }
     }
 
+    /// Sets the bits of collision resistance of the digests, which default to half of the digest size.
+    pub fn with_collision_bits(mut self, collision_bits: usize) -> Self {
+        self.collision_bits = Some(collision_bits);
+        self
+    }
+
+    /// Enables strict mode, in which building checks that every round follows a verifier message
+    /// and that every started round is ended (see `try_build`).
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
     pub fn start_round(mut self, name: &str) -> Self {
-        self.current_round = Some(RoundBuilder::new(name));
+        if let Some(round_builder) = self.current_round.replace(RoundBuilder::new(name)) {
+            self.unended_rounds.push(round_builder.name);
+        }
+        self
+    }
+
+    /// Attaches a note to the current round, which is printed alongside its name.
+    pub fn round_note(mut self, note: &str) -> Self {
+        self.current_round
+            .as_mut()
+            .unwrap_or_else(|| panic!("No current round started"))
+            .note = Some(note.to_owned());
         self
     }
 
@@ -50,6 +84,47 @@ impl ProtocolBuilder {
         self
     }
 
+    /// Adds the messages proving the final polynomial, with `2^final_log_degree` coefficients, to the current round.
+    /// With a sumcheck, each of its rounds sends a polynomial of degree `sumcheck_degree`, and `sumcheck_round` maps
+    /// the log degree of the polynomial being summed to the error and pow bits of the following challenge.
+    pub fn final_phase(
+        mut self,
+        final_phase: FinalPhase,
+        field: Field,
+        encoding: Encoding,
+        final_log_degree: usize,
+        sumcheck_degree: usize,
+        sumcheck_round: impl Fn(usize) -> (f64, f64),
+    ) -> Self {
+        let num_elements = final_phase.num_elements(final_log_degree, sumcheck_degree);
+        match final_phase {
+            FinalPhase::DirectSend => self.prover_message(ProverMessage::final_polynomial(
+                FieldElements::extension(field, num_elements).with_encoding(encoding),
+            )),
+            FinalPhase::Sumcheck => {
+                // Each round binds one variable of the final polynomial, until only its evaluation at the sumcheck point remains
+                for log_degree in (1..=final_log_degree).rev() {
+                    let (sumcheck_error, pow_bits) = sumcheck_round(log_degree);
+                    self = self
+                        .prover_message(ProverMessage::new(ProofElement::FieldElements(
+                            FieldElements::extension(field, sumcheck_degree + 1)
+                                .with_encoding(encoding),
+                        )))
+                        .verifier_message(
+                            VerifierMessage::new(
+                                vec![RbRError::new("final_sumcheck_error", sumcheck_error)],
+                                pow_bits,
+                            )
+                            .with_challenge_bits(field.extension_bit_size()),
+                        );
+                }
+                self.prover_message(ProverMessage::new(ProofElement::FieldElements(
+                    FieldElements::extension(field, 1).with_encoding(encoding),
+                )))
+            }
+        }
+    }
+
     pub fn end_round(mut self) -> Self {
         if let Some(round_builder) = self.current_round.take() {
             self.rounds.push(round_builder.build());
@@ -57,11 +132,53 @@ impl ProtocolBuilder {
         self
     }
 
+    /// Builds the protocol.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the last round was not ended, or in strict mode if the structure is invalid (see `try_build`).
     pub fn build(self) -> Protocol {
+        if let Err(error) = self.check_strict() {
+            panic!("Invalid protocol structure: {error}");
+        }
+        self.finish()
+    }
+
+    /// Builds the protocol, validating its structure if in strict mode.
+    pub fn try_build(self) -> Result<Protocol, BuilderError> {
+        self.check_strict()?;
+        Ok(self.finish())
+    }
+
+    /// In strict mode, checks that every started round is ended and follows a verifier message.
+    fn check_strict(&self) -> Result<(), BuilderError> {
+        if !self.strict {
+            return Ok(());
+        }
+        if let Some(name) = self.unended_rounds.first() {
+            return Err(BuilderError::UnendedRound { name: name.clone() });
+        }
+        if let Some(round_builder) = &self.current_round {
+            return Err(BuilderError::UnendedRound {
+                name: round_builder.name.clone(),
+            });
+        }
+        for window in self.rounds.windows(2) {
+            if !window[0].messages.iter().any(Message::is_verifier_message) {
+                return Err(BuilderError::NoPriorVerifierMessage {
+                    name: window[1].name.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Protocol {
         assert!(self.current_round.is_none(), "Round was not finalized");
         Protocol {
             protocol_name: self.protocol_name,
             digest_size_bits: self.digest_size_bits,
+            collision_bits: self.collision_bits.unwrap_or(self.digest_size_bits / 2),
             rounds: self.rounds,
         }
     }
@@ -69,6 +186,7 @@ impl ProtocolBuilder {
 
 pub struct RoundBuilder {
     name: String,
+    note: Option<String>,
     rounds: Vec<Message>,
 }
 
@@ -76,15 +194,120 @@ impl RoundBuilder {
     pub fn new(name: &str) -> Self {
         Self {
             name: name.to_string(),
+            note: None,
             rounds: Vec::new(),
         }
     }
 
+    /// Attaches a note to the round (e.g. describing its purpose).
+    pub fn with_note(mut self, note: &str) -> Self {
+        self.note = Some(note.to_owned());
+        self
+    }
+
     pub fn build(self) -> Round {
         assert!(!self.rounds.is_empty());
         Round {
             name: self.name,
+            note: self.note,
             messages: self.rounds,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ProtocolBuilder;
+    use crate::{
+        errors::BuilderError,
+        field::GOLDILOCKS_2,
+        protocol::{
+            proof_size::{MerkleTree, ProofElement},
+            ProverMessage, RbRError, VerifierMessage,
+        },
+    };
+
+    fn commitment() -> ProverMessage {
+        ProverMessage::new(ProofElement::MerkleRoot(MerkleTree::new(
+            10,
+            GOLDILOCKS_2,
+            2,
+            false,
+        )))
+    }
+
+    fn challenge() -> VerifierMessage {
+        VerifierMessage::new(vec![RbRError::new("folding_error", 100.)], 0.)
+    }
+
+    #[test]
+    fn test_strict_well_formed() {
+        let protocol = ProtocolBuilder::new("Test protocol", 256)
+            .strict()
+            .start_round("first")
+            .prover_message(commitment())
+            .verifier_message(challenge())
+            .end_round()
+            .start_round("second")
+            .prover_message(commitment())
+            .verifier_message(challenge())
+            .end_round()
+            .try_build();
+        assert!(protocol.is_ok());
+    }
+
+    #[test]
+    fn test_strict_missing_end_round() {
+        let protocol = ProtocolBuilder::new("Test protocol", 256)
+            .strict()
+            .start_round("first")
+            .prover_message(commitment())
+            .verifier_message(challenge())
+            .start_round("second")
+            .prover_message(commitment())
+            .verifier_message(challenge())
+            .end_round()
+            .try_build();
+        assert_eq!(
+            protocol.unwrap_err(),
+            BuilderError::UnendedRound {
+                name: "first".to_owned()
+            }
+        );
+    }
+
+    #[test]
+    fn test_strict_no_prior_verifier_message() {
+        let protocol = ProtocolBuilder::new("Test protocol", 256)
+            .strict()
+            .start_round("first")
+            .prover_message(commitment())
+            .end_round()
+            .start_round("second")
+            .prover_message(commitment())
+            .verifier_message(challenge())
+            .end_round()
+            .try_build();
+        assert_eq!(
+            protocol.unwrap_err(),
+            BuilderError::NoPriorVerifierMessage {
+                name: "second".to_owned()
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "round first was never ended")]
+    fn test_strict_build_panics() {
+        ProtocolBuilder::new("Test protocol", 256)
+            .strict()
+            .start_round("first")
+            .prover_message(commitment())
+            .verifier_message(challenge())
+            .start_round("second")
+            .prover_message(commitment())
+            .verifier_message(challenge())
+            .end_round()
+            .build();
+    }
+}
@@ -1,11 +1,19 @@
 pub mod builder;
 pub mod proof_size;
 
-use std::fmt;
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Write},
+};
 
-use proof_size::ProofElement;
+use proof_size::{FieldElements, MerkleTree, Proof, ProofElement, ProofRound};
 
-use crate::utils::{display_size, pretty_print_float_slice};
+use crate::{
+    field::Field,
+    hash::DigestConfig,
+    utils::{display_size, float_precision, pretty_print_float_slice, sum_errors},
+    LowDegreeParameters,
+};
 
 /// A struct representing a cryptographic protocol.
 ///
@@ -24,6 +32,9 @@ pub struct Protocol {
     /// The size of the digest in bits.
     digest_size_bits: usize,
 
+    /// The bits of collision resistance of the digests (half of the digest size for a generic hash).
+    collision_bits: usize,
+
     /// The rounds involved in the protocol.
     rounds: Vec<Round>,
 }
@@ -32,11 +43,35 @@ impl Protocol {
     /// Compose two protocols together
     pub fn chain(mut self, other: Protocol) -> Self {
         assert_eq!(self.digest_size_bits, other.digest_size_bits);
+        self.collision_bits = self.collision_bits.min(other.collision_bits);
         self.protocol_name = format!("{} <> {}", self.protocol_name, other.protocol_name);
         self.rounds.extend(other.rounds);
         self
     }
 
+    /// Compose two protocols together, where `other` starts by committing to the oracle last committed to by `self`.
+    /// The duplicate Merkle root (the first one of the first round of `other`) is dropped if it matches.
+    pub fn chain_sharing_commitment(self, mut other: Protocol) -> Self {
+        let last_commitment = self
+            .rounds
+            .iter()
+            .rev()
+            .flat_map(|round| round.messages.iter().rev())
+            .find_map(Message::merkle_root);
+        if let Some(first_round) = other.rounds.first_mut() {
+            let position = first_round
+                .messages
+                .iter()
+                .position(|message| message.merkle_root().is_some());
+            if let Some(position) = position {
+                if first_round.messages[position].merkle_root() == last_commitment {
+                    first_round.messages.remove(position);
+                }
+            }
+        }
+        self.chain(other)
+    }
+
     /// Calculates the proof size in bits of the protocol
     pub fn proof_size_bits(&self) -> usize {
         self.rounds
@@ -58,12 +93,350 @@ impl Protocol {
             .sum()
     }
 
+    /// Collects the elements sent by the prover into a proof, numbering the rounds sequentially.
+    pub fn to_proof(&self) -> Proof {
+        let rounds = self
+            .rounds
+            .iter()
+            .enumerate()
+            .map(|(round_number, round)| ProofRound {
+                round_number,
+                name: round.name.clone(),
+                elements: round
+                    .messages
+                    .iter()
+                    .filter_map(|message| match message {
+                        Message::ProverMessage(prover_message) => Some(prover_message.element),
+                        Message::VerifierMessage(_) => None,
+                    })
+                    .collect(),
+            })
+            .collect();
+        Proof { rounds }
+    }
+
+    /// Compares this protocol against another one, reporting the changes in proof size and soundness
+    /// from `self` to `other`. Rounds are matched by name, summing the sizes of rounds sharing a name.
+    pub fn diff(&self, other: &Protocol) -> ProtocolDiff {
+        let mut round_deltas: Vec<(String, isize)> = Vec::new();
+        let rounds = self
+            .rounds
+            .iter()
+            .map(|round| (round, -1))
+            .chain(other.rounds.iter().map(|round| (round, 1)));
+        for (round, sign) in rounds {
+            let delta = sign * round.proof_size_bits() as isize;
+            match round_deltas
+                .iter_mut()
+                .find(|(name, _)| *name == round.name)
+            {
+                Some((_, round_delta)) => *round_delta += delta,
+                None => round_deltas.push((round.name.clone(), delta)),
+            }
+        }
+
+        ProtocolDiff {
+            proof_size_bits_delta: other.proof_size_bits() as isize
+                - self.proof_size_bits() as isize,
+            rbr_error_delta: other.rbr_error() - self.rbr_error(),
+            round_deltas,
+        }
+    }
+
+    /// Calculates the size in bits of all the Merkle queries (openings and authentication paths) of the protocol.
+    pub fn merkle_queries_size_bits(&self) -> usize {
+        self.rounds
+            .iter()
+            .flat_map(|round| round.messages.iter())
+            .filter_map(|message| match message {
+                Message::ProverMessage(ProverMessage {
+                    element: element @ ProofElement::MerkleQueries(_),
+                    ..
+                }) => Some(element.size_bits()),
+                _ => None,
+            })
+            .sum()
+    }
+
+    /// Lists every distinct Merkle tree built by the prover as `(tree_depth, leaf_num_elements, digest_size)`.
+    /// A committed tree that is queried later is listed once, while trees that are only queried
+    /// (such as the starting oracle, whose root is part of the input) are listed at their first opening.
+    pub fn merkle_summary(&self) -> Vec<(usize, usize, usize)> {
+        let mut trees: Vec<MerkleTree> = Vec::new();
+        let mut unopened: Vec<MerkleTree> = Vec::new();
+        for message in self.rounds.iter().flat_map(|round| round.messages.iter()) {
+            match message {
+                Message::ProverMessage(ProverMessage {
+                    element: ProofElement::MerkleRoot(merkle_tree),
+                    ..
+                }) => {
+                    trees.push(*merkle_tree);
+                    unopened.push(*merkle_tree);
+                }
+                Message::ProverMessage(ProverMessage {
+                    element: ProofElement::MerkleQueries(queries),
+                    ..
+                }) => match unopened
+                    .iter()
+                    .position(|tree| *tree == queries.merkle_tree)
+                {
+                    Some(index) => {
+                        unopened.remove(index);
+                    }
+                    None => trees.push(queries.merkle_tree),
+                },
+                _ => {}
+            }
+        }
+        trees
+            .into_iter()
+            .map(|tree| (tree.tree_depth, tree.leaf.num_elements, tree.digest_size))
+            .collect()
+    }
+
+    /// Computes the number of random bits drawn by the verifier over the whole protocol.
+    /// The challenges are modelled as one extension field element per folding, combination or OOD challenge,
+    /// and `tree_depth` bits per query index, where the depth is that of the Merkle tree opened at these queries
+    /// (i.e. the queries select a leaf of the folded domain).
+    /// PoW nonces and the randomness of verifier messages which reuse earlier challenges are not counted.
+    pub fn verifier_randomness_bits(&self) -> usize {
+        self.verifier_messages()
+            .map(|verifier_message| verifier_message.challenge_bits)
+            .sum()
+    }
+
+    /// Computes the size in bits of the full transcript, i.e. the proof together with the verifier challenges
+    /// (see `verifier_randomness_bits`).
+    pub fn transcript_size_bits(&self) -> usize {
+        self.proof_size_bits() + self.verifier_randomness_bits()
+    }
+
+    /// Iterates over the rounds of the protocol.
+    pub fn rounds_iter(&self) -> impl Iterator<Item = &Round> {
+        self.rounds.iter()
+    }
+
+    /// Iterates over the prover messages of the protocol, in order.
+    pub fn prover_messages(&self) -> impl Iterator<Item = &ProverMessage> {
+        self.rounds_iter()
+            .flat_map(|round| round.messages.iter())
+            .filter_map(|message| match message {
+                Message::ProverMessage(prover_message) => Some(prover_message),
+                Message::VerifierMessage(_) => None,
+            })
+    }
+
+    /// Iterates over the verifier messages of the protocol, in order.
+    pub fn verifier_messages(&self) -> impl Iterator<Item = &VerifierMessage> {
+        self.rounds_iter()
+            .flat_map(|round| round.messages.iter())
+            .filter_map(|message| match message {
+                Message::VerifierMessage(verifier_message) => Some(verifier_message),
+                Message::ProverMessage(_) => None,
+            })
+    }
+
+    /// Returns the number of rounds in the classical sense, i.e. the number of verifier messages.
+    pub fn num_classical_rounds(&self) -> usize {
+        self.rounds
+            .iter()
+            .flat_map(|round| round.messages.iter())
+            .filter(|message| message.is_verifier_message())
+            .count()
+    }
+
+    /// Returns the total number of prover messages sent in the protocol.
+    pub fn num_prover_messages(&self) -> usize {
+        self.rounds
+            .iter()
+            .flat_map(|round| round.messages.iter())
+            .filter(|message| message.is_prover_message())
+            .count()
+    }
+
+    /// Renders the message flow of the protocol as a DOT graph, with a cluster per round and a node per message.
+    /// Prover messages are labeled with their element type and size, verifier messages with their binding error term
+    /// (and the resulting round-by-round error, including PoW). Consecutive messages are joined by edges.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        writeln!(dot, "digraph \"{}\" {{", self.protocol_name).unwrap();
+        writeln!(dot, "    rankdir=TB;").unwrap();
+
+        let mut num_messages = 0;
+        for (round_index, round) in self.rounds.iter().enumerate() {
+            writeln!(dot, "    subgraph cluster_{round_index} {{").unwrap();
+            writeln!(dot, "        label=\"{}\";", round.name).unwrap();
+            for message in &round.messages {
+                let (shape, label) = match message {
+                    Message::ProverMessage(prover_message) => (
+                        "box",
+                        format!(
+                            "P: {}\\n{}",
+                            prover_message.element.element_type(),
+                            display_size(prover_message.element.size_bits())
+                        ),
+                    ),
+                    Message::VerifierMessage(verifier_message) => {
+                        let binding_term = verifier_message
+                            .rbr_errors
+                            .iter()
+                            .min_by(|a, b| a.error.partial_cmp(&b.error).unwrap())
+                            .unwrap();
+                        (
+                            "ellipse",
+                            format!(
+                                "V: {}\\n{:.1} bits",
+                                binding_term.name,
+                                verifier_message.rbr_error()
+                            ),
+                        )
+                    }
+                };
+                writeln!(
+                    dot,
+                    "        m{num_messages} [shape={shape}, label=\"{label}\"];"
+                )
+                .unwrap();
+                num_messages += 1;
+            }
+            writeln!(dot, "    }}").unwrap();
+        }
+
+        for i in 1..num_messages {
+            writeln!(dot, "    m{} -> m{i};", i - 1).unwrap();
+        }
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+
+    /// Returns a JSON summary of the protocol, including the proof size of each round and the round-by-round errors.
+    #[cfg(feature = "serde")]
+    pub fn to_json_value(&self) -> serde_json::Value {
+        let rounds: Vec<_> = self
+            .rounds
+            .iter()
+            .map(|round| {
+                serde_json::json!({
+                    "name": round.name,
+                    "proof_size_bits": round.proof_size_bits(),
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "protocol_name": self.protocol_name,
+            "digest_size_bits": self.digest_size_bits,
+            "rounds": rounds,
+            "rbr_errors": self.rbr_errors(),
+            "rbr_breakdown": self.rbr_breakdown(),
+            "proof_size_bits": self.proof_size_bits(),
+        })
+    }
+
+    /// Estimates the work done by the verifier.
+    /// Hashes are counted along the authentication paths of every Merkle query, while field multiplications
+    /// are counted as one per field element sent by the prover (e.g. evaluating a sumcheck polynomial).
+    /// In particular, evaluating the final polynomial costs one multiplication per coefficient.
+    pub fn verifier_cost(&self) -> VerifierCost {
+        let mut verifier_cost = VerifierCost::default();
+        for message in self.rounds.iter().flat_map(|round| round.messages.iter()) {
+            if let Message::ProverMessage(prover_message) = message {
+                match prover_message.element {
+                    ProofElement::MerkleQueries(queries) => {
+                        verifier_cost.hashes +=
+                            queries.num_openings * queries.merkle_tree.num_levels();
+                    }
+                    ProofElement::FieldElements(elements) => {
+                        verifier_cost.field_muls += elements.num_elements;
+                        if prover_message.is_final_polynomial {
+                            verifier_cost.final_eval_muls += elements.num_elements;
+                        }
+                    }
+                    ProofElement::MerkleRoot(_) => {}
+                }
+            }
+        }
+        verifier_cost
+    }
+
+    /// Counts the elements sent by the prover. Field elements include those in the opened Merkle leaves.
+    pub fn element_stats(&self) -> ElementStats {
+        let mut stats = ElementStats::default();
+        for message in self.rounds.iter().flat_map(|round| round.messages.iter()) {
+            if let Message::ProverMessage(prover_message) = message {
+                match prover_message.element {
+                    ProofElement::MerkleRoot(_) => stats.merkle_roots += 1,
+                    ProofElement::MerkleQueries(queries) => {
+                        stats.merkle_openings += queries.num_openings;
+                        let leaf = queries.merkle_tree.leaf;
+                        stats.add_field_elements(FieldElements {
+                            num_elements: queries.num_openings
+                                * queries.shared_leaves
+                                * leaf.num_elements,
+                            ..leaf
+                        });
+                    }
+                    ProofElement::FieldElements(elements) => stats.add_field_elements(elements),
+                }
+            }
+        }
+        stats
+    }
+
+    /// Lists the field each opened oracle is committed over, in the order in which they are opened.
+    /// This includes the starting oracle, whose root is part of the input rather than of the proof.
+    pub fn committed_field_report(&self) -> Vec<CommittedOracle> {
+        self.rounds
+            .iter()
+            .flat_map(|round| {
+                round.messages.iter().filter_map(|message| match message {
+                    Message::ProverMessage(ProverMessage {
+                        element: ProofElement::MerkleQueries(queries),
+                        ..
+                    }) => {
+                        let leaf = queries.merkle_tree.leaf;
+                        Some(CommittedOracle {
+                            round_name: round.name.clone(),
+                            field: leaf.field,
+                            is_extension: leaf.is_extension,
+                            element_size_bits: FieldElements {
+                                num_elements: 1,
+                                ..leaf
+                            }
+                            .size_bits(),
+                        })
+                    }
+                    _ => None,
+                })
+            })
+            .collect()
+    }
+
+    /// Estimates the number of constraints needed to verify all the Merkle openings in a circuit, when the digests
+    /// are computed with `digest`. Each opened leaf is hashed by absorbing one digest worth of input per compression,
+    /// and then one compression is counted per level of its path.
+    pub fn recursion_cost(&self, digest: DigestConfig) -> usize {
+        self.prover_messages()
+            .filter_map(|prover_message| match &prover_message.element {
+                ProofElement::MerkleQueries(queries) => {
+                    let merkle_tree = &queries.merkle_tree;
+                    let leaf_compressions = merkle_tree
+                        .revealed_leaf_size_bits()
+                        .div_ceil(digest.output_bits());
+                    Some(queries.num_openings * (leaf_compressions + merkle_tree.num_levels()))
+                }
+                _ => None,
+            })
+            .sum::<usize>()
+            * digest.cost_per_compression()
+    }
+
     /// Prints a display of the rounds of the protocol, including the proof size of
     /// each round, and the descriptions of the components.
     pub fn print_size_summary(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "Protocol {}", self.protocol_name)?;
         for round in &self.rounds {
-            writeln!(f, "Round: {}", round.name)?;
+            round.print_header(f)?;
             for message in &round.messages {
                 if let Message::ProverMessage(prover_message) = message {
                     writeln!(
@@ -82,23 +455,134 @@ impl Protocol {
         )
     }
 
+    /// Same as `print_size_summary`, also printing the percentage of the proof size taken by each round and element.
+    pub fn print_size_summary_with_pct(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let proof_size_bits = self.proof_size_bits();
+        let precision = float_precision(f);
+        writeln!(f, "Protocol {}", self.protocol_name)?;
+        for round in &self.rounds {
+            round.print_header(f)?;
+            for message in &round.messages {
+                if let Message::ProverMessage(prover_message) = message {
+                    let size_bits = prover_message.element.size_bits();
+                    writeln!(
+                        f,
+                        "  {}: {} ({:.precision$}%)",
+                        prover_message.element.element_type(),
+                        display_size(size_bits),
+                        percentage(size_bits, proof_size_bits)
+                    )?;
+                }
+            }
+            let size_bits = round.proof_size_bits();
+            writeln!(
+                f,
+                "  Round total: {} ({:.precision$}%)",
+                display_size(size_bits),
+                percentage(size_bits, proof_size_bits)
+            )?;
+        }
+        writeln!(f, "Total Proof Size: {}", display_size(proof_size_bits))
+    }
+
+    /// Returns the percentage of the proof size taken by each round, keyed by round name.
+    /// All percentages are zero for a protocol with an empty proof.
+    pub fn round_size_percentages(&self) -> Vec<(String, f64)> {
+        let proof_size_bits = self.proof_size_bits();
+        self.rounds
+            .iter()
+            .map(|round| {
+                (
+                    round.name.clone(),
+                    percentage(round.proof_size_bits(), proof_size_bits),
+                )
+            })
+            .collect()
+    }
+
     /// Prints a summary of the round-by-round errors in each round of the protocol.
     pub fn print_rbr_summary(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let precision = float_precision(f);
         writeln!(f, "Protocol {}", self.protocol_name)?;
         for round in &self.rounds {
-            writeln!(f, "Round: {}", round.name)?;
+            round.print_header(f)?;
             for message in &round.messages {
                 if let Message::VerifierMessage(verifier_message) = message {
-                    writeln!(f, "  Total RBR Error: {:.1}", verifier_message.rbr_error())?;
+                    writeln!(
+                        f,
+                        "  Total RBR Error: {:.precision$}",
+                        verifier_message.rbr_error()
+                    )?;
                     for rbr_error in &verifier_message.rbr_errors {
-                        writeln!(f, "    - {}: {:.1}", rbr_error.name, rbr_error.error)?;
+                        writeln!(
+                            f,
+                            "    - {}: {:.precision$}",
+                            rbr_error.name, rbr_error.error
+                        )?;
                     }
-                    writeln!(f, "    + pow_bits: {:.1}", verifier_message.pow_bits)?;
+                    writeln!(
+                        f,
+                        "    + pow_bits: {:.precision$}",
+                        verifier_message.pow_bits
+                    )?;
                 }
             }
         }
         write!(f, "RbR vector: ")?;
-        pretty_print_float_slice(f, &self.rbr_errors())
+        pretty_print_float_slice(f, &self.rbr_errors(), precision)
+    }
+
+    /// Returns the distinct names of the error terms (e.g. "folding_error", "query_error") used by the protocol.
+    pub fn error_term_names(&self) -> BTreeSet<String> {
+        self.verifier_messages()
+            .flat_map(|verifier_message| verifier_message.rbr_errors.iter())
+            .map(|rbr_error| rbr_error.name.clone())
+            .collect()
+    }
+
+    /// Returns the round-by-round errors of the protocol (one per verifier message), keeping the names of the rounds and terms.
+    pub fn rbr_breakdown(&self) -> Vec<RoundRbR> {
+        self.rounds
+            .iter()
+            .flat_map(|round| {
+                round.messages.iter().filter_map(|message| {
+                    if let Message::VerifierMessage(verifier_message) = message {
+                        Some(RoundRbR {
+                            round_name: round.name.clone(),
+                            terms: verifier_message
+                                .rbr_errors
+                                .iter()
+                                .map(|rbr_error| (rbr_error.name.clone(), rbr_error.error))
+                                .collect(),
+                            pow_bits: verifier_message.pow_bits,
+                            combined: verifier_message.rbr_error(),
+                        })
+                    } else {
+                        None
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Finds the soundness bottleneck, i.e. the term achieving `rbr_error`, as `(round_name, error_name, error)`.
+    /// The error includes the pow bits of its message, so that it equals `rbr_error`.
+    /// Returns `None` if the protocol has no verifier message with an error term.
+    pub fn binding_term(&self) -> Option<(String, String, f64)> {
+        let binding_message = self
+            .rbr_breakdown()
+            .into_iter()
+            .min_by(|a, b| a.combined.partial_cmp(&b.combined).unwrap())?;
+        let (error_name, _) = binding_message
+            .terms
+            .iter()
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())?;
+
+        Some((
+            binding_message.round_name.clone(),
+            error_name.clone(),
+            binding_message.combined,
+        ))
     }
 
     /// Returns the vector of round by round errors of the protocol.
@@ -126,8 +610,76 @@ impl Protocol {
             .unwrap()
     }
 
+    /// Returns the soundness error of the protocol obtained by summing (rather than taking the minimum of)
+    /// every error term across all rounds, i.e. -log2(sum(2^-e_i)).
+    pub fn rbr_error_summed(&self) -> f64 {
+        sum_errors(
+            self.verifier_messages()
+                .map(VerifierMessage::rbr_error_summed),
+        )
+    }
+
+    /// Computes the bits of soundness of the interactive protocol (before Fiat-Shamir), by summing every
+    /// error term across all rounds as `rbr_error_summed` does.
+    /// Unlike the compiled security, this does not pay for the 2^log_ro_queries attempts of a state-restoration attacker,
+    /// nor does it count the PoW bits, as there is nothing to grind before Fiat-Shamir.
+    pub fn interactive_soundness(&self) -> f64 {
+        sum_errors(
+            self.verifier_messages()
+                .flat_map(|verifier_message| verifier_message.rbr_errors.iter().map(|e| e.error)),
+        )
+    }
+
     /// Computes the bits of security of the protocol against an adversary performing 2^log_ro_queries classical queries to the ROM.
     pub fn compiled_classical_security(&self, log_ro_queries: usize) -> f64 {
+        let (soundness_term, digest_term) = self.classical_security_terms(log_ro_queries);
+        soundness_term.min(digest_term)
+    }
+
+    /// Computes the bits of security of the protocol against an adversary performing 2^log_ro_queries quantum queries to the QROM.
+    pub fn compiled_quantum_security(&self, log_ro_queries: usize) -> f64 {
+        let (soundness_term, digest_term) = self.quantum_security_terms(log_ro_queries);
+        soundness_term.min(digest_term)
+    }
+
+    /// Breaks down `compiled_classical_security` into labeled stages: the round-by-round error ("rbr"),
+    /// the soundness after the state restoration loss of log_ro_queries bits ("state_restoration"),
+    /// the bound from the collision resistance of the digests ("digest_bound") and their minimum ("final").
+    pub fn security_waterfall(&self, log_ro_queries: usize) -> Vec<(String, f64)> {
+        let (soundness_term, digest_term) = self.classical_security_terms(log_ro_queries);
+        vec![
+            ("rbr".to_string(), self.rbr_error()),
+            ("state_restoration".to_string(), soundness_term),
+            ("digest_bound".to_string(), digest_term),
+            ("final".to_string(), soundness_term.min(digest_term)),
+        ]
+    }
+
+    /// Checks whether the classical compiled security (see `compiled_classical_security`) reaches `target` bits,
+    /// returning the answer together with the signed margin, i.e. the achieved security minus the target.
+    pub fn meets_security(&self, target: f64, log_ro_queries: usize) -> (bool, f64) {
+        let achieved = self.compiled_classical_security(log_ro_queries);
+        (achieved >= target, achieved - target)
+    }
+
+    /// Summarizes the classical and quantum security of the protocol against 2^log_ro_queries queries.
+    pub fn security_summary(&self, log_ro_queries: usize) -> SecuritySummary {
+        let (classical_soundness, classical_digest) = self.classical_security_terms(log_ro_queries);
+        let (quantum_soundness, quantum_digest) = self.quantum_security_terms(log_ro_queries);
+        SecuritySummary {
+            log_ro_queries,
+            rbr_error: self.rbr_error(),
+            digest_size_bits: self.digest_size_bits,
+            collision_bits: self.collision_bits,
+            classical_security: classical_soundness.min(classical_digest),
+            classical_bound: SecurityBound::binding(classical_soundness, classical_digest),
+            quantum_security: quantum_soundness.min(quantum_digest),
+            quantum_bound: SecurityBound::binding(quantum_soundness, quantum_digest),
+        }
+    }
+
+    /// The (soundness, digest) terms of the classical compiled security.
+    fn classical_security_terms(&self, log_ro_queries: usize) -> (f64, f64) {
         let log_ro_queries = log_ro_queries as f64;
         let min_error = self.rbr_error();
 
@@ -137,21 +689,27 @@ impl Protocol {
         let state_restoration_error = min_error - log_ro_queries;
 
         // Thm 26.1.1 from [CY24] (assuming that 6 * l * (log l + 1) <= t and taking min instead of summing to avoid precisions issue)
-        state_restoration_error
-            .min(self.digest_size_bits as f64 - ((3_f64).log2() + 2. * log_ro_queries))
+        (
+            state_restoration_error,
+            (2 * self.collision_bits) as f64 - ((3_f64).log2() + 2. * log_ro_queries),
+        )
     }
 
-    /// Computes the bits of security of the protocol against an adversary performing 2^log_ro_queries quantum queries to the QROM.
-    pub fn compiled_quantum_security(&self, log_ro_queries: usize) -> f64 {
+    /// The (soundness, digest) terms of the quantum compiled security.
+    fn quantum_security_terms(&self, log_ro_queries: usize) -> (f64, f64) {
         let log_ro_queries = log_ro_queries as f64;
         let min_error = self.rbr_error();
 
         // Thm 8.6 in 2019/834 (again taking min instead of summing)
         // NOTE: That thm only gives asymptotics and not concrete
-        (min_error - 2. * log_ro_queries).min(self.digest_size_bits as f64 - (3. * log_ro_queries))
+        (
+            min_error - 2. * log_ro_queries,
+            (2 * self.collision_bits) as f64 - (3. * log_ro_queries),
+        )
     }
 }
 
+/// The errors and pow bits are printed with the precision of the formatter (e.g. `{:.3}`), or one decimal by default.
 impl fmt::Display for Protocol {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.print_rbr_summary(f)?;
@@ -159,15 +717,351 @@ impl fmt::Display for Protocol {
     }
 }
 
+/// An estimate of the work done by the verifier of a protocol.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VerifierCost {
+    /// Number of hash invocations (from Merkle authentication paths).
+    pub hashes: usize,
+
+    /// Number of field multiplications (from evaluating the prover's field elements).
+    pub field_muls: usize,
+
+    /// Of the field multiplications, those evaluating the final polynomial.
+    /// This dominates the verifier's field work when the protocol folds too little.
+    pub final_eval_muls: usize,
+}
+
+impl fmt::Display for VerifierCost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Verifier cost: {} hashes, {} field multiplications ({} evaluating the final polynomial)",
+            self.hashes, self.field_muls, self.final_eval_muls
+        )
+    }
+}
+
+/// The round-by-round error of a verifier message, see `Protocol::rbr_breakdown`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RoundRbR {
+    /// The name of the round the message belongs to.
+    pub round_name: String,
+
+    /// The named error terms.
+    pub terms: Vec<(String, f64)>,
+
+    /// The pow bits used in this message.
+    pub pow_bits: f64,
+
+    /// The resulting error (the minimum term plus the pow bits).
+    pub combined: f64,
+}
+
+/// Which term determines the compiled security of a protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecurityBound {
+    /// The round-by-round soundness of the interactive protocol.
+    Soundness,
+    /// The collision resistance of the Merkle digests.
+    Digest,
+}
+
+impl SecurityBound {
+    fn binding(soundness_term: f64, digest_term: f64) -> Self {
+        if digest_term < soundness_term {
+            SecurityBound::Digest
+        } else {
+            SecurityBound::Soundness
+        }
+    }
+}
+
+impl fmt::Display for SecurityBound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SecurityBound::Soundness => write!(f, "soundness"),
+            SecurityBound::Digest => write!(f, "digest"),
+        }
+    }
+}
+
+/// The security of a protocol before and after compilation, see `Protocol::security_summary`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SecuritySummary {
+    /// The log of the number of RO queries of the adversary.
+    pub log_ro_queries: usize,
+
+    /// The round-by-round soundness of the interactive protocol.
+    pub rbr_error: f64,
+
+    /// The size of the digest in bits.
+    pub digest_size_bits: usize,
+
+    /// The bits of collision resistance of the digests.
+    pub collision_bits: usize,
+
+    /// The security against classical adversaries.
+    pub classical_security: f64,
+
+    /// The term determining the classical security.
+    pub classical_bound: SecurityBound,
+
+    /// The security against quantum adversaries.
+    pub quantum_security: f64,
+
+    /// The term determining the quantum security.
+    pub quantum_bound: SecurityBound,
+}
+
+impl fmt::Display for SecuritySummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let precision = float_precision(f);
+        writeln!(
+            f,
+            "RbR error: {:.precision$}, digest: {} bits ({} bits collision resistance), RO queries: 2^{}",
+            self.rbr_error, self.digest_size_bits, self.collision_bits, self.log_ro_queries
+        )?;
+        writeln!(f, "{:<10} {:>8}  binding", "", "security")?;
+        writeln!(
+            f,
+            "{:<10} {:>8.precision$}  {}",
+            "classical", self.classical_security, self.classical_bound
+        )?;
+        writeln!(
+            f,
+            "{:<10} {:>8.precision$}  {}",
+            "quantum", self.quantum_security, self.quantum_bound
+        )
+    }
+}
+
+/// The log of the number of RO queries against which `ProtocolReport` reports the compiled security.
+pub const REPORT_LOG_RO_QUERIES: usize = 100;
+
+/// A plain-data summary of a configured protocol, the structured counterpart of its Display output.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ProtocolReport {
+    /// The size of the proof in bits.
+    pub proof_size_bits: usize,
+
+    /// The total number of queries, over all query rounds.
+    pub num_queries: usize,
+
+    /// The classical compiled security against 2^`REPORT_LOG_RO_QUERIES` queries.
+    pub compiled_classical_100: f64,
+
+    /// The quantum compiled security against 2^`REPORT_LOG_RO_QUERIES` queries.
+    pub compiled_quantum_100: f64,
+
+    /// The number of folding rounds (after the starting fold).
+    pub num_rounds: usize,
+
+    /// Degree of the final polynomial sent over.
+    pub final_poly_log_degree: usize,
+
+    /// The round-by-round soundness of the interactive protocol.
+    pub rbr_error: f64,
+}
+
+impl ProtocolReport {
+    /// Summarizes the protocol, given the figures which only its configuration knows.
+    pub fn new(
+        protocol: &Protocol,
+        num_queries: usize,
+        num_rounds: usize,
+        final_poly_log_degree: usize,
+    ) -> Self {
+        ProtocolReport {
+            proof_size_bits: protocol.proof_size_bits(),
+            num_queries,
+            compiled_classical_100: protocol.compiled_classical_security(REPORT_LOG_RO_QUERIES),
+            compiled_quantum_100: protocol.compiled_quantum_security(REPORT_LOG_RO_QUERIES),
+            num_rounds,
+            final_poly_log_degree,
+            rbr_error: protocol.rbr_error(),
+        }
+    }
+}
+
+/// Configured protocols which can summarize themselves as a `ProtocolReport`.
+pub trait Report {
+    /// Summarizes the configured protocol.
+    fn report(&self) -> ProtocolReport;
+}
+
+/// Counts of the elements sent by the prover of a protocol.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ElementStats {
+    /// Number of base field elements (including in opened leaves).
+    pub base_field_elements: usize,
+
+    /// Number of extension field elements (including in opened leaves).
+    pub extension_field_elements: usize,
+
+    /// Number of Merkle roots.
+    pub merkle_roots: usize,
+
+    /// Number of Merkle openings.
+    pub merkle_openings: usize,
+}
+
+impl ElementStats {
+    fn add_field_elements(&mut self, elements: FieldElements) {
+        if elements.is_extension {
+            self.extension_field_elements += elements.num_elements;
+        } else {
+            self.base_field_elements += elements.num_elements;
+        }
+    }
+}
+
+/// The field an oracle is committed over, see `Protocol::committed_field_report`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommittedOracle {
+    /// The name of the round in which the oracle is opened.
+    pub round_name: String,
+
+    /// The field the protocol is run over.
+    pub field: Field,
+
+    /// Whether the oracle is over the extension (rather than the base) field.
+    pub is_extension: bool,
+
+    /// The size in bits of a single element of the oracle.
+    pub element_size_bits: usize,
+}
+
+impl fmt::Display for CommittedOracle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} {} field ({} bits/element)",
+            self.round_name,
+            self.field.name,
+            if self.is_extension {
+                "extension"
+            } else {
+                "base"
+            },
+            self.element_size_bits
+        )
+    }
+}
+
+/// The difference between two protocols, see `Protocol::diff`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtocolDiff {
+    /// The change in proof size (in bits).
+    pub proof_size_bits_delta: isize,
+
+    /// The change in round-by-round soundness (in bits of security).
+    pub rbr_error_delta: f64,
+
+    /// The change in proof size (in bits) of the rounds, keyed by round name.
+    pub round_deltas: Vec<(String, isize)>,
+}
+
+impl fmt::Display for ProtocolDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Proof size: {}",
+            display_size_delta(self.proof_size_bits_delta)
+        )?;
+        let precision = float_precision(f);
+        writeln!(
+            f,
+            "RbR error: {:+.precision$} bits security",
+            self.rbr_error_delta
+        )?;
+        for (name, delta) in &self.round_deltas {
+            writeln!(f, "  {}: {}", name, display_size_delta(*delta))?;
+        }
+        Ok(())
+    }
+}
+
+/// The total size in bits of the oracles committed to by the prover, given the (log) size of each evaluation domain.
+/// The starting oracle (the first domain) stores the `batch_size` polynomials over the base field, the others a single
+/// polynomial over the extension. This is the working set of the prover rather than the proof size.
+pub fn total_oracle_bits(
+    ldt_parameters: LowDegreeParameters,
+    domain_log_sizes: impl IntoIterator<Item = usize>,
+) -> usize {
+    let field = ldt_parameters.field;
+    domain_log_sizes
+        .into_iter()
+        .enumerate()
+        .map(|(i, domain_log_size)| {
+            let oracle_bits = if i == 0 {
+                ldt_parameters.batch_size * field.field_size_bits
+            } else {
+                field.extension_bit_size()
+            };
+            (1 << domain_log_size) * oracle_bits
+        })
+        .sum()
+}
+
+/// Displays a signed change in size.
+fn display_size_delta(bits: isize) -> String {
+    let sign = if bits < 0 { "-" } else { "+" };
+    format!("{}{}", sign, display_size(bits.unsigned_abs()))
+}
+
+/// Computes `part` as a percentage of `total`, returning zero if `total` is.
+fn percentage(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.
+    } else {
+        100. * part as f64 / total as f64
+    }
+}
+
 /// Represents a round of the protocol
 /// NOTE: This groups a number of messages into a single round.
 /// The number of rounds in a classical sense is obtained by counting the number of verifier messages.
 #[derive(Debug, Clone)]
 pub struct Round {
     name: String,
+    note: Option<String>,
     messages: Vec<Message>,
 }
 
+impl Round {
+    /// The name of the round.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The messages exchanged in the round, in order.
+    pub fn messages(&self) -> &[Message] {
+        &self.messages
+    }
+
+    /// Prints the name of the round, followed by its note (if any).
+    fn print_header(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Round: {}", self.name)?;
+        if let Some(note) = &self.note {
+            writeln!(f, "  Note: {note}")?;
+        }
+        Ok(())
+    }
+
+    /// Calculates the size in bits of the prover messages in this round.
+    fn proof_size_bits(&self) -> usize {
+        self.messages
+            .iter()
+            .filter_map(|message| match message {
+                Message::ProverMessage(prover_message) => Some(prover_message.element.size_bits()),
+                Message::VerifierMessage(_) => None,
+            })
+            .sum()
+    }
+}
+
 /// A message exchanged in the protocol
 #[derive(Debug, Clone)]
 pub enum Message {
@@ -183,18 +1077,53 @@ impl Message {
     pub fn is_verifier_message(&self) -> bool {
         matches!(self, Message::VerifierMessage(_))
     }
+
+    /// The committed Merkle tree, if this is a prover message sending a Merkle root.
+    pub fn merkle_root(&self) -> Option<MerkleTree> {
+        match self {
+            Message::ProverMessage(ProverMessage {
+                element: ProofElement::MerkleRoot(merkle_tree),
+                ..
+            }) => Some(*merkle_tree),
+            _ => None,
+        }
+    }
 }
 
 /// Represents a message sent from the prover to the verifier.
 #[derive(Debug, Clone)]
 pub struct ProverMessage {
     element: ProofElement,
+    is_final_polynomial: bool,
 }
 
 impl ProverMessage {
     /// Creates a prover message
     pub fn new(element: ProofElement) -> Self {
-        Self { element }
+        Self {
+            element,
+            is_final_polynomial: false,
+        }
+    }
+
+    /// Creates a prover message sending the coefficients of the final polynomial, which the verifier evaluates.
+    /// Checking it against the last oracle needs no error term of its own: a wrong final polynomial agrees with
+    /// the oracle on all the final queries with at most the probability bounded by the `query_error` of the final round.
+    pub fn final_polynomial(coefficients: FieldElements) -> Self {
+        Self {
+            element: ProofElement::FieldElements(coefficients),
+            is_final_polynomial: true,
+        }
+    }
+
+    /// The element sent by the prover.
+    pub fn element(&self) -> &ProofElement {
+        &self.element
+    }
+
+    /// Whether this message sends the final polynomial.
+    pub fn is_final_polynomial(&self) -> bool {
+        self.is_final_polynomial
     }
 }
 
@@ -203,6 +1132,7 @@ impl ProverMessage {
 pub struct VerifierMessage {
     rbr_errors: Vec<RbRError>,
     pow_bits: f64,
+    challenge_bits: usize,
 }
 
 impl VerifierMessage {
@@ -211,9 +1141,21 @@ impl VerifierMessage {
         Self {
             rbr_errors,
             pow_bits,
+            challenge_bits: 0,
         }
     }
 
+    /// Sets the bits of randomness sent by the verifier in this message, see `Protocol::verifier_randomness_bits`.
+    pub fn with_challenge_bits(mut self, challenge_bits: usize) -> Self {
+        self.challenge_bits = challenge_bits;
+        self
+    }
+
+    /// The pow bits used in this message.
+    pub fn pow_bits(&self) -> f64 {
+        self.pow_bits
+    }
+
     /// Computes the overall round-by-round error of this protocol
     pub fn rbr_error(&self) -> f64 {
         // Note this is actually improper, we are taking min instead of summing
@@ -225,6 +1167,11 @@ impl VerifierMessage {
             .unwrap()
             + self.pow_bits
     }
+
+    /// Computes the round-by-round error of this message by summing the error terms.
+    pub fn rbr_error_summed(&self) -> f64 {
+        sum_errors(self.rbr_errors.iter().map(|e| e.error)) + self.pow_bits
+    }
 }
 
 /// Represents a round-by-round error incurred by the protocol.
@@ -242,3 +1189,470 @@ impl RbRError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        builder::ProtocolBuilder,
+        proof_size::{Encoding, FieldElements, MerkleTree, ProofElement},
+        ProverMessage, RbRError, Round, SecurityBound, VerifierMessage,
+    };
+    use crate::field::GOLDILOCKS_2;
+
+    #[test]
+    fn test_message_counts() {
+        let protocol = ProtocolBuilder::new("Test protocol", 256)
+            .start_round("commit")
+            .prover_message(ProverMessage::new(ProofElement::MerkleRoot(
+                MerkleTree::new(10, GOLDILOCKS_2, 2, false),
+            )))
+            .verifier_message(VerifierMessage::new(
+                vec![RbRError::new("folding_error", 100.)],
+                0.,
+            ))
+            .end_round()
+            .start_round("final")
+            .verifier_message(VerifierMessage::new(
+                vec![RbRError::new("query_error", 90.)],
+                10.,
+            ))
+            .prover_message(ProverMessage::new(ProofElement::FieldElements(
+                FieldElements {
+                    field: GOLDILOCKS_2,
+                    num_elements: 4,
+                    is_extension: true,
+                    encoding: Encoding::Bitpacked,
+                },
+            )))
+            .prover_message(ProverMessage::new(ProofElement::FieldElements(
+                FieldElements {
+                    field: GOLDILOCKS_2,
+                    num_elements: 1,
+                    is_extension: true,
+                    encoding: Encoding::Bitpacked,
+                },
+            )))
+            .end_round()
+            .build();
+
+        assert_eq!(protocol.num_classical_rounds(), 2);
+        assert_eq!(protocol.num_prover_messages(), 3);
+    }
+
+    #[test]
+    fn test_chain_sharing_commitment() {
+        let merkle_tree = MerkleTree::new(10, GOLDILOCKS_2, 2, false);
+        let commit = |name: &str| {
+            ProtocolBuilder::new(name, 256)
+                .start_round("commit")
+                .prover_message(ProverMessage::new(ProofElement::MerkleRoot(merkle_tree)))
+                .verifier_message(VerifierMessage::new(
+                    vec![RbRError::new("folding_error", 100.)],
+                    0.,
+                ))
+                .end_round()
+                .build()
+        };
+
+        let naive = commit("Sumcheck").chain(commit("PCS"));
+        let shared = commit("Sumcheck").chain_sharing_commitment(commit("PCS"));
+        assert_eq!(shared.proof_size_bits() + 256, naive.proof_size_bits());
+        assert_eq!(
+            shared.num_prover_messages() + 1,
+            naive.num_prover_messages()
+        );
+
+        // A different commitment is not shared
+        let other = ProtocolBuilder::new("PCS", 256)
+            .start_round("commit")
+            .prover_message(ProverMessage::new(ProofElement::MerkleRoot(
+                MerkleTree::new(11, GOLDILOCKS_2, 2, false),
+            )))
+            .end_round()
+            .build();
+        assert_eq!(
+            commit("Sumcheck")
+                .chain_sharing_commitment(other.clone())
+                .proof_size_bits(),
+            commit("Sumcheck").chain(other).proof_size_bits()
+        );
+    }
+
+    #[test]
+    fn test_round_note() {
+        let protocol = ProtocolBuilder::new("Test protocol", 256)
+            .start_round("masking")
+            .round_note("this round is the zk masking round")
+            .prover_message(ProverMessage::new(ProofElement::FieldElements(
+                FieldElements::extension(GOLDILOCKS_2, 4),
+            )))
+            .verifier_message(VerifierMessage::new(
+                vec![RbRError::new("folding_error", 100.)],
+                0.,
+            ))
+            .end_round()
+            .start_round("final")
+            .prover_message(ProverMessage::new(ProofElement::FieldElements(
+                FieldElements::extension(GOLDILOCKS_2, 1),
+            )))
+            .end_round()
+            .build();
+
+        let formatted = protocol.to_string();
+        // Once in the RbR summary and once in the size summary
+        assert_eq!(
+            formatted
+                .matches("Round: masking\n  Note: this round is the zk masking round\n")
+                .count(),
+            2
+        );
+        assert_eq!(formatted.matches("Note:").count(), 2);
+    }
+
+    #[test]
+    fn test_round_size_percentages() {
+        let protocol = ProtocolBuilder::new("Test protocol", 256)
+            .start_round("commit")
+            .prover_message(ProverMessage::new(ProofElement::MerkleRoot(
+                MerkleTree::new(10, GOLDILOCKS_2, 2, false),
+            )))
+            .verifier_message(VerifierMessage::new(
+                vec![RbRError::new("folding_error", 100.)],
+                0.,
+            ))
+            .end_round()
+            .start_round("final")
+            .prover_message(ProverMessage::new(ProofElement::FieldElements(
+                FieldElements::extension(GOLDILOCKS_2, 6),
+            )))
+            .end_round()
+            .build();
+
+        // A 256-bit root and 6 * 128 bits of field elements
+        let percentages = protocol.round_size_percentages();
+        assert_eq!(percentages[0], ("commit".to_owned(), 25.));
+        assert!((percentages.iter().map(|(_, pct)| pct).sum::<f64>() - 100.).abs() < 1e-9);
+
+        let empty = ProtocolBuilder::new("Empty protocol", 256)
+            .start_round("challenge")
+            .verifier_message(VerifierMessage::new(
+                vec![RbRError::new("folding_error", 100.)],
+                0.,
+            ))
+            .end_round()
+            .build();
+        assert_eq!(
+            empty.round_size_percentages(),
+            vec![("challenge".to_owned(), 0.)]
+        );
+    }
+
+    #[test]
+    fn test_rbr_error_summed() {
+        let protocol = ProtocolBuilder::new("Test protocol", 256)
+            .start_round("round")
+            .verifier_message(VerifierMessage::new(
+                vec![
+                    RbRError::new("folding_error", 100.),
+                    RbRError::new("query_error", 100.),
+                ],
+                0.,
+            ))
+            .end_round()
+            .build();
+
+        assert_eq!(protocol.rbr_error(), 100.);
+        assert!((protocol.rbr_error_summed() - 99.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_transcript_size_bits() {
+        let with_challenges = |num_challenges: usize| {
+            let mut builder = ProtocolBuilder::new("Test protocol", 256)
+                .start_round("commit")
+                .prover_message(ProverMessage::new(ProofElement::MerkleRoot(
+                    MerkleTree::new(10, GOLDILOCKS_2, 2, false),
+                )));
+            for _ in 0..num_challenges {
+                builder = builder.verifier_message(
+                    VerifierMessage::new(vec![RbRError::new("folding_error", 100.)], 0.)
+                        .with_challenge_bits(128),
+                );
+            }
+            builder.end_round().build()
+        };
+
+        // The proof is the same, but each challenge adds to the transcript
+        for num_challenges in 1..4 {
+            let protocol = with_challenges(num_challenges);
+            assert_eq!(protocol.proof_size_bits(), 256);
+            assert_eq!(protocol.transcript_size_bits(), 256 + num_challenges * 128);
+        }
+    }
+
+    #[test]
+    fn test_message_iterators() {
+        let protocol = ProtocolBuilder::new("Test protocol", 256)
+            .start_round("commit")
+            .prover_message(ProverMessage::new(ProofElement::MerkleRoot(
+                MerkleTree::new(10, GOLDILOCKS_2, 2, false),
+            )))
+            .verifier_message(VerifierMessage::new(
+                vec![RbRError::new("folding_error", 100.)],
+                5.,
+            ))
+            .end_round()
+            .start_round("final")
+            .prover_message(ProverMessage::new(ProofElement::FieldElements(
+                FieldElements::extension(GOLDILOCKS_2, 4),
+            )))
+            .end_round()
+            .build();
+
+        assert_eq!(
+            protocol.prover_messages().count(),
+            protocol.num_prover_messages()
+        );
+        assert_eq!(
+            protocol.verifier_messages().count(),
+            protocol.num_classical_rounds()
+        );
+        assert_eq!(
+            protocol
+                .prover_messages()
+                .map(|message| message.element().size_bits())
+                .sum::<usize>(),
+            protocol.proof_size_bits()
+        );
+        assert_eq!(
+            protocol
+                .verifier_messages()
+                .map(VerifierMessage::pow_bits)
+                .sum::<f64>(),
+            5.
+        );
+        let round_names: Vec<_> = protocol.rounds_iter().map(Round::name).collect();
+        assert_eq!(round_names, vec!["commit", "final"]);
+    }
+
+    #[test]
+    fn test_display_precision() {
+        let protocol = ProtocolBuilder::new("Test protocol", 256)
+            .start_round("commit")
+            .prover_message(ProverMessage::new(ProofElement::MerkleRoot(
+                MerkleTree::new(10, GOLDILOCKS_2, 2, false),
+            )))
+            .verifier_message(VerifierMessage::new(
+                vec![RbRError::new("folding_error", 100.12345)],
+                0.,
+            ))
+            .end_round()
+            .build();
+
+        let default = protocol.to_string();
+        assert_eq!(default, format!("{protocol:.1}"));
+        assert!(default.contains("    - folding_error: 100.1\n"));
+        assert!(default.contains("RbR vector: [100.1]"));
+
+        let precise = format!("{protocol:.3}");
+        assert!(precise.contains("    - folding_error: 100.123\n"));
+        assert!(precise.contains("RbR vector: [100.123]"));
+        assert!(precise.len() > default.len());
+
+        let diff = protocol.diff(&protocol);
+        assert!(diff.to_string().contains("RbR error: +0.0 bits security"));
+        assert!(format!("{diff:.3}").contains("RbR error: +0.000 bits security"));
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let protocol = ProtocolBuilder::new("Test protocol", 256)
+            .start_round("commit")
+            .prover_message(ProverMessage::new(ProofElement::MerkleRoot(
+                MerkleTree::new(10, GOLDILOCKS_2, 2, false),
+            )))
+            .verifier_message(VerifierMessage::new(
+                vec![
+                    RbRError::new("folding_error", 100.),
+                    RbRError::new("sumcheck_error", 90.),
+                ],
+                5.,
+            ))
+            .end_round()
+            .start_round("final")
+            .prover_message(ProverMessage::new(ProofElement::FieldElements(
+                FieldElements::extension(GOLDILOCKS_2, 4),
+            )))
+            .end_round()
+            .build();
+
+        let dot = protocol.to_dot();
+        assert!(dot.starts_with("digraph \"Test protocol\" {"));
+        assert_eq!(dot.matches("subgraph cluster_").count(), 2);
+        assert_eq!(dot.matches("[shape=").count(), 3);
+        assert!(dot.contains("m0 [shape=box, label=\"P: MerkleRoot\\n"));
+        assert!(dot.contains("m1 [shape=ellipse, label=\"V: sumcheck_error\\n95.0 bits\"];"));
+        assert!(dot.contains("m2 [shape=box, label=\"P: FieldElements\\n"));
+        assert!(dot.contains("m0 -> m1;") && dot.contains("m1 -> m2;"));
+    }
+
+    #[test]
+    fn test_binding_term() {
+        let protocol = ProtocolBuilder::new("Test protocol", 256)
+            .start_round("commit")
+            .verifier_message(VerifierMessage::new(
+                vec![
+                    RbRError::new("folding_error", 100.),
+                    RbRError::new("sumcheck_error", 95.),
+                ],
+                0.,
+            ))
+            .end_round()
+            .start_round("query")
+            .verifier_message(VerifierMessage::new(
+                vec![
+                    RbRError::new("query_error", 80.),
+                    RbRError::new("batching_error", 120.),
+                ],
+                10.,
+            ))
+            .end_round()
+            .build();
+
+        // The query error is the smallest term, and with the pow bits it is still below the sumcheck error
+        assert_eq!(
+            protocol.binding_term(),
+            Some(("query".to_owned(), "query_error".to_owned(), 90.))
+        );
+        assert_eq!(protocol.binding_term().unwrap().2, protocol.rbr_error());
+
+        // Without verifier messages there is no bottleneck
+        let protocol = ProtocolBuilder::new("Test protocol", 256)
+            .start_round("final")
+            .prover_message(ProverMessage::new(ProofElement::FieldElements(
+                FieldElements {
+                    field: GOLDILOCKS_2,
+                    num_elements: 4,
+                    is_extension: true,
+                    encoding: Encoding::Bitpacked,
+                },
+            )))
+            .end_round()
+            .build();
+        assert_eq!(protocol.binding_term(), None);
+    }
+
+    #[test]
+    fn test_interactive_soundness() {
+        let protocol = |pow_bits| {
+            ProtocolBuilder::new("Test protocol", 256)
+                .start_round("commit")
+                .verifier_message(VerifierMessage::new(
+                    vec![
+                        RbRError::new("folding_error", 100.),
+                        RbRError::new("sumcheck_error", 110.),
+                    ],
+                    pow_bits,
+                ))
+                .end_round()
+                .start_round("final")
+                .verifier_message(VerifierMessage::new(
+                    vec![RbRError::new("query_error", 110.)],
+                    0.,
+                ))
+                .end_round()
+                .build()
+        };
+
+        // Every error term is summed, as in `rbr_error_summed`
+        let unground = protocol(0.);
+        let expected = -(2_f64.powf(-100.) + 2. * 2_f64.powf(-110.)).log2();
+        assert!((unground.interactive_soundness() - expected).abs() < 1e-9);
+        assert!((unground.interactive_soundness() - unground.rbr_error_summed()).abs() < 1e-9);
+        for log_ro_queries in [1, 10, 60] {
+            assert!(
+                unground.interactive_soundness()
+                    >= unground.compiled_classical_security(log_ro_queries)
+            );
+        }
+
+        // There is no grinding before Fiat-Shamir, so the pow bits do not count
+        let ground = protocol(10.);
+        assert_eq!(
+            ground.interactive_soundness(),
+            unground.interactive_soundness()
+        );
+        assert!(ground.rbr_error_summed() > ground.interactive_soundness());
+    }
+
+    #[test]
+    fn test_security_waterfall() {
+        let protocol = ProtocolBuilder::new("Test protocol", 128)
+            .start_round("round")
+            .verifier_message(VerifierMessage::new(
+                vec![RbRError::new("query_error", 100.)],
+                20.,
+            ))
+            .end_round()
+            .build();
+
+        for log_ro_queries in [0, 32, 64] {
+            let waterfall = protocol.security_waterfall(log_ro_queries);
+            let stages: Vec<_> = waterfall.iter().map(|(name, _)| name.as_str()).collect();
+            assert_eq!(
+                stages,
+                vec!["rbr", "state_restoration", "digest_bound", "final"]
+            );
+            assert_eq!(waterfall[0].1, 120.);
+            assert_eq!(waterfall[1].1, 120. - log_ro_queries as f64);
+            assert_eq!(
+                waterfall[3].1,
+                protocol.compiled_classical_security(log_ro_queries)
+            );
+        }
+    }
+
+    #[test]
+    fn test_meets_security() {
+        let protocol = ProtocolBuilder::new("Test protocol", 256)
+            .start_round("round")
+            .verifier_message(VerifierMessage::new(
+                vec![RbRError::new("query_error", 100.)],
+                0.,
+            ))
+            .end_round()
+            .build();
+
+        assert_eq!(protocol.compiled_classical_security(0), 100.);
+        assert_eq!(protocol.meets_security(80., 0), (true, 20.));
+        assert_eq!(protocol.meets_security(100., 0), (true, 0.));
+        assert_eq!(protocol.meets_security(128., 0), (false, -28.));
+    }
+
+    #[test]
+    fn test_security_summary_digest_bound() {
+        let protocol = ProtocolBuilder::new("Test protocol", 128)
+            .start_round("round")
+            .verifier_message(VerifierMessage::new(
+                vec![RbRError::new("query_error", 100.)],
+                28.,
+            ))
+            .end_round()
+            .build();
+
+        let summary = protocol.security_summary(32);
+        assert_eq!(summary.rbr_error, 128.);
+        assert_eq!(
+            summary.classical_security,
+            protocol.compiled_classical_security(32)
+        );
+        assert_eq!(summary.classical_bound, SecurityBound::Digest);
+        // A 128-bit digest only gives 128 - 3 * 32 bits against quantum adversaries
+        assert_eq!(summary.quantum_security, 128. - 3. * 32.);
+        assert_eq!(summary.quantum_bound, SecurityBound::Digest);
+        assert_eq!(
+            summary.quantum_security,
+            protocol.compiled_quantum_security(32)
+        );
+    }
+}
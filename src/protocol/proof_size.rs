@@ -1,8 +1,81 @@
 //! Misc utilities for computing proof size
-use crate::field::Field;
+use std::fmt;
+
+use crate::{field::Field, hash::DigestConfig, utils::display_size};
+
+/// A proof, i.e. the elements sent by the prover grouped by round.
+#[derive(Debug, Clone)]
+pub struct Proof {
+    /// The rounds of the proof.
+    pub rounds: Vec<ProofRound>,
+}
+
+impl Proof {
+    /// Computes the total number of bits of the proof.
+    pub fn total_size_bits(&self) -> usize {
+        self.rounds.iter().map(ProofRound::size_bits).sum()
+    }
+}
+
+impl fmt::Display for Proof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for round in &self.rounds {
+            writeln!(
+                f,
+                "Round {} ({}): {}",
+                round.round_number,
+                round.name,
+                display_size(round.size_bits())
+            )?;
+            for element in &round.elements {
+                match element {
+                    ProofElement::MerkleQueries(queries) => writeln!(
+                        f,
+                        "  {}: {} (expected), {} (upper bound)",
+                        element.element_type(),
+                        display_size(queries.expected_size_bits()),
+                        display_size(element.size_bits())
+                    )?,
+                    _ => writeln!(
+                        f,
+                        "  {}: {}",
+                        element.element_type(),
+                        display_size(element.size_bits())
+                    )?,
+                }
+            }
+        }
+        writeln!(
+            f,
+            "Total Proof Size: {}",
+            display_size(self.total_size_bits())
+        )
+    }
+}
+
+/// The elements sent by the prover in a single round.
+#[derive(Debug, Clone)]
+pub struct ProofRound {
+    /// The (sequential) number of the round.
+    pub round_number: usize,
+
+    /// The name of the round.
+    pub name: String,
+
+    /// The elements sent in the round.
+    pub elements: Vec<ProofElement>,
+}
+
+impl ProofRound {
+    /// Computes the number of bits sent in the round.
+    pub fn size_bits(&self) -> usize {
+        self.elements.iter().map(ProofElement::size_bits).sum()
+    }
+}
 
 /// A token which is part of the argument string
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum ProofElement {
     /// A Merkle root
     MerkleRoot(MerkleTree),
@@ -34,7 +107,8 @@ impl ProofElement {
 }
 
 /// Represents a Merkle tree
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct MerkleTree {
     /// The elements in the leaf of the tree
     pub leaf: FieldElements,
@@ -44,70 +118,302 @@ pub struct MerkleTree {
 
     /// How large is the hash digest
     pub digest_size: usize,
+
+    /// How many children each internal node has (2 for a binary tree)
+    pub arity: usize,
+
+    /// The bits of randomness salting each leaf (0 unless zero-knowledge)
+    pub salt_bits: usize,
+}
+
+/// The digest size (in bits) needed for collision resistance at the given security level.
+/// This is 2 * security_level, rounded up to a byte boundary.
+pub fn digest_size_bits_for_security_level(security_level: usize) -> usize {
+    (2 * security_level).div_ceil(8) * 8
+}
+
+/// The size and collision resistance (in bits) of the Merkle digests.
+/// A concrete `digest` is taken at its exact output size. Otherwise the digest is generic, with `digest_size_bits` bits
+/// or, if that is `None`, sized for the security level. The collision resistance is at most half the size.
+pub fn merkle_digest_bits(
+    digest: Option<DigestConfig>,
+    digest_size_bits: Option<usize>,
+    security_level: usize,
+) -> (usize, usize) {
+    match digest {
+        Some(digest) => {
+            let output_bits = digest.output_bits();
+            (output_bits, digest.collision_bits().min(output_bits / 2))
+        }
+        None => {
+            let digest_size_bits = digest_size_bits
+                .unwrap_or_else(|| digest_size_bits_for_security_level(security_level));
+            (digest_size_bits, digest_size_bits / 2)
+        }
+    }
 }
 
 impl MerkleTree {
+    /// Creates a Merkle tree using 256-bit digests.
     pub fn new(tree_depth: usize, field: Field, leaf_size: usize, is_extension: bool) -> Self {
+        Self::with_digest_size(tree_depth, field, leaf_size, is_extension, 256)
+    }
+
+    /// Creates a Merkle tree whose digests are sized for the given security level.
+    pub fn with_security_level(
+        tree_depth: usize,
+        field: Field,
+        leaf_size: usize,
+        is_extension: bool,
+        security_level: usize,
+    ) -> Self {
+        Self::with_digest_size(
+            tree_depth,
+            field,
+            leaf_size,
+            is_extension,
+            digest_size_bits_for_security_level(security_level),
+        )
+    }
+
+    /// Creates a binary Merkle tree with an explicit digest size (in bits).
+    pub fn with_digest_size(
+        tree_depth: usize,
+        field: Field,
+        leaf_size: usize,
+        is_extension: bool,
+        digest_size: usize,
+    ) -> Self {
+        Self::with_arity(tree_depth, field, leaf_size, is_extension, digest_size, 2)
+    }
+
+    /// Creates a k-ary Merkle tree with an explicit digest size (in bits).
+    pub fn with_arity(
+        tree_depth: usize,
+        field: Field,
+        leaf_size: usize,
+        is_extension: bool,
+        digest_size: usize,
+        arity: usize,
+    ) -> Self {
+        assert!(arity >= 2, "Merkle tree arity should be at least 2");
         MerkleTree {
             leaf: FieldElements {
                 field,
                 num_elements: leaf_size,
                 is_extension,
+                encoding: Encoding::default(),
             },
             tree_depth,
-            digest_size: 256, // TODO: we might change this based on security level
+            digest_size,
+            arity,
+            salt_bits: 0,
         }
     }
+
+    /// Sets the number of bits of randomness salting each leaf.
+    pub fn with_salt_bits(mut self, salt_bits: usize) -> Self {
+        self.salt_bits = salt_bits;
+        self
+    }
+
+    /// The size of a leaf when revealed, i.e. including its salt.
+    pub fn revealed_leaf_size_bits(&self) -> usize {
+        self.leaf.size_bits() + self.salt_bits
+    }
+
+    /// Sets the encoding of the elements in the leaves.
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.leaf.encoding = encoding;
+        self
+    }
+
+    /// The number of levels of the tree, i.e. log_arity(2^tree_depth) rounded up.
+    pub fn num_levels(&self) -> usize {
+        (self.tree_depth as f64 / (self.arity as f64).log2()).ceil() as usize
+    }
 }
 
 /// Represents the opening to a merkle tree
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct MerkleQueries {
     /// The corresponding tree
     pub merkle_tree: MerkleTree,
 
     /// How many openings are requested
     pub num_openings: usize,
+
+    /// How many leaves (one per polynomial, e.g. for batched polynomials) are stored at each position.
+    /// These are opened at the same query positions and hashed together, so they share one authentication path.
+    /// NOTE: This is the same as a single leaf with `shared_leaves` times as many elements.
+    pub shared_leaves: usize,
 }
 
 impl MerkleQueries {
     /// Computes the number of copath elements in an authentication path.
     /// Includes path pruning done to deduplicate and reduce proof size.
+    /// The siblings of the opened leaves are not included, see `leaf_siblings_size`.
     pub fn copath_elements(&self) -> usize {
-        let log_num_openings = (self.num_openings as f64).log2().ceil() as usize;
+        let log_arity = (self.merkle_tree.arity as f64).log2();
+        // The top levels are shared between the openings
+        let shared_levels = ((self.num_openings as f64).log2() / log_arity).ceil() as usize;
+        // The leaf level is covered by the leaf siblings
+        let num_levels = self.merkle_tree.num_levels().saturating_sub(1);
         // If we are opening the entire tree, just don't send anything
-        if log_num_openings >= self.merkle_tree.tree_depth {
+        if shared_levels >= num_levels {
             return 0;
         }
 
-        self.num_openings * (self.merkle_tree.tree_depth - log_num_openings)
+        // Each level of the path reveals all the siblings of the node
+        self.num_openings * (num_levels - shared_levels) * (self.merkle_tree.arity - 1)
+    }
+
+    /// Computes the expected number of distinct copath elements when the openings are at uniformly random leaves.
+    /// A level with `n` nodes has `n * (1 - (1 - 1/n)^q)` of them on some path in expectation (by occupancy),
+    /// and the copath elements at a level are the children of the touched nodes above which are not touched themselves.
+    /// As in `copath_elements`, the siblings of the opened leaves are not included.
+    /// This is below `copath_elements` unless the openings are about as many as the leaves,
+    /// where `copath_elements` assumes that the whole tree is opened.
+    pub fn expected_copath_elements(&self) -> f64 {
+        let arity = self.merkle_tree.arity as f64;
+        let num_openings = self.num_openings as f64;
+        let num_levels = self.merkle_tree.num_levels();
+        let touched_nodes = |level: usize| {
+            let num_nodes = arity.powi((num_levels - level) as i32);
+            num_nodes * (1. - (1. - 1. / num_nodes).powf(num_openings))
+        };
+
+        (1..num_levels)
+            .map(|level| arity * touched_nodes(level + 1) - touched_nodes(level))
+            .sum()
     }
 
     /// Computes the size of an authentication path.
     pub fn copath_size(&self) -> usize {
-        // We either reveal the neighbouring leaf or its digest, depending on which is shorter
+        self.leaf_siblings_size() + self.copath_elements() * self.merkle_tree.digest_size
+    }
+
+    /// Computes the expected size of an authentication path, see `expected_copath_elements`.
+    pub fn expected_copath_size(&self) -> usize {
+        self.leaf_siblings_size()
+            + (self.expected_copath_elements() * self.merkle_tree.digest_size as f64).ceil()
+                as usize
+    }
+
+    /// Computes the size of the siblings of the opened leaves.
+    fn leaf_siblings_size(&self) -> usize {
+        // We either reveal the neighbouring leaves (with their salt) or their digest, depending on which is shorter
         self.num_openings
+            * (self.merkle_tree.arity - 1)
             * self
-                .merkle_tree
-                .leaf
-                .size_bits()
+                .revealed_leaves_size_bits()
                 .min(self.merkle_tree.digest_size)
-            + self.copath_elements() * self.merkle_tree.digest_size
+    }
+
+    /// The size of the leaves at a position when revealed, i.e. including their (single) salt.
+    fn revealed_leaves_size_bits(&self) -> usize {
+        self.shared_leaves * self.merkle_tree.leaf.size_bits() + self.merkle_tree.salt_bits
+    }
+
+    /// Compute the size of the openings of a single one of the shared leaves (excluding the salt).
+    pub fn opening_size_per_poly(&self) -> usize {
+        self.num_openings * self.merkle_tree.leaf.size_bits()
     }
 
     /// Compute the size of an opening.
     pub fn opening_size(&self) -> usize {
-        self.num_openings * self.merkle_tree.leaf.size_bits()
+        self.num_openings * self.revealed_leaves_size_bits()
     }
 
     /// Computes the total size, includes the auth path and the opening.
+    /// This is an upper bound on the size for random openings, see `expected_size_bits`.
     pub fn estimate_size_bits(&self) -> usize {
         self.copath_size() + self.opening_size()
     }
+
+    /// Computes the expected total size for openings at uniformly random leaves.
+    pub fn expected_size_bits(&self) -> usize {
+        self.expected_copath_size() + self.opening_size()
+    }
+}
+
+/// How field elements are serialized in the proof.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Encoding {
+    /// Each base field element takes exactly `field_size_bits` bits.
+    #[default]
+    Bitpacked,
+    /// Each base field element is padded to a whole number of bytes.
+    ByteAligned,
+    /// Each base field element is padded to a whole number of 64-bit words.
+    WordAligned,
+}
+
+impl Encoding {
+    /// The number of bits used to encode a base field element of `field_size_bits` bits.
+    pub fn encoded_size_bits(&self, field_size_bits: usize) -> usize {
+        match self {
+            Encoding::Bitpacked => field_size_bits,
+            Encoding::ByteAligned => field_size_bits.div_ceil(8) * 8,
+            Encoding::WordAligned => field_size_bits.div_ceil(64) * 64,
+        }
+    }
+}
+
+/// How the batched polynomials are committed to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum InterleavingMode {
+    /// A single tree, each leaf storing the evaluations of all the polynomials, opened with one authentication path.
+    #[default]
+    Interleaved,
+    /// One tree per polynomial, each opened with its own authentication path.
+    Separate,
+}
+
+impl InterleavingMode {
+    /// The number of trees committing to `batch_size` polynomials.
+    pub fn num_trees(&self, batch_size: usize) -> usize {
+        match self {
+            InterleavingMode::Interleaved => 1,
+            InterleavingMode::Separate => batch_size,
+        }
+    }
+
+    /// The number of polynomials stored in each tree when committing to `batch_size` polynomials.
+    pub fn polys_per_tree(&self, batch_size: usize) -> usize {
+        batch_size / self.num_trees(batch_size)
+    }
+}
+
+/// How the prover convinces the verifier of the final polynomial.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum FinalPhase {
+    /// The final polynomial is sent in the clear, i.e. its `2^final_log_degree` coefficients.
+    #[default]
+    DirectSend,
+    /// The final (multilinear) polynomial is checked with a sumcheck, i.e. `final_log_degree` rounds each
+    /// sending a sumcheck polynomial, followed by the evaluation of the final polynomial at the sumcheck point.
+    Sumcheck,
+}
+
+impl FinalPhase {
+    /// The number of (extension) field elements sent for a final polynomial with `2^final_log_degree` coefficients,
+    /// where each sumcheck polynomial has degree `sumcheck_degree`.
+    pub fn num_elements(&self, final_log_degree: usize, sumcheck_degree: usize) -> usize {
+        match self {
+            FinalPhase::DirectSend => 1 << final_log_degree,
+            FinalPhase::Sumcheck => final_log_degree * (sumcheck_degree + 1) + 1,
+        }
+    }
 }
 
 /// Represents a list of field elements
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct FieldElements {
     /// The field used
     pub field: Field,
@@ -117,15 +423,242 @@ pub struct FieldElements {
 
     /// Whether these are extension or base field elements
     pub is_extension: bool,
+
+    /// How each element is serialized
+    pub encoding: Encoding,
 }
 
 impl FieldElements {
-    fn size_bits(&self) -> usize {
+    /// A list of `num_elements` elements of the base field.
+    pub fn base(field: Field, num_elements: usize) -> Self {
+        FieldElements {
+            field,
+            num_elements,
+            is_extension: false,
+            encoding: Encoding::default(),
+        }
+    }
+
+    /// A list of `num_elements` elements of the extension field.
+    pub fn extension(field: Field, num_elements: usize) -> Self {
+        FieldElements {
+            field,
+            num_elements,
+            is_extension: true,
+            encoding: Encoding::default(),
+        }
+    }
+
+    /// Sets the encoding of the elements.
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Computes the number of bits of the elements.
+    /// Extension field elements are encoded as their `extension_degree` base field coefficients.
+    pub fn size_bits(&self) -> usize {
+        let base_size_bits = self.encoding.encoded_size_bits(self.field.field_size_bits);
         self.num_elements
             * if self.is_extension {
-                self.field.extension_bit_size()
+                self.field.extension_degree * base_size_bits
             } else {
-                self.field.field_size_bits
+                base_size_bits
+            }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        digest_size_bits_for_security_level, merkle_digest_bits, Encoding, FieldElements,
+        MerkleQueries, MerkleTree,
+    };
+    use crate::{
+        field::{BABYBEAR_5, GOLDILOCKS_2},
+        hash::DigestConfig,
+    };
+
+    #[test]
+    fn test_encoding() {
+        let elements = FieldElements::extension(BABYBEAR_5, 2);
+        assert_eq!(elements.size_bits(), 2 * 5 * 27);
+        assert_eq!(
+            elements.with_encoding(Encoding::ByteAligned).size_bits(),
+            2 * 5 * 32
+        );
+        assert_eq!(
+            elements.with_encoding(Encoding::WordAligned).size_bits(),
+            2 * 5 * 64
+        );
+
+        // Goldilocks is already aligned
+        let elements = FieldElements::base(GOLDILOCKS_2, 3);
+        assert_eq!(
+            elements.with_encoding(Encoding::WordAligned).size_bits(),
+            elements.size_bits()
+        );
+    }
+
+    #[test]
+    fn test_expected_copath_elements() {
+        for (tree_depth, num_openings) in
+            [(10, 1), (10, 16), (20, 100), (20, 1 << 12), (16, 1 << 10)]
+        {
+            let queries = MerkleQueries {
+                merkle_tree: MerkleTree::new(tree_depth, GOLDILOCKS_2, 2, false),
+                num_openings,
+                shared_leaves: 1,
+            };
+            assert!(queries.expected_copath_elements() <= queries.copath_elements() as f64);
+            assert!(queries.expected_size_bits() <= queries.estimate_size_bits());
+        }
+
+        // A single opening needs one sibling per level, of which the leaf sibling is counted separately
+        let queries = MerkleQueries {
+            merkle_tree: MerkleTree::new(10, GOLDILOCKS_2, 2, false),
+            num_openings: 1,
+            shared_leaves: 1,
+        };
+        assert!((queries.expected_copath_elements() - 9.).abs() < 1e-9);
+        assert_eq!(queries.copath_elements(), 9);
+    }
+
+    #[test]
+    fn test_shared_leaves() {
+        let merkle_tree = MerkleTree::new(16, GOLDILOCKS_2, 4, false);
+        let queries = MerkleQueries {
+            merkle_tree,
+            num_openings: 50,
+            shared_leaves: 1,
+        };
+        let shared_queries = MerkleQueries {
+            shared_leaves: 4,
+            ..queries
+        };
+
+        // The four polynomials share the authentication paths
+        assert_eq!(
+            shared_queries.opening_size(),
+            4 * queries.opening_size_per_poly()
+        );
+        assert_eq!(
+            shared_queries.estimate_size_bits(),
+            shared_queries.copath_size() + 4 * queries.opening_size_per_poly()
+        );
+        assert!(shared_queries.estimate_size_bits() < 4 * queries.estimate_size_bits());
+
+        // Which is the same as storing them in a single leaf
+        let wide_leaf_queries = MerkleQueries {
+            merkle_tree: MerkleTree::new(16, GOLDILOCKS_2, 16, false),
+            ..queries
+        };
+        assert_eq!(
+            shared_queries.estimate_size_bits(),
+            wide_leaf_queries.estimate_size_bits()
+        );
+    }
+
+    #[test]
+    fn test_digest_size_for_security_level() {
+        assert_eq!(digest_size_bits_for_security_level(100), 200);
+        assert_eq!(digest_size_bits_for_security_level(128), 256);
+        // Rounded up to a byte boundary
+        assert_eq!(digest_size_bits_for_security_level(97), 200);
+    }
+
+    #[test]
+    fn test_merkle_digest_bits() {
+        // Only a generic digest without an explicit size is sized for the security level
+        assert_eq!(merkle_digest_bits(None, None, 100), (200, 100));
+        assert_eq!(merkle_digest_bits(None, Some(256), 100), (256, 128));
+
+        // A concrete hash keeps its output size, whatever the security level
+        for security_level in [80, 100, 128] {
+            assert_eq!(
+                merkle_digest_bits(Some(DigestConfig::Sha3_256), None, security_level),
+                (256, 128)
+            );
+            assert_eq!(
+                merkle_digest_bits(
+                    Some(DigestConfig::Poseidon2Goldilocks),
+                    Some(128),
+                    security_level
+                ),
+                (256, 100)
+            );
+        }
+    }
+
+    #[test]
+    fn test_merkle_queries_shrink_with_security_level() {
+        let size_at = |security_level| {
+            MerkleQueries {
+                merkle_tree: MerkleTree::with_security_level(
+                    20,
+                    GOLDILOCKS_2,
+                    16,
+                    true,
+                    security_level,
+                ),
+                num_openings: 50,
+                shared_leaves: 1,
             }
+            .estimate_size_bits()
+        };
+
+        assert!(size_at(100) < size_at(128));
+        assert_eq!(
+            size_at(128),
+            MerkleQueries {
+                merkle_tree: MerkleTree::new(20, GOLDILOCKS_2, 16, true),
+                num_openings: 50,
+                shared_leaves: 1,
+            }
+            .estimate_size_bits()
+        );
+    }
+
+    #[test]
+    fn test_base_vs_extension_field_elements() {
+        let base = FieldElements::base(GOLDILOCKS_2, 3);
+        let extension = FieldElements::extension(GOLDILOCKS_2, 3);
+
+        assert_eq!(base.size_bits(), 3 * 64);
+        assert_eq!(extension.size_bits(), 3 * 128);
+    }
+
+    #[test]
+    fn test_binary_copath_unchanged() {
+        let queries = MerkleQueries {
+            merkle_tree: MerkleTree::new(20, GOLDILOCKS_2, 16, true),
+            num_openings: 50,
+            shared_leaves: 1,
+        };
+
+        // 50 openings share the top ceil(log2(50)) = 6 levels, and the leaf siblings are counted separately
+        assert_eq!(queries.merkle_tree.arity, 2);
+        assert_eq!(queries.copath_elements(), 50 * (20 - 1 - 6));
+        assert_eq!(queries.copath_size(), 50 * 256 + 50 * (20 - 1 - 6) * 256);
+    }
+
+    #[test]
+    fn test_four_ary_copath() {
+        let binary = MerkleTree::new(20, GOLDILOCKS_2, 16, true);
+        let four_ary = MerkleTree::with_arity(20, GOLDILOCKS_2, 16, true, 256, 4);
+
+        assert_eq!(binary.num_levels(), 20);
+        assert_eq!(four_ary.num_levels(), 10);
+
+        let siblings_per_level = |merkle_tree: MerkleTree| {
+            let queries = MerkleQueries {
+                merkle_tree,
+                num_openings: 1,
+                shared_leaves: 1,
+            };
+            queries.copath_elements() / (merkle_tree.num_levels() - 1)
+        };
+        assert_eq!(siblings_per_level(binary), 1);
+        assert_eq!(siblings_per_level(four_ary), 3);
     }
 }
@@ -1,14 +1,21 @@
-use std::fmt::Display;
+use std::fmt::{Display, Write};
 
 use crate::{
-    errors::SecurityAssumption,
+    errors::{OodStrategy, ParameterError, SecurityAssumption, DEFAULT_ETA_FACTOR},
+    hash::DigestConfig,
     protocol::{
         builder::ProtocolBuilder,
-        proof_size::{FieldElements, MerkleQueries, MerkleTree, ProofElement},
-        Protocol, ProverMessage, RbRError, VerifierMessage,
+        proof_size::{
+            merkle_digest_bits, Encoding, FieldElements, InterleavingMode, MerkleQueries,
+            MerkleTree, ProofElement,
+        },
+        total_oracle_bits, Protocol, ProtocolReport, ProverMessage, RbRError, Report,
+        VerifierMessage,
     },
-    utils::pow_util,
-    LowDegreeParameters,
+    utils::{
+        float_precision, pow_util, print_final_poly_warning, print_pow_warning, ROUNDS_CSV_HEADER,
+    },
+    LowDegreeParameters, LowDegreeTest,
 };
 
 /// Parameters parametrizing an instance of STIR.
@@ -24,6 +31,7 @@ pub struct StirParameters {
 
     /// The folding factors in the remaining rounds.
     /// Given in log form, i.e. folding_factors[i] = 2 implies that the degree in round i is reduced by a factor of 4.
+    /// If empty, only the starting fold is done, and with a starting folding factor of 0 the polynomial is sent whole.
     pub folding_factors: Vec<usize>,
 
     /// The rates used in the internal rounds of STIR.
@@ -32,19 +40,119 @@ pub struct StirParameters {
     /// The security assumption under which to configure STIR.
     pub security_assumption: SecurityAssumption,
 
+    /// Optionally, the security assumption to use in each round (falls back to `security_assumption` if empty).
+    pub security_assumptions: Vec<SecurityAssumption>,
+
+    /// Optionally, the divisor used to set η (see `SecurityAssumption::log_eta_with_factor`), defaults to `DEFAULT_ETA_FACTOR`.
+    pub eta_factor: Option<f64>,
+
+    /// Optionally, a cap on the (log) list size, see `SecurityAssumptionWithEta::with_max_list_size_bits`.
+    pub max_list_size_bits: Option<f64>,
+
     /// The security level desired.
     pub security_level: usize,
 
     /// The number of pow bits to use to reduce query error.
     /// Traditionally called also "grinding".
-    /// NOTE: This does not affect the pow bits used to reduce proximity gaps errors.
+    /// NOTE: This does not bound the pow bits used to reduce proximity gaps errors, see `folding_pow_bits`.
     pub pow_bits: usize,
 
-    /// The size of the digest for the Merkle tree
-    pub digest_size_bits: usize,
+    /// Optionally, the maximum number of pow bits used to reduce proximity gaps (folding and batching) errors.
+    /// If unset, these are ground up to the security level.
+    pub folding_pow_bits: Option<usize>,
+
+    /// Optionally, a hard cap on the pow bits of any grind (e.g. the most that is practical on the prover's hardware).
+    /// Unlike `pow_bits`, the queries are not increased to compensate, and so the protocol may fall short of
+    /// `security_level` (see `security_shortfall` in the config).
+    pub grinding_cap: Option<usize>,
+
+    /// The size of the digest for the Merkle tree. If `None`, digests are sized for the security level
+    /// (see `digest_size_bits_for_security_level`).
+    pub digest_size_bits: Option<usize>,
+
+    /// Optionally, the hash used for the Merkle digests, overriding `digest_size_bits` with its output size
+    /// and bounding the collision resistance of the digests.
+    pub digest: Option<DigestConfig>,
+
+    /// Whether grinding is forbidden, in which case no PoW is used and `pow_bits` is ignored.
+    pub no_grinding: bool,
+
+    /// Whether the commitments are zero-knowledge, in which case the Merkle leaves are salted.
+    pub zero_knowledge: bool,
+
+    /// How field elements are serialized in the proof.
+    pub encoding: Encoding,
+
+    /// How the batched polynomials are committed to in the starting commitment, see `InterleavingMode`.
+    pub interleaving: InterleavingMode,
+
+    /// The smallest (log) degree the final polynomial may have, guarding against folding all the way down by accident.
+    pub min_final_log_degree: usize,
+
+    /// How the out-of-domain samples are drawn, see `OodStrategy`.
+    pub ood_strategy: OodStrategy,
 }
 
 impl StirParameters {
+    /// Checks the parameters against the LDT parameters, reporting every violation rather than only the first.
+    /// These are the checks done when constructing the protocol, see `StirProtocol::new`.
+    pub fn validate(
+        &self,
+        ldt_parameters: &LowDegreeParameters,
+    ) -> Result<(), Vec<ParameterError>> {
+        let mut errors = vec![];
+        errors.extend(ldt_parameters.validate().err());
+
+        // STIR only supports proximity testing
+        if ldt_parameters.constraint_degree != 0 {
+            errors.push(ParameterError::UnsupportedConstraintDegree {
+                constraint_degree: ldt_parameters.constraint_degree,
+            });
+        }
+
+        // Each fold needs to reduce the degree, only without rounds may the starting fold be skipped
+        if (self.starting_folding_factor == 0 && !self.folding_factors.is_empty())
+            || self.folding_factors.contains(&0)
+        {
+            errors.push(ParameterError::ZeroFoldingFactor);
+        }
+        if self.folding_factors.len() != self.log_inv_rates.len()
+            || (!self.security_assumptions.is_empty()
+                && self.security_assumptions.len() != self.folding_factors.len())
+        {
+            errors.push(ParameterError::MismatchedRoundVectors);
+        }
+
+        // We cannot fold too much
+        let total_reduction =
+            self.starting_folding_factor + self.folding_factors.iter().sum::<usize>();
+        if total_reduction > ldt_parameters.log_degree {
+            errors.push(ParameterError::OverFolded {
+                total_reduction,
+                log_degree: ldt_parameters.log_degree,
+            });
+        } else if ldt_parameters.log_degree - total_reduction < self.min_final_log_degree {
+            errors.push(ParameterError::FinalDegreeTooSmall {
+                final_log_degree: ldt_parameters.log_degree - total_reduction,
+                min_final_log_degree: self.min_final_log_degree,
+            });
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// The security assumption used in the given round.
+    pub fn round_security_assumption(&self, round: usize) -> SecurityAssumption {
+        self.security_assumptions
+            .get(round)
+            .copied()
+            .unwrap_or(self.security_assumption)
+    }
+
     /// Instantiates a STIR configuration in which the rate is constant. This is a worse version of FRI.
     pub fn fixed_rate_folding(
         log_inv_rate: usize,
@@ -61,9 +169,21 @@ impl StirParameters {
             folding_factors: vec![folding_factor; num_rounds],
             log_inv_rates: vec![log_inv_rate; num_rounds],
             security_assumption,
+            security_assumptions: vec![],
+            eta_factor: None,
+            max_list_size_bits: None,
             security_level,
-            digest_size_bits,
+            digest_size_bits: Some(digest_size_bits),
+            digest: None,
             pow_bits,
+            no_grinding: false,
+            folding_pow_bits: None,
+            grinding_cap: None,
+            zero_knowledge: false,
+            encoding: Encoding::default(),
+            interleaving: InterleavingMode::default(),
+            min_final_log_degree: 0,
+            ood_strategy: OodStrategy::default(),
         }
     }
 
@@ -86,9 +206,21 @@ impl StirParameters {
                 .map(|i| log_inv_rate + (i + 1) * (folding_factor - 1))
                 .collect(),
             security_assumption,
-            digest_size_bits,
+            security_assumptions: vec![],
+            eta_factor: None,
+            max_list_size_bits: None,
+            digest_size_bits: Some(digest_size_bits),
+            digest: None,
             security_level,
             pow_bits,
+            no_grinding: false,
+            folding_pow_bits: None,
+            grinding_cap: None,
+            zero_knowledge: false,
+            encoding: Encoding::default(),
+            interleaving: InterleavingMode::default(),
+            min_final_log_degree: 0,
+            ood_strategy: OodStrategy::default(),
         }
     }
 }
@@ -102,115 +234,179 @@ pub struct StirProtocol {
 
 impl StirProtocol {
     /// Given a LDT parameter and some parameters for STIR, populate the config.
-    pub fn new(ldt_parameters: LowDegreeParameters, stir_parameters: StirParameters) -> Self {
-        // STIR only supports proximity testing
-        assert_eq!(ldt_parameters.constraint_degree, 0);
-
-        // We need to fold at least some time
-        assert!(
-            stir_parameters.starting_folding_factor > 0
-                && stir_parameters.folding_factors.iter().all(|&x| x > 0),
-            "folding factors should be non zero"
-        );
-        assert_eq!(
-            stir_parameters.folding_factors.len(),
-            stir_parameters.log_inv_rates.len()
-        );
-
-        // We cannot fold too much
-        let total_reduction = stir_parameters.starting_folding_factor
-            + stir_parameters.folding_factors.iter().sum::<usize>();
-        assert!(total_reduction <= ldt_parameters.log_degree);
-
-        // If less, just send the damn polynomials
-        assert!(ldt_parameters.log_degree >= stir_parameters.folding_factors[0]);
+    /// Without any folds (i.e. no rounds and no starting fold), this is the trivial LDT: the prover sends the whole
+    /// polynomial, which the verifier checks against the committed evaluations at the queried points.
+    pub fn new(
+        ldt_parameters: LowDegreeParameters,
+        stir_parameters: StirParameters,
+    ) -> Result<Self, ParameterError> {
+        // Check the parameters, reporting the first violation
+        if let Err(errors) = stir_parameters.validate(&ldt_parameters) {
+            return Err(errors[0]);
+        }
+        let final_log_degree = ldt_parameters.log_degree
+            - stir_parameters.starting_folding_factor
+            - stir_parameters.folding_factors.iter().sum::<usize>();
 
-        // Compute the number of rounds and the leftover
-        let final_log_degree = ldt_parameters.log_degree - total_reduction;
+        // Compute the number of rounds
         let num_rounds = stir_parameters.folding_factors.len();
 
         // Compute the security level
         let security_level = stir_parameters.security_level;
-        let protocol_security_level =
-            0.max(stir_parameters.security_level - stir_parameters.pow_bits);
+        // Without grinding, the queries alone reach the security level and no PoW is used anywhere
+        let max_pow_bits = if stir_parameters.no_grinding {
+            0
+        } else {
+            stir_parameters.pow_bits
+        };
+        let protocol_security_level = security_level.saturating_sub(max_pow_bits);
+        let pow_security_level = if stir_parameters.no_grinding {
+            0
+        } else {
+            security_level
+        };
+        // The PoW for the proximity gaps errors is capped separately from the one for the queries
+        let folding_pow_cap = stir_parameters
+            .folding_pow_bits
+            .map_or(f64::INFINITY, |bits| bits as f64);
+        let grinding_cap = stir_parameters.grinding_cap;
+        let folding_pow_util =
+            |error: f64| pow_util(pow_security_level, error, grinding_cap).min(folding_pow_cap);
+
+        // The security assumption (and choice of η) used to compute the errors
+        let eta_factor = stir_parameters.eta_factor.unwrap_or(DEFAULT_ETA_FACTOR);
+        let security_assumption = stir_parameters
+            .security_assumption
+            .with_eta_factor(eta_factor)
+            .with_list_size_cap(stir_parameters.max_list_size_bits);
 
         // Initial domain size (the trace domain)
         let starting_folding_factor = stir_parameters.starting_folding_factor;
         let starting_domain_log_size =
             ldt_parameters.log_degree + stir_parameters.starting_log_inv_rate;
 
-        let mut protocol_builder =
-            ProtocolBuilder::new("STIR protocol", stir_parameters.digest_size_bits);
+        let (digest_size_bits, collision_bits) = merkle_digest_bits(
+            stir_parameters.digest,
+            stir_parameters.digest_size_bits,
+            security_level,
+        );
+
+        // In zero-knowledge mode, each leaf is salted with randomness
+        let salt_bits = if stir_parameters.zero_knowledge {
+            2 * security_level
+        } else {
+            0
+        };
+
+        // How field elements are serialized in the proof
+        let encoding = stir_parameters.encoding;
+        let interleaving = stir_parameters.interleaving;
+        let ood_strategy = stir_parameters.ood_strategy;
+
+        // Each verifier challenge (other than the query indices) is an extension field element
+        let challenge_bits = ldt_parameters.field.extension_bit_size();
+
+        let mut protocol_builder = ProtocolBuilder::new("STIR protocol", digest_size_bits)
+            .with_collision_bits(collision_bits);
 
         // Pow bits for the batching steps
         let mut batching_pow_bits = 0.;
         if ldt_parameters.batch_size > 1 {
-            let prox_gaps_error_batching = stir_parameters.security_assumption.prox_gaps_error(
+            let prox_gaps_error_batching = security_assumption.prox_gaps_error(
                 ldt_parameters.log_degree,
                 stir_parameters.starting_log_inv_rate,
                 ldt_parameters.field.extension_bit_size(),
                 ldt_parameters.batch_size,
             ); // we now start, the initial folding pow bits
-            batching_pow_bits = pow_util(security_level, prox_gaps_error_batching);
+            batching_pow_bits = folding_pow_util(prox_gaps_error_batching);
 
-            // Add the round for the batching
+            // Add the round for the batching.
+            // NOTE: No commitment to the combined codeword is needed, as the starting tree stores the evaluations
+            // of all the batched functions in each leaf, from which the verifier derives the combination.
             protocol_builder = protocol_builder
                 .start_round("batching_round")
-                .verifier_message(VerifierMessage::new(
-                    vec![RbRError::new("batching_error", prox_gaps_error_batching)],
-                    batching_pow_bits,
-                ))
+                .verifier_message(
+                    VerifierMessage::new(
+                        vec![RbRError::new("batching_error", prox_gaps_error_batching)],
+                        batching_pow_bits,
+                    )
+                    .with_challenge_bits(challenge_bits),
+                )
                 .end_round();
         }
 
         // Merkle tree committed to
-        let mut current_merkle_tree = MerkleTree::new(
+        let mut current_merkle_tree = MerkleTree::with_digest_size(
             starting_domain_log_size - starting_folding_factor,
             ldt_parameters.field,
-            (1 << starting_folding_factor) * ldt_parameters.batch_size,
+            (1 << starting_folding_factor) * interleaving.polys_per_tree(ldt_parameters.batch_size),
             false, // first tree is over the base
-        );
+            digest_size_bits,
+        )
+        .with_salt_bits(salt_bits)
+        .with_encoding(encoding);
+        // How many trees the current oracle is spread over (only the starting one may be more than one)
+        let mut current_num_trees = interleaving.num_trees(ldt_parameters.batch_size);
 
         // Degree of next polynomial to send
         let mut current_log_degree = ldt_parameters.log_degree - starting_folding_factor;
         let mut log_inv_rate = stir_parameters.starting_log_inv_rate;
 
         // we now start, the initial folding pow bits
-        let starting_folding_prox_gaps_error = stir_parameters.security_assumption.prox_gaps_error(
-            current_log_degree,
-            log_inv_rate,
-            ldt_parameters.field.extension_bit_size(),
-            1 << starting_folding_factor,
-        );
-        let starting_folding_pow_bits = pow_util(security_level, starting_folding_prox_gaps_error);
-        protocol_builder = protocol_builder
-            .start_round("initial_iteration")
-            .verifier_message(VerifierMessage::new(
-                vec![RbRError::new(
-                    "folding_error",
-                    starting_folding_prox_gaps_error,
-                )],
-                starting_folding_pow_bits,
-            ))
-            .end_round();
+        let mut starting_folding_pow_bits = 0.;
+        if starting_folding_factor > 0 {
+            let starting_folding_prox_gaps_error = security_assumption.prox_gaps_error(
+                current_log_degree,
+                log_inv_rate,
+                ldt_parameters.field.extension_bit_size(),
+                1 << starting_folding_factor,
+            );
+            starting_folding_pow_bits = folding_pow_util(starting_folding_prox_gaps_error);
+            protocol_builder = protocol_builder
+                .start_round("initial_iteration")
+                .verifier_message(
+                    VerifierMessage::new(
+                        vec![RbRError::new(
+                            "folding_error",
+                            starting_folding_prox_gaps_error,
+                        )],
+                        starting_folding_pow_bits,
+                    )
+                    .with_challenge_bits(challenge_bits),
+                )
+                .end_round();
+        }
 
         let mut round_parameters = Vec::with_capacity(num_rounds);
 
-        for (folding_factor, next_rate) in stir_parameters
+        let round_security_assumptions: Vec<_> = (0..num_rounds)
+            .map(|i| {
+                stir_parameters
+                    .round_security_assumption(i)
+                    .with_eta_factor(eta_factor)
+                    .with_list_size_cap(stir_parameters.max_list_size_bits)
+            })
+            .collect();
+
+        for ((folding_factor, next_rate), round_security_assumption) in stir_parameters
             .folding_factors
             .into_iter()
             .zip(stir_parameters.log_inv_rates)
+            .zip(round_security_assumptions)
         {
             // This is the size of the new evaluation domain
             let new_evaluation_domain_size = current_log_degree + next_rate;
 
             // Send the new oracle
-            let next_merkle_tree = MerkleTree::new(
+            let next_merkle_tree = MerkleTree::with_digest_size(
                 new_evaluation_domain_size - folding_factor,
                 ldt_parameters.field,
                 1 << folding_factor,
                 true,
-            );
+                digest_size_bits,
+            )
+            .with_salt_bits(salt_bits)
+            .with_encoding(encoding);
             protocol_builder = protocol_builder
                 .start_round("stir_iteration")
                 .prover_message(ProverMessage::new(ProofElement::MerkleRoot(
@@ -218,55 +414,51 @@ impl StirProtocol {
                 )));
 
             // Compute the ood samples required
-            let ood_samples = stir_parameters.security_assumption.determine_ood_samples(
+            let ood_samples = round_security_assumption.determine_ood_samples(
                 security_level,
                 current_log_degree,
                 next_rate,
                 ldt_parameters.field.extension_bit_size(),
-            );
+                ood_strategy,
+            )?;
 
             // Add OOD rounds to protocol
             if ood_samples > 0 {
-                let ood_error = stir_parameters.security_assumption.ood_error(
+                let ood_error = round_security_assumption.ood_error(
                     current_log_degree,
                     next_rate,
                     ldt_parameters.field.extension_bit_size(),
                     ood_samples,
+                    ood_strategy,
                 );
 
                 protocol_builder = protocol_builder
-                    .verifier_message(VerifierMessage::new(
-                        vec![RbRError::new("ood_error", ood_error)],
-                        0.,
-                    ))
+                    .verifier_message(
+                        VerifierMessage::new(vec![RbRError::new("ood_error", ood_error)], 0.)
+                            .with_challenge_bits(ood_samples * challenge_bits),
+                    )
                     .prover_message(ProverMessage::new(ProofElement::FieldElements(
-                        FieldElements {
-                            field: ldt_parameters.field,
-                            num_elements: ood_samples,
-                            is_extension: true,
-                        },
+                        FieldElements::extension(ldt_parameters.field, ood_samples)
+                            .with_encoding(encoding),
                     )));
             }
 
             // Compute the number of queries required
-            let num_queries = stir_parameters
-                .security_assumption
-                .queries(protocol_security_level, log_inv_rate);
+            let num_queries =
+                round_security_assumption.queries(protocol_security_level, log_inv_rate)?;
 
             // We need to compute the errors, to compute the according PoW
-            let query_error = stir_parameters
-                .security_assumption
-                .queries_error(log_inv_rate, num_queries);
+            let query_error = round_security_assumption.queries_error(log_inv_rate, num_queries)?;
 
             let num_terms = num_queries + ood_samples;
-            let prox_gaps_error_1 = stir_parameters.security_assumption.prox_gaps_error(
+            let prox_gaps_error_1 = round_security_assumption.prox_gaps_error(
                 current_log_degree,
                 next_rate,
                 ldt_parameters.field.extension_bit_size(),
                 num_terms,
             );
 
-            let prox_gaps_error_2 = stir_parameters.security_assumption.prox_gaps_error(
+            let prox_gaps_error_2 = round_security_assumption.prox_gaps_error(
                 current_log_degree - folding_factor,
                 next_rate,
                 ldt_parameters.field.extension_bit_size(),
@@ -275,30 +467,40 @@ impl StirProtocol {
 
             // Now compute the PoW
             let pow_bits = pow_util(
-                security_level,
+                pow_security_level,
                 query_error.min(prox_gaps_error_1).min(prox_gaps_error_2),
+                grinding_cap,
             );
 
-            protocol_builder = protocol_builder
-                .verifier_message(VerifierMessage::new(
+            protocol_builder = protocol_builder.verifier_message(
+                VerifierMessage::new(
                     vec![
                         RbRError::new("query_error", query_error),
                         RbRError::new("prox_gaps_error_1", prox_gaps_error_1),
                         RbRError::new("prox_gaps_error_2", prox_gaps_error_2),
                     ],
                     pow_bits,
-                ))
-                .prover_message(ProverMessage::new(ProofElement::MerkleQueries(
-                    MerkleQueries {
+                )
+                // The query indices, the combination randomness and the next folding randomness
+                .with_challenge_bits(
+                    num_queries * current_merkle_tree.tree_depth + 2 * challenge_bits,
+                ),
+            );
+            for _ in 0..current_num_trees {
+                protocol_builder = protocol_builder.prover_message(ProverMessage::new(
+                    ProofElement::MerkleQueries(MerkleQueries {
                         merkle_tree: current_merkle_tree,
                         num_openings: num_queries,
-                    },
-                )))
-                .end_round();
+                        shared_leaves: 1,
+                    }),
+                ));
+            }
+            protocol_builder = protocol_builder.end_round();
 
             let round_config = RoundConfig {
                 evaluation_domain_log_size: new_evaluation_domain_size,
                 folding_factor,
+                security_assumption: round_security_assumption.assumption,
                 num_queries,
                 pow_bits,
                 ood_samples,
@@ -307,51 +509,68 @@ impl StirProtocol {
             round_parameters.push(round_config);
 
             current_merkle_tree = next_merkle_tree;
+            current_num_trees = 1;
             log_inv_rate = next_rate;
             current_log_degree -= folding_factor;
         }
 
         // Compute the number of queries required
-        let final_queries = stir_parameters
-            .security_assumption
-            .queries(protocol_security_level, log_inv_rate);
+        let final_queries = security_assumption.queries(protocol_security_level, log_inv_rate)?;
 
-        // We need to compute the errors, to compute the according PoW
-        let query_error = stir_parameters
-            .security_assumption
-            .queries_error(log_inv_rate, final_queries);
+        // We need to compute the errors, to compute the according PoW.
+        let query_error = security_assumption.queries_error(log_inv_rate, final_queries)?;
 
         // Now compute the PoW
-        let final_pow_bits = pow_util(security_level, query_error);
+        let final_pow_bits = pow_util(pow_security_level, query_error, grinding_cap);
 
         // Add the final round message
         protocol_builder = protocol_builder
             .start_round("final_round")
-            .verifier_message(VerifierMessage::new(
-                vec![RbRError::new("query_error", query_error)],
-                final_pow_bits,
-            ))
-            .prover_message(ProverMessage::new(ProofElement::FieldElements(
-                FieldElements {
-                    field: ldt_parameters.field,
-                    num_elements: 1 << final_log_degree,
-                    is_extension: true,
-                },
-            )))
-            .prover_message(ProverMessage::new(ProofElement::MerkleQueries(
-                MerkleQueries {
+            .verifier_message(
+                VerifierMessage::new(
+                    vec![RbRError::new("query_error", query_error)],
+                    final_pow_bits,
+                )
+                .with_challenge_bits(final_queries * current_merkle_tree.tree_depth),
+            )
+            .prover_message(ProverMessage::final_polynomial(
+                FieldElements::extension(ldt_parameters.field, 1 << final_log_degree)
+                    .with_encoding(encoding),
+            ));
+        for _ in 0..current_num_trees {
+            protocol_builder = protocol_builder.prover_message(ProverMessage::new(
+                ProofElement::MerkleQueries(MerkleQueries {
                     merkle_tree: current_merkle_tree,
                     num_openings: final_queries,
-                },
-            )))
-            .end_round();
+                    shared_leaves: 1,
+                }),
+            ));
+        }
+        protocol_builder = protocol_builder.end_round();
+
+        let protocol = protocol_builder.build();
+        // Unless some grind is capped or forbidden, every round reaches the security level
+        let security_shortfall = 0f64.max(security_level as f64 - protocol.rbr_error());
 
-        StirProtocol {
+        Ok(StirProtocol {
             config: StirConfig {
                 ldt_parameters,
                 security_assumption: stir_parameters.security_assumption,
                 security_level,
-                max_pow_bits: stir_parameters.pow_bits,
+                max_pow_bits,
+                no_grinding: stir_parameters.no_grinding,
+                max_folding_pow_bits: stir_parameters.folding_pow_bits,
+                grinding_cap: stir_parameters.grinding_cap,
+                security_shortfall,
+                eta_factor,
+                max_list_size_bits: stir_parameters.max_list_size_bits,
+                digest_size_bits: stir_parameters.digest_size_bits,
+                digest: stir_parameters.digest,
+                zero_knowledge: stir_parameters.zero_knowledge,
+                min_final_log_degree: stir_parameters.min_final_log_degree,
+                ood_strategy,
+                encoding,
+                interleaving,
                 batching_pow_bits,
                 starting_folding_factor,
                 starting_domain_log_size,
@@ -363,84 +582,277 @@ impl StirProtocol {
                 final_poly_log_degree: final_log_degree,
                 final_log_inv_rate: log_inv_rate,
             },
-            protocol: protocol_builder.build(),
-        }
+            protocol,
+        })
+    }
+}
+
+impl StirProtocol {
+    /// Serializes the configuration and the round-by-round proof sizes to JSON.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::json!({
+            "config": self.config,
+            "protocol": self.protocol.to_json_value(),
+        })
+        .to_string()
+    }
+}
+
+impl LowDegreeTest for StirProtocol {
+    fn protocol(&self) -> &Protocol {
+        &self.protocol
+    }
+}
+
+impl Report for StirProtocol {
+    fn report(&self) -> ProtocolReport {
+        ProtocolReport::new(
+            &self.protocol,
+            self.config
+                .round_parameters
+                .iter()
+                .map(|round| round.num_queries)
+                .sum::<usize>()
+                + self.config.final_queries,
+            self.config.round_parameters.len(),
+            self.config.final_poly_log_degree,
+        )
     }
 }
 
 impl Display for StirProtocol {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.config.fmt(f)?;
-        self.protocol.fmt(f)
+        self.protocol.fmt(f)?;
+        print_final_poly_warning(
+            f,
+            self.config.final_poly_size_bits(),
+            self.protocol.proof_size_bits(),
+        )
     }
 }
 
 /// A fully expanded STIR configuration.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct StirConfig {
     /// The configuration for the LDT desired.
-    pub(crate) ldt_parameters: LowDegreeParameters,
+    pub ldt_parameters: LowDegreeParameters,
 
     /// The security assumption under which STIR was configured.
-    pub(crate) security_assumption: SecurityAssumption,
+    pub security_assumption: SecurityAssumption,
 
     /// The desired security level.
-    pub(crate) security_level: usize,
+    pub security_level: usize,
 
     /// The maximum number of pow bits allowed (over and we throw a warning, as probably then we are misconfigured.)
-    pub(crate) max_pow_bits: usize,
+    pub max_pow_bits: usize,
+
+    /// Whether grinding was forbidden.
+    pub no_grinding: bool,
+
+    /// The maximum number of pow bits used to reduce proximity gaps errors, if capped.
+    pub max_folding_pow_bits: Option<usize>,
+
+    /// The cap on the pow bits of any grind, if given.
+    pub grinding_cap: Option<usize>,
+
+    /// How many bits the round-by-round soundness falls short of the security level, i.e. `security_level - rbr_error`
+    /// (if positive). This includes any shortfall due to `grinding_cap`, `max_folding_pow_bits` or `no_grinding`.
+    pub security_shortfall: f64,
+
+    /// The divisor used to set η.
+    pub eta_factor: f64,
+
+    /// The cap on the (log) list size, if any.
+    pub max_list_size_bits: Option<f64>,
+
+    /// The size of the digest for the Merkle trees, unless sized for the security level.
+    pub digest_size_bits: Option<usize>,
+
+    /// The hash used for the Merkle digests, if given.
+    pub digest: Option<DigestConfig>,
+
+    /// Whether the commitments are zero-knowledge.
+    pub zero_knowledge: bool,
+
+    /// The smallest (log) degree the final polynomial was allowed to have.
+    pub min_final_log_degree: usize,
+
+    /// How the out-of-domain samples were drawn.
+    pub ood_strategy: OodStrategy,
+
+    /// How field elements are serialized in the proof.
+    pub encoding: Encoding,
+
+    /// How the batched polynomials were committed to in the starting commitment.
+    pub interleaving: InterleavingMode,
 
     /// The rate of the inital RS code used during the protocol.    
-    pub(crate) starting_log_inv_rate: usize,
+    pub starting_log_inv_rate: usize,
 
     /// The pow bits used in the batching phase.
     pub batching_pow_bits: f64,
 
     /// The initial folding factor.
-    pub(crate) starting_folding_factor: usize,
+    pub starting_folding_factor: usize,
 
     /// The initial domain size
-    pub(crate) starting_domain_log_size: usize,
+    pub starting_domain_log_size: usize,
 
     /// The initial pow bits used in the first fold.
-    pub(crate) starting_folding_pow_bits: f64,
+    pub starting_folding_pow_bits: f64,
 
     /// The round-specific parameters.
-    pub(crate) round_parameters: Vec<RoundConfig>,
+    pub round_parameters: Vec<RoundConfig>,
 
     /// Degree of the final polynomial sent over.
-    pub(crate) final_poly_log_degree: usize,
+    pub final_poly_log_degree: usize,
 
     /// Number of queries in the last round
-    pub(crate) final_queries: usize,
+    pub final_queries: usize,
 
     /// Number of final bits of proof of work (for the queries).
-    pub(crate) final_pow_bits: f64,
+    pub final_pow_bits: f64,
 
     /// Rate of the final RS codeword.
-    pub(crate) final_log_inv_rate: usize,
+    pub final_log_inv_rate: usize,
 }
 
 /// Round specific configuration
-#[derive(Debug, Clone)]
-pub(crate) struct RoundConfig {
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RoundConfig {
     /// Folding factor for this round.
-    pub(crate) folding_factor: usize,
+    pub folding_factor: usize,
+    /// The security assumption used in this round.
+    pub security_assumption: SecurityAssumption,
     /// Size of evaluation domain (of oracle sent in this round)
-    pub(crate) evaluation_domain_log_size: usize,
+    pub evaluation_domain_log_size: usize,
     /// Number of bits of proof of work (for the queries).
-    pub(crate) pow_bits: f64,
+    pub pow_bits: f64,
     /// Number of queries in this round
-    pub(crate) num_queries: usize,
+    pub num_queries: usize,
     /// Number of OOD samples in this round
-    pub(crate) ood_samples: usize,
+    pub ood_samples: usize,
     /// Rate of current RS codeword
-    pub(crate) log_inv_rate: usize,
+    pub log_inv_rate: usize,
 }
 
 impl StirConfig {
+    /// Reconstructs the parameters this configuration was built from, such that `StirProtocol::new` with these
+    /// parameters (and the same LDT parameters) yields this configuration again.
+    /// NOTE: The digest size is the one used, i.e. after capping it for the security level.
+    pub fn to_parameters(&self) -> StirParameters {
+        // Each round records the rate of the oracle it queries, which was committed in the previous round
+        let log_inv_rates = self
+            .round_parameters
+            .iter()
+            .skip(1)
+            .map(|round| round.log_inv_rate)
+            .chain(std::iter::once(self.final_log_inv_rate))
+            .collect();
+
+        StirParameters {
+            starting_log_inv_rate: self.starting_log_inv_rate,
+            starting_folding_factor: self.starting_folding_factor,
+            folding_factors: self
+                .round_parameters
+                .iter()
+                .map(|round| round.folding_factor)
+                .collect(),
+            log_inv_rates,
+            security_assumption: self.security_assumption,
+            security_assumptions: self
+                .round_parameters
+                .iter()
+                .map(|round| round.security_assumption)
+                .collect(),
+            eta_factor: Some(self.eta_factor),
+            max_list_size_bits: self.max_list_size_bits,
+            security_level: self.security_level,
+            pow_bits: self.max_pow_bits,
+            folding_pow_bits: self.max_folding_pow_bits,
+            grinding_cap: self.grinding_cap,
+            digest_size_bits: self.digest_size_bits,
+            digest: self.digest,
+            no_grinding: self.no_grinding,
+            zero_knowledge: self.zero_knowledge,
+            encoding: self.encoding,
+            interleaving: self.interleaving,
+            min_final_log_degree: self.min_final_log_degree,
+            ood_strategy: self.ood_strategy,
+        }
+    }
+
+    /// The total size in bits of the oracles committed to by the prover, see `protocol::total_oracle_bits`.
+    pub fn total_oracle_bits(&self) -> usize {
+        total_oracle_bits(
+            self.ldt_parameters,
+            std::iter::once(self.starting_domain_log_size).chain(
+                self.round_parameters
+                    .iter()
+                    .map(|r| r.evaluation_domain_log_size),
+            ),
+        )
+    }
+
+    /// The size in bits of the final polynomial, which is sent in the clear.
+    pub fn final_poly_size_bits(&self) -> usize {
+        FieldElements::extension(self.ldt_parameters.field, 1 << self.final_poly_log_degree)
+            .with_encoding(self.encoding)
+            .size_bits()
+    }
+
+    /// Returns the (labeled) PoW values exceeding `max_pow_bits`, which likely signal a misconfiguration.
+    pub fn pow_bits_exceeded(&self) -> Vec<(String, f64)> {
+        let mut pow_bits = vec![
+            ("batching".to_string(), self.batching_pow_bits),
+            (
+                "starting folding".to_string(),
+                self.starting_folding_pow_bits,
+            ),
+        ];
+        for (i, r) in self.round_parameters.iter().enumerate() {
+            pow_bits.push((format!("round {i}"), r.pow_bits));
+        }
+        pow_bits.push(("final".to_string(), self.final_pow_bits));
+
+        pow_bits.retain(|&(_, bits)| bits > self.max_pow_bits as f64);
+        pow_bits
+    }
+
+    /// Exports the round-specific parameters as CSV, with one row per round followed by the final round.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        writeln!(csv, "{ROUNDS_CSV_HEADER}").unwrap();
+        for (i, r) in self.round_parameters.iter().enumerate() {
+            writeln!(
+                csv,
+                "{},{},{},{},{},{},{:.1}",
+                i,
+                r.folding_factor,
+                r.evaluation_domain_log_size,
+                r.log_inv_rate,
+                r.num_queries,
+                r.ood_samples,
+                r.pow_bits
+            )
+            .unwrap();
+        }
+        writeln!(
+            csv,
+            "final,,,{},{},0,{:.1}",
+            self.final_log_inv_rate, self.final_queries, self.final_pow_bits
+        )
+        .unwrap();
+        csv
+    }
+
     /// Prints a summary of the configuration for STIR.
     pub fn print_config_summary(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let precision = float_precision(f);
         writeln!(f, "{}", self.ldt_parameters)?;
         writeln!(
             f,
@@ -457,14 +869,14 @@ impl StirConfig {
         if self.ldt_parameters.batch_size > 1 {
             writeln!(
                 f,
-                "Batch size: {}, batching_pow_bits: {:.1}",
+                "Batch size: {}, batching_pow_bits: {:.precision$}",
                 self.ldt_parameters.batch_size, self.batching_pow_bits
             )?;
         }
 
         writeln!(
             f,
-            "Initial folding factor: {}, initial_folding_pow_bits: {:.1}",
+            "Initial folding factor: {}, initial_folding_pow_bits: {:.precision$}",
             self.starting_folding_factor, self.starting_folding_pow_bits
         )?;
         for r in &self.round_parameters {
@@ -473,13 +885,15 @@ impl StirConfig {
 
         writeln!(
             f,
-            "final_queries: {}, final polynomial: {}, final_rate: 2^-{}, final_pow_bits: {:.1}",
+            "final_queries: {}, final polynomial: {}, final_rate: 2^-{}, final_pow_bits: {:.precision$}",
             self.final_queries,
             self.final_poly_log_degree,
             self.final_log_inv_rate,
             self.final_pow_bits,
         )?;
 
+        print_pow_warning(f, self.max_pow_bits, &self.pow_bits_exceeded())?;
+
         Ok(())
     }
 }
@@ -492,10 +906,250 @@ impl Display for StirConfig {
 
 impl Display for RoundConfig {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let precision = float_precision(f);
         writeln!(
             f,
-            "Folding factor: {}, domain_size: 2^{}, num_queries: {}, rate: 2^-{}, ood_samples: {}, pow_bits: {:.1}",
+            "Folding factor: {}, domain_size: 2^{}, num_queries: {}, rate: 2^-{}, ood_samples: {}, pow_bits: {:.precision$}",
             self.folding_factor, self.evaluation_domain_log_size, self.num_queries, self.log_inv_rate, self.ood_samples, self.pow_bits
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{StirParameters, StirProtocol};
+    use crate::{
+        errors::{OodStrategy, ParameterError, SecurityAssumption},
+        field::GOLDILOCKS_2,
+        hash::DigestConfig,
+        protocol::proof_size::InterleavingMode,
+        LowDegreeParameters,
+    };
+
+    #[test]
+    fn test_unsupported_constraint_degree() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 20,
+            batch_size: 1,
+            constraint_degree: 2,
+            num_constraints: 1,
+        };
+        let stir_parameters = StirParameters::fixed_domain_shift(
+            1,
+            4,
+            3,
+            SecurityAssumption::CapacityBound,
+            100,
+            20,
+            256,
+        );
+        assert_eq!(
+            StirProtocol::new(ldt_parameters, stir_parameters).unwrap_err(),
+            ParameterError::UnsupportedConstraintDegree {
+                constraint_degree: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 20,
+            batch_size: 1,
+            constraint_degree: 0,
+            num_constraints: 1,
+        };
+        let stir_parameters = StirParameters::fixed_domain_shift(
+            1,
+            4,
+            3,
+            SecurityAssumption::CapacityBound,
+            100,
+            20,
+            256,
+        );
+        let stir_protocol = StirProtocol::new(ldt_parameters, stir_parameters).unwrap();
+
+        let csv = stir_protocol.config.to_csv();
+        let mut lines = csv.lines();
+        assert!(lines.next().unwrap().starts_with("round,folding_factor"));
+        assert_eq!(
+            lines.count(),
+            stir_protocol.config.round_parameters.len() + 1
+        );
+    }
+
+    #[test]
+    fn test_zero_rounds() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 16,
+            batch_size: 1,
+            constraint_degree: 0,
+            num_constraints: 1,
+        };
+        let stir_parameters = |starting_folding_factor| StirParameters {
+            starting_folding_factor,
+            ..StirParameters::fixed_domain_shift(
+                1,
+                4,
+                0,
+                SecurityAssumption::CapacityBound,
+                100,
+                20,
+                256,
+            )
+        };
+
+        // Without any fold, the whole polynomial is sent and checked against the starting commitment
+        let stir_protocol = StirProtocol::new(ldt_parameters, stir_parameters(0)).unwrap();
+        assert!(stir_protocol.config.round_parameters.is_empty());
+        assert_eq!(stir_protocol.config.final_poly_log_degree, 16);
+        assert_eq!(stir_protocol.protocol.num_classical_rounds(), 1);
+        let final_poly_size_bits = (1 << 16) * GOLDILOCKS_2.extension_bit_size();
+        assert!(10 * final_poly_size_bits > 9 * stir_protocol.protocol.proof_size_bits());
+
+        // Otherwise, only the starting fold is done
+        let stir_protocol = StirProtocol::new(ldt_parameters, stir_parameters(4)).unwrap();
+        assert_eq!(stir_protocol.config.final_poly_log_degree, 12);
+        assert_eq!(stir_protocol.protocol.num_classical_rounds(), 2);
+    }
+
+    #[test]
+    fn test_final_round_has_only_query_error() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 20,
+            batch_size: 1,
+            constraint_degree: 0,
+            num_constraints: 1,
+        };
+        let stir_parameters = StirParameters::fixed_domain_shift(
+            1,
+            4,
+            3,
+            SecurityAssumption::CapacityBound,
+            100,
+            20,
+            256,
+        );
+        let stir_protocol = StirProtocol::new(ldt_parameters, stir_parameters).unwrap();
+
+        // The consistency of the final polynomial is covered by the final query error,
+        // see `ProverMessage::final_polynomial`
+        let final_round: Vec<_> = stir_protocol
+            .protocol
+            .rbr_breakdown()
+            .into_iter()
+            .filter(|round| round.round_name == "final_round")
+            .collect();
+        assert_eq!(final_round.len(), 1);
+        let terms = &final_round[0].terms;
+        assert_eq!(terms.len(), 1);
+        assert_eq!(terms[0].0, "query_error");
+        assert!(final_round[0].combined >= 100.);
+        assert_eq!(
+            stir_protocol.protocol.rbr_errors().len(),
+            stir_protocol.protocol.num_classical_rounds()
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 20,
+            batch_size: 1,
+            constraint_degree: 0,
+            num_constraints: 1,
+        };
+        let domain_shift = StirProtocol::new(
+            ldt_parameters,
+            StirParameters::fixed_domain_shift(
+                1,
+                4,
+                3,
+                SecurityAssumption::CapacityBound,
+                100,
+                20,
+                256,
+            ),
+        )
+        .unwrap();
+        let rate_folding = StirProtocol::new(
+            ldt_parameters,
+            StirParameters::fixed_rate_folding(
+                1,
+                4,
+                3,
+                SecurityAssumption::CapacityBound,
+                100,
+                20,
+                256,
+            ),
+        )
+        .unwrap();
+
+        let diff = domain_shift.protocol.diff(&domain_shift.protocol);
+        assert_eq!(diff.proof_size_bits_delta, 0);
+        assert_eq!(diff.rbr_error_delta, 0.);
+        assert!(diff.round_deltas.iter().all(|&(_, delta)| delta == 0));
+
+        let diff = domain_shift.protocol.diff(&rate_folding.protocol);
+        assert_eq!(
+            diff.proof_size_bits_delta,
+            diff.round_deltas
+                .iter()
+                .map(|(_, delta)| delta)
+                .sum::<isize>()
+        );
+    }
+
+    #[test]
+    fn test_to_parameters() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 20,
+            batch_size: 2,
+            constraint_degree: 0,
+            num_constraints: 1,
+        };
+        let round_trip = |stir_parameters: StirParameters| {
+            let config = StirProtocol::new(ldt_parameters, stir_parameters)
+                .unwrap()
+                .config;
+            let rebuilt = StirProtocol::new(config.ldt_parameters, config.to_parameters())
+                .unwrap()
+                .config;
+            assert_eq!(rebuilt, config);
+        };
+
+        let stir_parameters = StirParameters::fixed_domain_shift(
+            1,
+            4,
+            3,
+            SecurityAssumption::CapacityBound,
+            100,
+            20,
+            256,
+        );
+        round_trip(stir_parameters.clone());
+        round_trip(StirParameters {
+            security_assumptions: vec![
+                SecurityAssumption::JohnsonBound,
+                SecurityAssumption::CapacityBound,
+                SecurityAssumption::UniqueDecoding,
+            ],
+            eta_factor: Some(4.),
+            folding_pow_bits: Some(10),
+            digest: Some(DigestConfig::Sha3_256),
+            no_grinding: true,
+            zero_knowledge: true,
+            interleaving: InterleavingMode::Separate,
+            ood_strategy: OodStrategy::SinglePoint,
+            ..stir_parameters
+        });
+    }
+}
@@ -1,39 +1,149 @@
 use std::fmt;
 
-pub(crate) fn pow_util(security_level: usize, error: f64) -> f64 {
-    0f64.max(security_level as f64 - error)
+/// The pow bits needed to lift `error` to the security level, clamped to the grinding cap if given.
+pub(crate) fn pow_util(security_level: usize, error: f64, grinding_cap: Option<usize>) -> f64 {
+    let pow_bits = 0f64.max(security_level as f64 - error);
+    grinding_cap.map_or(pow_bits, |cap| pow_bits.min(cap as f64))
 }
 
-/// Converts a number of bits into an appropriate unit.
+/// Converts a rate given as log(1/ρ) (the convention used throughout the crate) into the rate ρ = 2^-log_inv_rate.
+pub fn rate_from_log_inv(log_inv_rate: usize) -> f64 {
+    2_f64.powi(-(log_inv_rate as i32))
+}
+
+/// Converts a rate ρ into log(1/ρ), the inverse of `rate_from_log_inv`.
+/// The result is fractional if ρ is not a power of two.
+pub fn log_inv_from_rate(rate: f64) -> f64 {
+    -rate.log2()
+}
+
+/// Given errors in bits (i.e. each error is 2^-e), computes the bits of their sum, i.e. -log2(sum(2^-e_i)).
+/// The computation is done relative to the smallest error to avoid losing precision.
+pub(crate) fn sum_errors(errors: impl IntoIterator<Item = f64>) -> f64 {
+    let errors: Vec<f64> = errors.into_iter().collect();
+    let min_error = errors.iter().copied().fold(f64::INFINITY, f64::min);
+    let sum: f64 = errors.iter().map(|e| 2_f64.powf(min_error - e)).sum();
+    min_error - sum.log2()
+}
+
+/// The units used when displaying sizes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitKind {
+    /// Powers of 1024, labelled with IEC units (KiB, MiB, ...).
+    Binary,
+    /// Powers of 1000, labelled with SI units (kB, MB, ...).
+    Decimal,
+}
+
+/// Converts a number of bits into an appropriate binary unit.
 pub(crate) fn display_size(bits: usize) -> String {
+    display_size_with_unit(bits, UnitKind::Binary)
+}
+
+/// Converts a number of bits into an appropriate unit of the given kind.
+pub fn display_size_with_unit(bits: usize, unit_kind: UnitKind) -> String {
     if bits == 0 {
         return "0B".to_owned();
     }
 
+    let (base, size_name) = match unit_kind {
+        UnitKind::Binary => (
+            1024_f64,
+            ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB", "ZiB", "YiB"],
+        ),
+        UnitKind::Decimal => (
+            1000_f64,
+            ["B", "kB", "MB", "GB", "TB", "PB", "EB", "ZB", "YB"],
+        ),
+    };
+
     let size_bytes = bits as f64 / 8.;
-    let size_name = ["B", "KB", "MB", "GB", "TB", "PB", "EB", "ZB", "YB"];
-    let i = size_bytes.log(1024_f64).floor() as usize;
-    let p = 1024_f64.powf(i as f64);
+    let i = (size_bytes.log(base).floor() as usize).min(size_name.len() - 1);
+    let p = base.powf(i as f64);
     let s = (size_bytes / p).round();
 
     format!("{} {}", s, size_name[i])
 }
 
-/// Prints prettily a slice of floats
-pub(crate) fn pretty_print_float_slice(f: &mut fmt::Formatter<'_>, v: &[f64]) -> fmt::Result {
+/// The header of the CSV tables describing the rounds of a protocol.
+pub(crate) const ROUNDS_CSV_HEADER: &str =
+    "round,folding_factor,evaluation_domain_log_size,log_inv_rate,num_queries,ood_samples,pow_bits";
+
+/// Prints a warning listing the PoW locations that exceed the configured maximum (if any).
+pub(crate) fn print_pow_warning(
+    f: &mut std::fmt::Formatter<'_>,
+    max_pow_bits: usize,
+    exceeded: &[(String, f64)],
+) -> std::fmt::Result {
+    if exceeded.is_empty() {
+        return Ok(());
+    }
+
+    let precision = float_precision(f);
+    let locations: Vec<_> = exceeded
+        .iter()
+        .map(|(location, pow_bits)| format!("{location}: {pow_bits:.precision$}"))
+        .collect();
+    writeln!(
+        f,
+        "⚠ PoW exceeds max ({max_pow_bits} bits): {}",
+        locations.join(", ")
+    )
+}
+
+/// The fraction of the proof size above which the final polynomial is considered to dominate.
+pub(crate) const FINAL_POLY_WARNING_FRACTION: f64 = 0.1;
+
+/// Prints a warning if the final polynomial makes up a large fraction of the proof (if so, more folding is advised).
+pub(crate) fn print_final_poly_warning(
+    f: &mut std::fmt::Formatter<'_>,
+    final_poly_size_bits: usize,
+    proof_size_bits: usize,
+) -> std::fmt::Result {
+    let fraction = final_poly_size_bits as f64 / proof_size_bits as f64;
+    if fraction <= FINAL_POLY_WARNING_FRACTION {
+        return Ok(());
+    }
+
+    let precision = float_precision(f);
+    writeln!(
+        f,
+        "⚠ Final polynomial is {:.precision$}% of the proof ({}), consider more folding rounds",
+        fraction * 100.,
+        display_size(final_poly_size_bits)
+    )
+}
+
+/// The number of decimals floats are displayed with, unless the formatter asks for a precision (e.g. `{:.3}`).
+pub(crate) const DEFAULT_FLOAT_PRECISION: usize = 1;
+
+/// The number of decimals to display floats with in `f`, i.e. its precision if given.
+pub(crate) fn float_precision(f: &fmt::Formatter<'_>) -> usize {
+    f.precision().unwrap_or(DEFAULT_FLOAT_PRECISION)
+}
+
+/// Prints prettily a slice of floats, each with `precision` decimals
+pub(crate) fn pretty_print_float_slice(
+    f: &mut fmt::Formatter<'_>,
+    v: &[f64],
+    precision: usize,
+) -> fmt::Result {
     write!(f, "[")?;
     for (i, value) in v.iter().enumerate() {
         if i > 0 {
             write!(f, ", ")?;
         }
-        write!(f, "{value:.1}")?;
+        write!(f, "{value:.precision$}")?;
     }
     writeln!(f, "]")
 }
 
 #[cfg(test)]
 mod tests {
-    use super::display_size;
+    use super::{
+        display_size, display_size_with_unit, log_inv_from_rate, rate_from_log_inv, sum_errors,
+        UnitKind,
+    };
 
     #[test]
     fn test_display_size_zero() {
@@ -42,6 +152,33 @@ mod tests {
 
     #[test]
     fn test_display_size_one_kb() {
-        assert_eq!(display_size(8 * 1024), "1 KB");
+        assert_eq!(display_size(8 * 1024), "1 KiB");
+    }
+
+    #[test]
+    fn test_display_size_decimal() {
+        assert_eq!(display_size_with_unit(8000, UnitKind::Decimal), "1 kB");
+        assert_eq!(display_size_with_unit(8000, UnitKind::Binary), "1000 B");
+    }
+
+    #[test]
+    fn test_sum_errors() {
+        assert_eq!(sum_errors([100., 100.]), 99.);
+        assert!((sum_errors([100., 200.]) - 100.).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_rate_conversions() {
+        assert_eq!(rate_from_log_inv(0), 1.);
+        assert_eq!(rate_from_log_inv(1), 0.5);
+        assert_eq!(rate_from_log_inv(2), 0.25);
+        assert_eq!(log_inv_from_rate(0.25), 2.);
+        assert_eq!(log_inv_from_rate(0.125), 3.);
+        for log_inv_rate in 0..10 {
+            assert_eq!(
+                log_inv_from_rate(rate_from_log_inv(log_inv_rate)),
+                log_inv_rate as f64
+            );
+        }
     }
 }
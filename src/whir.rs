@@ -1,14 +1,26 @@
-use std::fmt::Display;
+use std::{
+    fmt::{Display, Write},
+    ops::Range,
+};
 
 use crate::{
-    errors::SecurityAssumption,
+    errors::{OodStrategy, SecurityAssumption, WhirError, DEFAULT_ETA_FACTOR},
+    field::Field,
+    hash::DigestConfig,
     protocol::{
         builder::ProtocolBuilder,
-        proof_size::{FieldElements, MerkleQueries, MerkleTree, ProofElement},
-        Protocol, ProverMessage, RbRError, VerifierMessage,
+        proof_size::{
+            merkle_digest_bits, Encoding, FieldElements, FinalPhase, InterleavingMode,
+            MerkleQueries, MerkleTree, ProofElement,
+        },
+        total_oracle_bits, Protocol, ProtocolReport, ProverMessage, RbRError, Report,
+        VerifierMessage,
+    },
+    utils::{
+        float_precision, pow_util, pretty_print_float_slice, print_final_poly_warning,
+        print_pow_warning, ROUNDS_CSV_HEADER,
     },
-    utils::{pow_util, pretty_print_float_slice},
-    LowDegreeParameters,
+    LowDegreeParameters, LowDegreeTest,
 };
 
 /// Parameters parametrizing an instance of WHIR.
@@ -24,6 +36,7 @@ pub struct WhirParameters {
 
     /// The folding factors in the remaining rounds.
     /// Given in log form, i.e. folding_factors[i] = 2 implies that the degree in round i is reduced by a factor of 4.
+    /// If empty, only the starting fold is done, and with a starting folding factor of 0 the polynomial is sent whole.
     pub folding_factors: Vec<usize>,
 
     /// The rates used in the internal rounds of WHIR.
@@ -32,19 +45,117 @@ pub struct WhirParameters {
     /// The security assumption under which to configure WHIR.
     pub security_assumption: SecurityAssumption,
 
+    /// Optionally, the security assumption to use in each round (falls back to `security_assumption` if empty).
+    pub security_assumptions: Vec<SecurityAssumption>,
+
+    /// Optionally, the divisor used to set η (see `SecurityAssumption::log_eta_with_factor`), defaults to `DEFAULT_ETA_FACTOR`.
+    pub eta_factor: Option<f64>,
+
+    /// Optionally, a cap on the (log) list size, see `SecurityAssumptionWithEta::with_max_list_size_bits`.
+    pub max_list_size_bits: Option<f64>,
+
     /// The security level desired.
     pub security_level: usize,
 
     /// The number of pow bits to use to reduce query error.
     /// Traditionally called also "grinding".
-    /// NOTE: This does not affect the pow bits used to reduce proximity gaps errors.
+    /// NOTE: This does not bound the pow bits used to reduce proximity gaps errors, see `folding_pow_bits`.
     pub pow_bits: usize,
 
-    /// The size of the digest for the Merkle tree
-    pub digest_size_bits: usize,
+    /// Optionally, the maximum number of pow bits used to reduce proximity gaps (folding and batching) errors.
+    /// If unset, these are ground up to the security level.
+    pub folding_pow_bits: Option<usize>,
+
+    /// Optionally, a hard cap on the pow bits of any grind (e.g. the most that is practical on the prover's hardware).
+    /// Unlike `pow_bits`, the queries are not increased to compensate, and so the protocol may fall short of
+    /// `security_level` (see `security_shortfall` in the config).
+    pub grinding_cap: Option<usize>,
+
+    /// The size of the digest for the Merkle tree. If `None`, digests are sized for the security level
+    /// (see `digest_size_bits_for_security_level`).
+    pub digest_size_bits: Option<usize>,
+
+    /// Optionally, the hash used for the Merkle digests, overriding `digest_size_bits` with its output size
+    /// and bounding the collision resistance of the digests.
+    pub digest: Option<DigestConfig>,
+
+    /// Whether grinding is forbidden, in which case no PoW is used and `pow_bits` is ignored.
+    pub no_grinding: bool,
+
+    /// Whether the commitments are zero-knowledge, in which case the Merkle leaves are salted.
+    pub zero_knowledge: bool,
+
+    /// Whether the statement (i.e. the weights of the constraint) is over the base field, e.g. an evaluation claim at
+    /// a base field point. Only then, and if nothing is batched, does the first sumcheck polynomial precede any verifier
+    /// randomness, and so is sent over the base field. The usual evaluation claim is at an extension field point.
+    pub base_field_statement: bool,
+
+    /// How field elements are serialized in the proof.
+    pub encoding: Encoding,
+
+    /// How the batched polynomials are committed to in the starting commitment, see `InterleavingMode`.
+    pub interleaving: InterleavingMode,
+
+    /// How the final polynomial is checked, see `FinalPhase`.
+    pub final_phase: FinalPhase,
+
+    /// The smallest (log) degree the final polynomial may have, guarding against folding all the way down by accident.
+    pub min_final_log_degree: usize,
+
+    /// How the out-of-domain samples are drawn, see `OodStrategy`.
+    pub ood_strategy: OodStrategy,
 }
 
 impl WhirParameters {
+    /// Checks the parameters against the LDT parameters, reporting every violation rather than only the first.
+    /// These are the checks done when constructing the protocol, see `WhirProtocol::new`.
+    pub fn validate(&self, ldt_parameters: &LowDegreeParameters) -> Result<(), Vec<WhirError>> {
+        let mut errors = vec![];
+        errors.extend(ldt_parameters.validate().err());
+
+        // Each fold needs to reduce the degree, only without rounds may the starting fold be skipped
+        if (self.starting_folding_factor == 0 && !self.folding_factors.is_empty())
+            || self.folding_factors.contains(&0)
+        {
+            errors.push(WhirError::ZeroFoldingFactor);
+        }
+        if self.folding_factors.len() != self.log_inv_rates.len()
+            || (!self.security_assumptions.is_empty()
+                && self.security_assumptions.len() != self.folding_factors.len())
+        {
+            errors.push(WhirError::MismatchedRoundVectors);
+        }
+
+        // We cannot fold too much
+        let total_reduction =
+            self.starting_folding_factor + self.folding_factors.iter().sum::<usize>();
+        if total_reduction > ldt_parameters.log_degree {
+            errors.push(WhirError::OverFolded {
+                total_reduction,
+                log_degree: ldt_parameters.log_degree,
+            });
+        } else if ldt_parameters.log_degree - total_reduction < self.min_final_log_degree {
+            errors.push(WhirError::FinalDegreeTooSmall {
+                final_log_degree: ldt_parameters.log_degree - total_reduction,
+                min_final_log_degree: self.min_final_log_degree,
+            });
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// The security assumption used in the given round.
+    pub fn round_security_assumption(&self, round: usize) -> SecurityAssumption {
+        self.security_assumptions
+            .get(round)
+            .copied()
+            .unwrap_or(self.security_assumption)
+    }
+
     /// Instantiates a WHIR configuration in which the rate is constant. This is a worse version of FRI.
     pub fn fixed_rate_folding(
         log_inv_rate: usize,
@@ -61,9 +172,23 @@ impl WhirParameters {
             folding_factors: vec![folding_factor; num_rounds],
             log_inv_rates: vec![log_inv_rate; num_rounds],
             security_assumption,
+            security_assumptions: vec![],
+            eta_factor: None,
+            max_list_size_bits: None,
             security_level,
-            digest_size_bits,
+            digest_size_bits: Some(digest_size_bits),
+            digest: None,
             pow_bits,
+            no_grinding: false,
+            folding_pow_bits: None,
+            grinding_cap: None,
+            zero_knowledge: false,
+            base_field_statement: false,
+            encoding: Encoding::default(),
+            interleaving: InterleavingMode::default(),
+            final_phase: FinalPhase::default(),
+            min_final_log_degree: 0,
+            ood_strategy: OodStrategy::default(),
         }
     }
 
@@ -86,10 +211,128 @@ impl WhirParameters {
                 .map(|i| log_inv_rate + (i + 1) * (folding_factor - 1))
                 .collect(),
             security_assumption,
-            digest_size_bits,
+            security_assumptions: vec![],
+            eta_factor: None,
+            max_list_size_bits: None,
+            digest_size_bits: Some(digest_size_bits),
+            digest: None,
             security_level,
             pow_bits,
+            no_grinding: false,
+            folding_pow_bits: None,
+            grinding_cap: None,
+            zero_knowledge: false,
+            base_field_statement: false,
+            encoding: Encoding::default(),
+            interleaving: InterleavingMode::default(),
+            final_phase: FinalPhase::default(),
+            min_final_log_degree: 0,
+            ood_strategy: OodStrategy::default(),
+        }
+    }
+
+    /// A WHIR configuration as in `fixed_domain_shift`, where the number of rounds is chosen for the given LDT parameters:
+    /// rounds are added for as long as another round (commitment and queries) is cheaper than the part of the final
+    /// polynomial it saves, see `marginal_round_cost`.
+    /// Fails if not even a single round can be configured.
+    pub fn auto_rounds(
+        ldt_parameters: LowDegreeParameters,
+        log_inv_rate: usize,
+        folding_factor: usize,
+        security_assumption: SecurityAssumption,
+        security_level: usize,
+        pow_bits: usize,
+        digest_size_bits: usize,
+    ) -> Result<Self, WhirError> {
+        let with_rounds = |num_rounds| {
+            WhirParameters::fixed_domain_shift(
+                log_inv_rate,
+                folding_factor,
+                num_rounds,
+                security_assumption,
+                security_level,
+                pow_bits,
+                digest_size_bits,
+            )
+        };
+
+        // Check that a single round can be configured at all
+        let mut num_rounds = 1;
+        WhirProtocol::new(ldt_parameters, with_rounds(num_rounds))?;
+
+        // Stop once another round does not pay for itself (or does not fit)
+        while marginal_round_cost(ldt_parameters, with_rounds(num_rounds))
+            .is_ok_and(|cost| cost < 0)
+        {
+            num_rounds += 1;
+        }
+
+        Ok(with_rounds(num_rounds))
+    }
+
+    /// A WHIR configuration which folds by `start_factor` first and then follows the `taper` schedule,
+    /// with the domain shrinking by (1/2) in each iteration (as in `fixed_domain_shift`).
+    /// The folding schedule is validated against the degree `2^log_degree`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn tapered(
+        log_degree: usize,
+        log_inv_rate: usize,
+        start_factor: usize,
+        taper: &[usize],
+        security_assumption: SecurityAssumption,
+        security_level: usize,
+        pow_bits: usize,
+        digest_size_bits: usize,
+    ) -> Result<Self, WhirError> {
+        if start_factor == 0 || taper.contains(&0) {
+            return Err(WhirError::ZeroFoldingFactor);
+        }
+        if taper.is_empty() {
+            return Err(WhirError::NoRounds);
+        }
+        let total_reduction = start_factor + taper.iter().sum::<usize>();
+        if total_reduction > log_degree {
+            return Err(WhirError::OverFolded {
+                total_reduction,
+                log_degree,
+            });
         }
+
+        // The oracle of each round is folded by the previous factor (the starting one first) on a domain of half the size
+        let mut current_log_inv_rate = log_inv_rate;
+        let log_inv_rates = std::iter::once(start_factor)
+            .chain(taper.iter().copied())
+            .take(taper.len())
+            .map(|folding_factor| {
+                current_log_inv_rate += folding_factor - 1;
+                current_log_inv_rate
+            })
+            .collect();
+
+        Ok(WhirParameters {
+            starting_log_inv_rate: log_inv_rate,
+            starting_folding_factor: start_factor,
+            folding_factors: taper.to_vec(),
+            log_inv_rates,
+            security_assumption,
+            security_assumptions: vec![],
+            eta_factor: None,
+            max_list_size_bits: None,
+            digest_size_bits: Some(digest_size_bits),
+            digest: None,
+            security_level,
+            pow_bits,
+            no_grinding: false,
+            folding_pow_bits: None,
+            grinding_cap: None,
+            zero_knowledge: false,
+            base_field_statement: false,
+            encoding: Encoding::default(),
+            interleaving: InterleavingMode::default(),
+            final_phase: FinalPhase::default(),
+            min_final_log_degree: 0,
+            ood_strategy: OodStrategy::default(),
+        })
     }
 }
 
@@ -102,71 +345,123 @@ pub struct WhirProtocol {
 
 impl WhirProtocol {
     /// Given a LDT parameter and some parameters for WHIR, populate the config.
-    pub fn new(ldt_parameters: LowDegreeParameters, whir_parameters: WhirParameters) -> Self {
-        // We need to fold at least some time
-        assert!(
-            whir_parameters.starting_folding_factor > 0
-                && whir_parameters.folding_factors.iter().all(|&x| x > 0),
-            "folding factors should be non zero"
-        );
-        assert_eq!(
-            whir_parameters.folding_factors.len(),
-            whir_parameters.log_inv_rates.len()
-        );
-
-        // We cannot fold too much
-        let total_reduction = whir_parameters.starting_folding_factor
-            + whir_parameters.folding_factors.iter().sum::<usize>();
-        assert!(total_reduction <= ldt_parameters.log_degree);
-
-        // If less, just send the damn polynomials
-        assert!(ldt_parameters.log_degree >= whir_parameters.folding_factors[0]);
+    /// Without any folds (i.e. no rounds and no starting fold), this is the trivial LDT: the prover sends the whole
+    /// polynomial, which the verifier checks against the committed evaluations at the queried points.
+    pub fn new(
+        ldt_parameters: LowDegreeParameters,
+        whir_parameters: WhirParameters,
+    ) -> Result<Self, WhirError> {
+        // Check the parameters, reporting the first violation
+        if let Err(errors) = whir_parameters.validate(&ldt_parameters) {
+            return Err(errors[0]);
+        }
+        let final_log_degree = ldt_parameters.log_degree
+            - whir_parameters.starting_folding_factor
+            - whir_parameters.folding_factors.iter().sum::<usize>();
 
-        // Compute the number of rounds and the leftover
-        let final_log_degree = ldt_parameters.log_degree - total_reduction;
+        // Compute the number of rounds
         let num_rounds = whir_parameters.folding_factors.len();
 
         // Compute the security level
         let security_level = whir_parameters.security_level;
-        let protocol_security_level =
-            0.max(whir_parameters.security_level - whir_parameters.pow_bits);
+        // Without grinding, the queries alone reach the security level and no PoW is used anywhere
+        let max_pow_bits = if whir_parameters.no_grinding {
+            0
+        } else {
+            whir_parameters.pow_bits
+        };
+        let protocol_security_level = security_level.saturating_sub(max_pow_bits);
+        let pow_security_level = if whir_parameters.no_grinding {
+            0
+        } else {
+            security_level
+        };
+        // The PoW for the proximity gaps errors is capped separately from the one for the queries
+        let folding_pow_cap = whir_parameters
+            .folding_pow_bits
+            .map_or(f64::INFINITY, |bits| bits as f64);
+        let grinding_cap = whir_parameters.grinding_cap;
+        let folding_pow_util =
+            |error: f64| pow_util(pow_security_level, error, grinding_cap).min(folding_pow_cap);
+
+        // The security assumption (and choice of η) used to compute the errors
+        let eta_factor = whir_parameters.eta_factor.unwrap_or(DEFAULT_ETA_FACTOR);
+        let security_assumption = whir_parameters
+            .security_assumption
+            .with_eta_factor(eta_factor)
+            .with_list_size_cap(whir_parameters.max_list_size_bits);
 
         // Initial domain size (the trace domain)
         let starting_folding_factor = whir_parameters.starting_folding_factor;
         let starting_domain_log_size = ldt_parameters.log_degree - starting_folding_factor
             + whir_parameters.starting_log_inv_rate;
 
-        let mut protocol_builder =
-            ProtocolBuilder::new("WHIR protocol", whir_parameters.digest_size_bits);
+        // Each sumcheck polynomial is sent as its sumcheck_degree + 1 coefficients
+        let sumcheck_degree = sumcheck_degree(&ldt_parameters);
+
+        let (digest_size_bits, collision_bits) = merkle_digest_bits(
+            whir_parameters.digest,
+            whir_parameters.digest_size_bits,
+            security_level,
+        );
+
+        // In zero-knowledge mode, each leaf is salted with randomness
+        let salt_bits = if whir_parameters.zero_knowledge {
+            2 * security_level
+        } else {
+            0
+        };
+
+        // How field elements are serialized in the proof
+        let encoding = whir_parameters.encoding;
+        let interleaving = whir_parameters.interleaving;
+        let final_phase = whir_parameters.final_phase;
+        let ood_strategy = whir_parameters.ood_strategy;
+
+        // Each verifier challenge (other than the query indices) is an extension field element
+        let challenge_bits = ldt_parameters.field.extension_bit_size();
+
+        let mut protocol_builder = ProtocolBuilder::new("WHIR protocol", digest_size_bits)
+            .with_collision_bits(collision_bits);
 
         // Pow bits for the batching steps
         let mut batching_pow_bits = 0.;
         if ldt_parameters.batch_size > 1 {
-            let prox_gaps_error_batching = whir_parameters.security_assumption.prox_gaps_error(
+            let prox_gaps_error_batching = security_assumption.prox_gaps_error(
                 ldt_parameters.log_degree,
                 whir_parameters.starting_log_inv_rate,
                 ldt_parameters.field.extension_bit_size(),
                 ldt_parameters.batch_size,
             ); // we now start, the initial folding pow bits
-            batching_pow_bits = pow_util(security_level, prox_gaps_error_batching);
+            batching_pow_bits = folding_pow_util(prox_gaps_error_batching);
 
-            // Add the round for the batching
+            // Add the round for the batching.
+            // NOTE: No commitment to the combined codeword is needed, as the starting tree stores the evaluations
+            // of all the batched functions in each leaf, from which the verifier derives the combination.
             protocol_builder = protocol_builder
                 .start_round("batching_round")
-                .verifier_message(VerifierMessage::new(
-                    vec![RbRError::new("batching_error", prox_gaps_error_batching)],
-                    batching_pow_bits,
-                ))
+                .verifier_message(
+                    VerifierMessage::new(
+                        vec![RbRError::new("batching_error", prox_gaps_error_batching)],
+                        batching_pow_bits,
+                    )
+                    .with_challenge_bits(challenge_bits),
+                )
                 .end_round();
         }
 
         // Merkle tree committed to
-        let mut current_merkle_tree = MerkleTree::new(
+        let mut current_merkle_tree = MerkleTree::with_digest_size(
             starting_domain_log_size,
             ldt_parameters.field,
-            (1 << starting_folding_factor) * ldt_parameters.batch_size,
+            (1 << starting_folding_factor) * interleaving.polys_per_tree(ldt_parameters.batch_size),
             false, // first tree is over the base
-        );
+            digest_size_bits,
+        )
+        .with_salt_bits(salt_bits)
+        .with_encoding(encoding);
+        // How many trees the current oracle is spread over (only the starting one may be more than one)
+        let mut current_num_trees = interleaving.num_trees(ldt_parameters.batch_size);
 
         // Degree of next polynomial to send
         let mut current_log_degree = ldt_parameters.log_degree;
@@ -175,43 +470,90 @@ impl WhirProtocol {
         let mut starting_folding_pow_bits_vec =
             Vec::with_capacity(whir_parameters.starting_folding_factor);
 
-        protocol_builder = protocol_builder.start_round("whir_iteration");
-        for _ in 0..whir_parameters.starting_folding_factor {
+        // Without a starting fold (only allowed without rounds) or claims, the polynomial is just sent whole
+        let has_starting_round =
+            starting_folding_factor > 0 || ldt_parameters.constraint_degree > 0;
+        if has_starting_round {
+            protocol_builder = protocol_builder.start_round("whir_iteration");
+        }
+
+        // As a PCS, the prover starts by sending the claimed value of each constraint for each committed polynomial
+        if ldt_parameters.constraint_degree > 0 {
+            protocol_builder =
+                protocol_builder.prover_message(ProverMessage::new(ProofElement::FieldElements(
+                    FieldElements::extension(
+                        ldt_parameters.field,
+                        ldt_parameters.batch_size * ldt_parameters.num_constraints,
+                    )
+                    .with_encoding(encoding),
+                )));
+
+            // Several constraints are combined into one (of the maximum degree) with verifier randomness
+            if ldt_parameters.num_constraints > 1 {
+                let constraint_batching_error = security_assumption.constraint_folding_error(
+                    ldt_parameters.log_degree,
+                    whir_parameters.starting_log_inv_rate,
+                    ldt_parameters.field.extension_bit_size(),
+                    ldt_parameters.num_constraints,
+                );
+                protocol_builder = protocol_builder.verifier_message(
+                    VerifierMessage::new(
+                        vec![RbRError::new(
+                            "constraint_batching_error",
+                            constraint_batching_error,
+                        )],
+                        folding_pow_util(constraint_batching_error),
+                    )
+                    .with_challenge_bits(challenge_bits),
+                );
+            }
+        }
+
+        // See `WhirParameters::base_field_statement`
+        let base_field_sumcheck = whir_parameters.base_field_statement
+            && ldt_parameters.batch_size == 1
+            && (ldt_parameters.constraint_degree == 0 || ldt_parameters.num_constraints == 1);
+
+        for i in 0..whir_parameters.starting_folding_factor {
             // we now start, the initial folding pow bits
-            let prox_gaps_error = whir_parameters.security_assumption.prox_gaps_error(
+            let prox_gaps_error = security_assumption.prox_gaps_error(
                 current_log_degree - 1,
                 log_inv_rate,
                 ldt_parameters.field.extension_bit_size(),
                 1 << starting_folding_factor,
             );
 
-            let sumcheck_error = whir_parameters
-                .security_assumption
-                .constraint_folding_error(
-                    current_log_degree,
-                    log_inv_rate,
-                    ldt_parameters.field.extension_bit_size(),
-                    ldt_parameters.constraint_degree,
-                );
+            let sumcheck_error = security_assumption.constraint_folding_error(
+                current_log_degree,
+                log_inv_rate,
+                ldt_parameters.field.extension_bit_size(),
+                sumcheck_degree,
+            );
+
+            let starting_folding_pow_bits = folding_pow_util(prox_gaps_error.min(sumcheck_error));
 
-            let starting_folding_pow_bits =
-                pow_util(security_level, prox_gaps_error.min(sumcheck_error));
+            let sumcheck_polynomial = if i == 0 && base_field_sumcheck {
+                FieldElements::base(ldt_parameters.field, sumcheck_degree + 1)
+                    .with_encoding(encoding)
+            } else {
+                FieldElements::extension(ldt_parameters.field, sumcheck_degree + 1)
+                    .with_encoding(encoding)
+            };
 
             protocol_builder = protocol_builder
                 .prover_message(ProverMessage::new(ProofElement::FieldElements(
-                    FieldElements {
-                        field: ldt_parameters.field,
-                        num_elements: ldt_parameters.constraint_degree + 1,
-                        is_extension: true,
-                    },
+                    sumcheck_polynomial,
                 )))
-                .verifier_message(VerifierMessage::new(
-                    vec![
-                        RbRError::new("folding_error", prox_gaps_error),
-                        RbRError::new("sumcheck_error", sumcheck_error),
-                    ],
-                    starting_folding_pow_bits,
-                ));
+                .verifier_message(
+                    VerifierMessage::new(
+                        vec![
+                            RbRError::new("folding_error", prox_gaps_error),
+                            RbRError::new("sumcheck_error", sumcheck_error),
+                        ],
+                        starting_folding_pow_bits,
+                    )
+                    .with_challenge_bits(challenge_bits),
+                );
 
             starting_folding_pow_bits_vec.push(starting_folding_pow_bits);
             current_log_degree -= 1;
@@ -219,95 +561,112 @@ impl WhirProtocol {
 
         let mut round_parameters = Vec::with_capacity(num_rounds);
 
-        for (i, (folding_factor, next_rate)) in whir_parameters
+        let round_security_assumptions: Vec<_> = (0..num_rounds)
+            .map(|i| {
+                whir_parameters
+                    .round_security_assumption(i)
+                    .with_eta_factor(eta_factor)
+                    .with_list_size_cap(whir_parameters.max_list_size_bits)
+            })
+            .collect();
+
+        for (i, ((folding_factor, next_rate), round_security_assumption)) in whir_parameters
             .folding_factors
             .into_iter()
             .zip(whir_parameters.log_inv_rates)
+            .zip(round_security_assumptions)
             .enumerate()
         {
             // This is the size of the new evaluation domain
             let new_evaluation_domain_size = current_log_degree - folding_factor + next_rate;
 
             // Send the new oracle
-            let next_merkle_tree = MerkleTree::new(
+            let next_merkle_tree = MerkleTree::with_digest_size(
                 new_evaluation_domain_size,
                 ldt_parameters.field,
                 1 << folding_factor,
                 true,
-            );
+                digest_size_bits,
+            )
+            .with_salt_bits(salt_bits)
+            .with_encoding(encoding);
             protocol_builder = protocol_builder.prover_message(ProverMessage::new(
                 ProofElement::MerkleRoot(next_merkle_tree),
             ));
 
             // Compute the ood samples required
-            let ood_samples = whir_parameters.security_assumption.determine_ood_samples(
+            let ood_samples = round_security_assumption.determine_ood_samples(
                 security_level,
                 current_log_degree,
                 next_rate,
                 ldt_parameters.field.extension_bit_size(),
-            );
+                ood_strategy,
+            )?;
 
             // Add OOD rounds to protocol
             if ood_samples > 0 {
-                let ood_error = whir_parameters.security_assumption.ood_error(
+                let ood_error = round_security_assumption.ood_error(
                     current_log_degree,
                     next_rate,
                     ldt_parameters.field.extension_bit_size(),
                     ood_samples,
+                    ood_strategy,
                 );
 
                 protocol_builder = protocol_builder
-                    .verifier_message(VerifierMessage::new(
-                        vec![RbRError::new("ood_error", ood_error)],
-                        0.,
-                    ))
+                    .verifier_message(
+                        VerifierMessage::new(vec![RbRError::new("ood_error", ood_error)], 0.)
+                            .with_challenge_bits(ood_samples * challenge_bits),
+                    )
                     .prover_message(ProverMessage::new(ProofElement::FieldElements(
-                        FieldElements {
-                            field: ldt_parameters.field,
-                            num_elements: ood_samples,
-                            is_extension: true,
-                        },
+                        FieldElements::extension(ldt_parameters.field, ood_samples)
+                            .with_encoding(encoding),
                     )));
             }
 
             // Compute the number of queries required
-            let num_queries = whir_parameters
-                .security_assumption
-                .queries(protocol_security_level, log_inv_rate);
+            let num_queries =
+                round_security_assumption.queries(protocol_security_level, log_inv_rate)?;
 
             // We need to compute the errors, to compute the according PoW
-            let query_error = whir_parameters
-                .security_assumption
-                .queries_error(log_inv_rate, num_queries);
+            let query_error = round_security_assumption.queries_error(log_inv_rate, num_queries)?;
 
             let num_terms = num_queries + ood_samples;
-            let batching_error = whir_parameters
-                .security_assumption
-                .constraint_folding_error(
-                    current_log_degree,
-                    log_inv_rate,
-                    ldt_parameters.field.extension_bit_size(),
-                    num_terms,
-                );
+            let batching_error = round_security_assumption.constraint_folding_error(
+                current_log_degree,
+                log_inv_rate,
+                ldt_parameters.field.extension_bit_size(),
+                num_terms,
+            );
 
             // Now compute the PoW
-            let query_pow_bits = pow_util(security_level, query_error.min(batching_error));
+            let query_pow_bits = pow_util(
+                pow_security_level,
+                query_error.min(batching_error),
+                grinding_cap,
+            );
 
-            protocol_builder = protocol_builder
-                .verifier_message(VerifierMessage::new(
+            protocol_builder = protocol_builder.verifier_message(
+                VerifierMessage::new(
                     vec![
                         RbRError::new("query_error", query_error),
                         RbRError::new("batching_error", batching_error),
                     ],
                     query_pow_bits,
-                ))
-                .prover_message(ProverMessage::new(ProofElement::MerkleQueries(
-                    MerkleQueries {
+                )
+                // The query indices and the combination randomness
+                .with_challenge_bits(num_queries * current_merkle_tree.tree_depth + challenge_bits),
+            );
+            for _ in 0..current_num_trees {
+                protocol_builder = protocol_builder.prover_message(ProverMessage::new(
+                    ProofElement::MerkleQueries(MerkleQueries {
                         merkle_tree: current_merkle_tree,
                         num_openings: num_queries,
-                    },
-                )))
-                .end_round();
+                        shared_leaves: 1,
+                    }),
+                ));
+            }
+            protocol_builder = protocol_builder.end_round();
 
             protocol_builder = protocol_builder.start_round(if i != num_rounds - 1 {
                 "whir_iteration"
@@ -318,40 +677,38 @@ impl WhirProtocol {
             let mut pow_bits_vec = Vec::with_capacity(folding_factor);
             for _ in 0..folding_factor {
                 // we now start, the initial folding pow bits
-                let prox_gaps_error = whir_parameters.security_assumption.prox_gaps_error(
+                let prox_gaps_error = round_security_assumption.prox_gaps_error(
                     current_log_degree - 1,
                     next_rate,
                     ldt_parameters.field.extension_bit_size(),
                     2,
                 );
 
-                let sumcheck_error = whir_parameters
-                    .security_assumption
-                    .constraint_folding_error(
-                        current_log_degree,
-                        next_rate,
-                        ldt_parameters.field.extension_bit_size(),
-                        ldt_parameters.constraint_degree.max(2),
-                    );
+                let sumcheck_error = round_security_assumption.constraint_folding_error(
+                    current_log_degree,
+                    next_rate,
+                    ldt_parameters.field.extension_bit_size(),
+                    sumcheck_degree,
+                );
 
                 let starting_folding_pow_bits =
-                    pow_util(security_level, prox_gaps_error.min(sumcheck_error));
+                    folding_pow_util(prox_gaps_error.min(sumcheck_error));
 
                 protocol_builder = protocol_builder
                     .prover_message(ProverMessage::new(ProofElement::FieldElements(
-                        FieldElements {
-                            field: ldt_parameters.field,
-                            num_elements: ldt_parameters.constraint_degree + 1,
-                            is_extension: true,
-                        },
+                        FieldElements::extension(ldt_parameters.field, sumcheck_degree + 1)
+                            .with_encoding(encoding),
                     )))
-                    .verifier_message(VerifierMessage::new(
-                        vec![
-                            RbRError::new("folding_error", prox_gaps_error),
-                            RbRError::new("sumcheck_error", sumcheck_error),
-                        ],
-                        starting_folding_pow_bits,
-                    ));
+                    .verifier_message(
+                        VerifierMessage::new(
+                            vec![
+                                RbRError::new("folding_error", prox_gaps_error),
+                                RbRError::new("sumcheck_error", sumcheck_error),
+                            ],
+                            starting_folding_pow_bits,
+                        )
+                        .with_challenge_bits(challenge_bits),
+                    );
 
                 pow_bits_vec.push(starting_folding_pow_bits);
                 current_log_degree -= 1;
@@ -359,6 +716,7 @@ impl WhirProtocol {
             let round_config = RoundConfig {
                 evaluation_domain_log_size: new_evaluation_domain_size,
                 folding_factor,
+                security_assumption: round_security_assumption.assumption,
                 num_queries,
                 query_pow_bits,
                 folding_pow_bits: pow_bits_vec,
@@ -368,51 +726,85 @@ impl WhirProtocol {
             round_parameters.push(round_config);
 
             current_merkle_tree = next_merkle_tree;
+            current_num_trees = 1;
             log_inv_rate = next_rate;
         }
-        protocol_builder = protocol_builder.end_round();
+        if has_starting_round {
+            protocol_builder = protocol_builder.end_round();
+        }
 
         // Compute the number of queries required
-        let final_queries = whir_parameters
-            .security_assumption
-            .queries(protocol_security_level, log_inv_rate);
+        let final_queries = security_assumption.queries(protocol_security_level, log_inv_rate)?;
 
-        // We need to compute the errors, to compute the according PoW
-        let query_error = whir_parameters
-            .security_assumption
-            .queries_error(log_inv_rate, final_queries);
+        // We need to compute the errors, to compute the according PoW.
+        let query_error = security_assumption.queries_error(log_inv_rate, final_queries)?;
 
         // Now compute the PoW
-        let final_pow_bits = pow_util(security_level, query_error);
+        let final_pow_bits = pow_util(pow_security_level, query_error, grinding_cap);
 
         // Add the final round message
         protocol_builder = protocol_builder
             .start_round("final_round")
-            .verifier_message(VerifierMessage::new(
-                vec![RbRError::new("query_error", query_error)],
-                final_pow_bits,
-            ))
-            .prover_message(ProverMessage::new(ProofElement::FieldElements(
-                FieldElements {
-                    field: ldt_parameters.field,
-                    num_elements: 1 << final_log_degree,
-                    is_extension: true,
-                },
-            )))
-            .prover_message(ProverMessage::new(ProofElement::MerkleQueries(
-                MerkleQueries {
+            .verifier_message(
+                VerifierMessage::new(
+                    vec![RbRError::new("query_error", query_error)],
+                    final_pow_bits,
+                )
+                .with_challenge_bits(final_queries * current_merkle_tree.tree_depth),
+            );
+        // The final sumcheck has the same degree, and hence error, as the folding sumchecks
+        protocol_builder = protocol_builder.final_phase(
+            final_phase,
+            ldt_parameters.field,
+            encoding,
+            final_log_degree,
+            sumcheck_degree,
+            |log_degree| {
+                let sumcheck_error = security_assumption.constraint_folding_error(
+                    log_degree,
+                    log_inv_rate,
+                    ldt_parameters.field.extension_bit_size(),
+                    sumcheck_degree,
+                );
+                (sumcheck_error, folding_pow_util(sumcheck_error))
+            },
+        );
+        for _ in 0..current_num_trees {
+            protocol_builder = protocol_builder.prover_message(ProverMessage::new(
+                ProofElement::MerkleQueries(MerkleQueries {
                     merkle_tree: current_merkle_tree,
                     num_openings: final_queries,
-                },
-            )))
-            .end_round();
+                    shared_leaves: 1,
+                }),
+            ));
+        }
+        protocol_builder = protocol_builder.end_round();
+
+        let protocol = protocol_builder.build();
+        // Unless some grind is capped or forbidden, every round reaches the security level
+        let security_shortfall = 0f64.max(security_level as f64 - protocol.rbr_error());
 
-        WhirProtocol {
+        Ok(WhirProtocol {
             config: WhirConfig {
                 ldt_parameters,
                 security_assumption: whir_parameters.security_assumption,
                 security_level,
-                max_pow_bits: whir_parameters.pow_bits,
+                max_pow_bits,
+                no_grinding: whir_parameters.no_grinding,
+                max_folding_pow_bits: whir_parameters.folding_pow_bits,
+                grinding_cap: whir_parameters.grinding_cap,
+                security_shortfall,
+                eta_factor,
+                max_list_size_bits: whir_parameters.max_list_size_bits,
+                digest_size_bits: whir_parameters.digest_size_bits,
+                digest: whir_parameters.digest,
+                zero_knowledge: whir_parameters.zero_knowledge,
+                base_field_statement: whir_parameters.base_field_statement,
+                min_final_log_degree: whir_parameters.min_final_log_degree,
+                ood_strategy,
+                encoding,
+                interleaving,
+                final_phase,
                 batching_pow_bits,
                 starting_folding_factor,
                 starting_domain_log_size,
@@ -424,86 +816,554 @@ impl WhirProtocol {
                 final_poly_log_degree: final_log_degree,
                 final_log_inv_rate: log_inv_rate,
             },
-            protocol: protocol_builder.build(),
+            protocol,
+        })
+    }
+}
+
+/// The degree of the sumcheck polynomials, which are at least quadratic (as the weights multiply the folded polynomial).
+fn sumcheck_degree(ldt_parameters: &LowDegreeParameters) -> usize {
+    ldt_parameters.constraint_degree.max(2)
+}
+
+/// Searches over the starting and per-round folding factors (each between 1 and `max_factor`) of a WHIR
+/// configuration with as many rounds as `base`, and returns the parameters yielding the smallest proof
+/// together with its size in bits.
+/// The rates of each candidate follow the schedule of `fixed_domain_shift`, i.e. the evaluation domain halves in each round.
+/// Candidates that cannot be configured or do not achieve `security_level` are skipped.
+pub fn optimize_folding(
+    ldt_parameters: LowDegreeParameters,
+    base: WhirParameters,
+    max_factor: usize,
+) -> Option<(WhirParameters, usize)> {
+    let num_rounds = base.folding_factors.len();
+    let mut best: Option<(WhirParameters, usize)> = None;
+
+    // The starting folding factor followed by the folding factors of each round
+    let mut factors = vec![1; num_rounds + 1];
+    loop {
+        let total_reduction: usize = factors.iter().sum();
+        if total_reduction <= ldt_parameters.log_degree {
+            let mut log_inv_rate = base.starting_log_inv_rate;
+            let log_inv_rates = factors[..num_rounds]
+                .iter()
+                .map(|&folding_factor| {
+                    log_inv_rate += folding_factor - 1;
+                    log_inv_rate
+                })
+                .collect();
+
+            let candidate = WhirParameters {
+                starting_folding_factor: factors[0],
+                folding_factors: factors[1..].to_vec(),
+                log_inv_rates,
+                ..base.clone()
+            };
+
+            if let Ok(whir_protocol) = WhirProtocol::new(ldt_parameters, candidate.clone()) {
+                let proof_size = whir_protocol.protocol.proof_size_bits();
+                let secure = whir_protocol.protocol.rbr_error() >= candidate.security_level as f64;
+                if secure
+                    && best
+                        .as_ref()
+                        .is_none_or(|(_, best_size)| proof_size < *best_size)
+                {
+                    best = Some((candidate, proof_size));
+                }
+            }
+        }
+
+        // Move on to the next assignment of folding factors
+        match factors.iter().position(|&factor| factor < max_factor) {
+            Some(i) => {
+                factors[i] += 1;
+                factors[..i].fill(1);
+            }
+            None => break,
         }
     }
+
+    best
+}
+
+/// Binary searches for the smallest `security_level` such that WHIR, configured as `base` but at that security level,
+/// achieves `target_compiled` bits of security against an adversary making 2^`log_ro_queries` RO queries
+/// (see `Protocol::compiled_classical_security`).
+/// Returns `None` if the target is not reached even at security level `2 * (target_compiled + log_ro_queries)`.
+pub fn min_security_level(
+    ldt_parameters: LowDegreeParameters,
+    base: WhirParameters,
+    target_compiled: f64,
+    log_ro_queries: usize,
+) -> Option<usize> {
+    let achieves_target = |security_level| {
+        let parameters = WhirParameters {
+            security_level,
+            ..base.clone()
+        };
+        WhirProtocol::new(ldt_parameters, parameters).is_ok_and(|whir_protocol| {
+            whir_protocol
+                .protocol
+                .compiled_classical_security(log_ro_queries)
+                >= target_compiled
+        })
+    };
+
+    // Invariant: lo does not achieve the target, while hi does
+    let mut lo = 0;
+    let mut hi = 2 * (target_compiled.ceil() as usize + log_ro_queries);
+    if !achieves_target(hi) {
+        return None;
+    }
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+        if achieves_target(mid) {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+
+    Some(hi)
+}
+
+/// Computes the signed change in proof size (in bits) from adding one more round to `base`.
+/// The extra round repeats the last folding factor (and security assumption, if given per round) and its rate follows
+/// the schedule of `fixed_domain_shift`, i.e. the evaluation domain halves.
+/// Adding a round shrinks the final polynomial but adds a commitment and its queries, so either sign is possible.
+/// Fails if either configuration cannot be built (e.g. if the extra round folds below the final degree).
+pub fn marginal_round_cost(
+    ldt_parameters: LowDegreeParameters,
+    base: WhirParameters,
+) -> Result<i64, WhirError> {
+    let mut extended = base.clone();
+    let folding_factor = *base.folding_factors.last().ok_or(WhirError::NoRounds)?;
+    let log_inv_rate = base
+        .log_inv_rates
+        .last()
+        .map_or(base.starting_log_inv_rate, |&log_inv_rate| log_inv_rate);
+    extended.folding_factors.push(folding_factor);
+    extended
+        .log_inv_rates
+        .push(log_inv_rate + folding_factor - 1);
+    if let Some(&security_assumption) = base.security_assumptions.last() {
+        extended.security_assumptions.push(security_assumption);
+    }
+
+    let base_size = WhirProtocol::new(ldt_parameters, base)?
+        .protocol
+        .proof_size_bits();
+    let extended_size = WhirProtocol::new(ldt_parameters, extended)?
+        .protocol
+        .proof_size_bits();
+    Ok(extended_size as i64 - base_size as i64)
+}
+
+/// Configures WHIR for each of the given degrees, returning `(log_degree, proof_size_bits)` pairs.
+/// Each configuration folds by the starting folding factor of `base` in every round, with as many rounds as needed
+/// to reach (about) the final degree of `base` over `base_ldt`. The rates follow the schedule of `fixed_domain_shift`.
+/// Degrees too small to fold at least once after the starting fold are skipped.
+pub fn size_scaling(
+    base_ldt: LowDegreeParameters,
+    base: WhirParameters,
+    log_degrees: Range<usize>,
+) -> Vec<(usize, usize)> {
+    let folding_factor = base.starting_folding_factor;
+    let total_reduction = folding_factor + base.folding_factors.iter().sum::<usize>();
+    let final_log_degree = base_ldt.log_degree.saturating_sub(total_reduction);
+
+    log_degrees
+        .filter_map(|log_degree| {
+            // The starting fold followed by the folding rounds
+            let num_folds = log_degree
+                .checked_sub(final_log_degree)?
+                .checked_div(folding_factor)?;
+            let num_rounds = num_folds.checked_sub(1).filter(|&n| n > 0)?;
+
+            let mut log_inv_rate = base.starting_log_inv_rate;
+            let parameters = WhirParameters {
+                folding_factors: vec![folding_factor; num_rounds],
+                log_inv_rates: (0..num_rounds)
+                    .map(|_| {
+                        log_inv_rate += folding_factor - 1;
+                        log_inv_rate
+                    })
+                    .collect(),
+                security_assumptions: vec![],
+                ..base.clone()
+            };
+            let ldt_parameters = LowDegreeParameters {
+                log_degree,
+                ..base_ldt
+            };
+
+            let whir_protocol = WhirProtocol::new(ldt_parameters, parameters).ok()?;
+            Some((log_degree, whir_protocol.protocol.proof_size_bits()))
+        })
+        .collect()
+}
+
+impl WhirProtocol {
+    /// Serializes the configuration and the round-by-round proof sizes to JSON.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::json!({
+            "config": self.config,
+            "protocol": self.protocol.to_json_value(),
+        })
+        .to_string()
+    }
+
+    /// Rebuilds the protocol at a different security level, keeping the folding, rate and field parameters.
+    /// As the query counts, OOD samples and pow bits scale with the security level, so does the proof size.
+    /// Digests sized for the old security level are resized for the new one.
+    pub fn resecure(&self, new_security_level: usize) -> Result<WhirProtocol, WhirError> {
+        let mut whir_parameters = self.config.to_parameters();
+        whir_parameters.security_level = new_security_level;
+
+        WhirProtocol::new(self.config.ldt_parameters, whir_parameters)
+    }
+}
+
+impl LowDegreeTest for WhirProtocol {
+    fn protocol(&self) -> &Protocol {
+        &self.protocol
+    }
+}
+
+impl Report for WhirProtocol {
+    fn report(&self) -> ProtocolReport {
+        ProtocolReport::new(
+            &self.protocol,
+            self.config
+                .round_parameters
+                .iter()
+                .map(|round| round.num_queries)
+                .sum::<usize>()
+                + self.config.final_queries,
+            self.config.round_parameters.len(),
+            self.config.final_poly_log_degree,
+        )
+    }
 }
 
 impl Display for WhirProtocol {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.config.fmt(f)?;
-        self.protocol.fmt(f)
+        self.protocol.fmt(f)?;
+        print_final_poly_warning(
+            f,
+            self.config.final_poly_size_bits(),
+            self.protocol.proof_size_bits(),
+        )
     }
 }
 
 /// A fully expanded WHIR configuration.
-#[derive(Debug, Clone)]
+///
+/// ```
+/// use stir_whir_estimation::{
+///     errors::SecurityAssumption,
+///     field::GOLDILOCKS_2,
+///     whir::{WhirParameters, WhirProtocol},
+///     LowDegreeParameters,
+/// };
+///
+/// let ldt_parameters = LowDegreeParameters {
+///     field: GOLDILOCKS_2,
+///     log_degree: 20,
+///     batch_size: 1,
+///     constraint_degree: 2,
+///     num_constraints: 1,
+/// };
+/// let whir_parameters =
+///     WhirParameters::fixed_domain_shift(1, 4, 3, SecurityAssumption::CapacityBound, 100, 20, 256);
+/// let config = WhirProtocol::new(ldt_parameters, whir_parameters).unwrap().config;
+///
+/// assert_eq!(config.starting_log_inv_rate, 1);
+/// assert_eq!(config.round_parameters.len(), 3);
+/// assert!(config.final_queries > 0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct WhirConfig {
     /// The configuration for the LDT desired.
-    pub(crate) ldt_parameters: LowDegreeParameters,
+    pub ldt_parameters: LowDegreeParameters,
 
     /// The security assumption under which WHIR was configured.
-    pub(crate) security_assumption: SecurityAssumption,
+    pub security_assumption: SecurityAssumption,
 
     /// The desired security level.
-    pub(crate) security_level: usize,
+    pub security_level: usize,
 
     /// The maximum number of pow bits allowed (over and we throw a warning, as probably then we are misconfigured.)
-    pub(crate) max_pow_bits: usize,
+    pub max_pow_bits: usize,
+
+    /// Whether grinding was forbidden.
+    pub no_grinding: bool,
+
+    /// The maximum number of pow bits used to reduce proximity gaps errors, if capped.
+    pub max_folding_pow_bits: Option<usize>,
+
+    /// The cap on the pow bits of any grind, if given.
+    pub grinding_cap: Option<usize>,
+
+    /// How many bits the round-by-round soundness falls short of the security level, i.e. `security_level - rbr_error`
+    /// (if positive). This includes any shortfall due to `grinding_cap`, `max_folding_pow_bits` or `no_grinding`.
+    pub security_shortfall: f64,
+
+    /// The divisor used to set η.
+    pub eta_factor: f64,
+
+    /// The cap on the (log) list size, if any.
+    pub max_list_size_bits: Option<f64>,
+
+    /// The size of the digest for the Merkle trees, unless sized for the security level.
+    pub digest_size_bits: Option<usize>,
+
+    /// The hash used for the Merkle digests, if given.
+    pub digest: Option<DigestConfig>,
+
+    /// Whether the commitments are zero-knowledge.
+    pub zero_knowledge: bool,
+
+    /// Whether the statement was over the base field.
+    pub base_field_statement: bool,
+
+    /// The smallest (log) degree the final polynomial was allowed to have.
+    pub min_final_log_degree: usize,
+
+    /// How the out-of-domain samples were drawn.
+    pub ood_strategy: OodStrategy,
+
+    /// How field elements are serialized in the proof.
+    pub encoding: Encoding,
+
+    /// How the batched polynomials were committed to in the starting commitment.
+    pub interleaving: InterleavingMode,
+
+    /// How the final polynomial was checked.
+    pub final_phase: FinalPhase,
 
     /// The rate of the inital RS code used during the protocol.    
-    pub(crate) starting_log_inv_rate: usize,
+    pub starting_log_inv_rate: usize,
 
     /// The pow bits used in the batching phase.
     pub batching_pow_bits: f64,
 
     /// The initial folding factor.
-    pub(crate) starting_folding_factor: usize,
+    pub starting_folding_factor: usize,
 
     /// The initial domain size
-    pub(crate) starting_domain_log_size: usize,
+    pub starting_domain_log_size: usize,
 
     /// The initial pow bits used in the first fold.
-    pub(crate) starting_folding_pow_bits: Vec<f64>,
+    pub starting_folding_pow_bits: Vec<f64>,
 
     /// The round-specific parameters.
-    pub(crate) round_parameters: Vec<RoundConfig>,
+    pub round_parameters: Vec<RoundConfig>,
 
     /// Degree of the final polynomial sent over.
-    pub(crate) final_poly_log_degree: usize,
+    pub final_poly_log_degree: usize,
 
     /// Number of queries in the last round
-    pub(crate) final_queries: usize,
+    pub final_queries: usize,
 
     /// Number of final bits of proof of work (for the queries).
-    pub(crate) final_query_pow_bits: f64,
+    pub final_query_pow_bits: f64,
 
     /// Rate of the final RS codeword.
-    pub(crate) final_log_inv_rate: usize,
+    pub final_log_inv_rate: usize,
 }
 
 /// Round specific configuration
-#[derive(Debug, Clone)]
-pub(crate) struct RoundConfig {
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RoundConfig {
     /// Folding factor for this round.
-    pub(crate) folding_factor: usize,
+    pub folding_factor: usize,
+    /// The security assumption used in this round.
+    pub security_assumption: SecurityAssumption,
     /// Size of evaluation domain (of oracle sent in this round)
-    pub(crate) evaluation_domain_log_size: usize,
+    pub evaluation_domain_log_size: usize,
     /// Number of bits of proof of work (for the foldings).
-    pub(crate) folding_pow_bits: Vec<f64>,
+    pub folding_pow_bits: Vec<f64>,
     /// Number of queries in this round
-    pub(crate) num_queries: usize,
+    pub num_queries: usize,
     /// Number of bits of proof of work (for the queries).
-    pub(crate) query_pow_bits: f64,
+    pub query_pow_bits: f64,
     /// Number of OOD samples in this round
-    pub(crate) ood_samples: usize,
+    pub ood_samples: usize,
     /// Rate of current RS codeword
-    pub(crate) log_inv_rate: usize,
+    pub log_inv_rate: usize,
 }
 
 impl WhirConfig {
+    /// The rate of each oracle, i.e. the starting rate followed by the rate of the oracle committed in each round.
+    pub fn rate_schedule(&self) -> Vec<usize> {
+        // Each round records the rate of the oracle it queries, which was committed in the previous round
+        std::iter::once(self.starting_log_inv_rate)
+            .chain(
+                self.round_parameters
+                    .iter()
+                    .skip(1)
+                    .map(|round| round.log_inv_rate),
+            )
+            .chain(std::iter::once(self.final_log_inv_rate))
+            .collect()
+    }
+
+    /// The (log) degree of the polynomial encoded by each oracle, in the same order as `rate_schedule`.
+    pub fn degree_schedule(&self) -> Vec<usize> {
+        let mut current_log_degree = self.ldt_parameters.log_degree - self.starting_folding_factor;
+        let mut degrees = vec![self.ldt_parameters.log_degree, current_log_degree];
+        for round in &self.round_parameters[..self.round_parameters.len() - 1] {
+            current_log_degree -= round.folding_factor;
+            degrees.push(current_log_degree);
+        }
+        degrees
+    }
+
+    /// Reconfigures the protocol over a different field, keeping the folding, rate and security parameters.
+    /// As the errors depend on the field size, the OOD samples and pow bits may change.
+    pub fn with_field(&self, new_field: Field) -> Result<WhirProtocol, WhirError> {
+        let ldt_parameters = LowDegreeParameters {
+            field: new_field,
+            ..self.ldt_parameters
+        };
+
+        WhirProtocol::new(ldt_parameters, self.to_parameters())
+    }
+
+    /// Reconstructs the parameters this configuration was built from, such that `WhirProtocol::new` with these
+    /// parameters (and the same LDT parameters) yields this configuration again.
+    /// NOTE: The digest size is the one used, i.e. after capping it for the security level.
+    pub fn to_parameters(&self) -> WhirParameters {
+        let log_inv_rates = self.rate_schedule()[1..].to_vec();
+
+        WhirParameters {
+            starting_log_inv_rate: self.starting_log_inv_rate,
+            starting_folding_factor: self.starting_folding_factor,
+            folding_factors: self
+                .round_parameters
+                .iter()
+                .map(|round| round.folding_factor)
+                .collect(),
+            log_inv_rates,
+            security_assumption: self.security_assumption,
+            security_assumptions: self
+                .round_parameters
+                .iter()
+                .map(|round| round.security_assumption)
+                .collect(),
+            eta_factor: Some(self.eta_factor),
+            max_list_size_bits: self.max_list_size_bits,
+            security_level: self.security_level,
+            pow_bits: self.max_pow_bits,
+            folding_pow_bits: self.max_folding_pow_bits,
+            grinding_cap: self.grinding_cap,
+            digest_size_bits: self.digest_size_bits,
+            digest: self.digest,
+            no_grinding: self.no_grinding,
+            zero_knowledge: self.zero_knowledge,
+            base_field_statement: self.base_field_statement,
+            encoding: self.encoding,
+            interleaving: self.interleaving,
+            final_phase: self.final_phase,
+            min_final_log_degree: self.min_final_log_degree,
+            ood_strategy: self.ood_strategy,
+        }
+    }
+
+    /// The total size in bits of the oracles committed to by the prover, see `protocol::total_oracle_bits`.
+    pub fn total_oracle_bits(&self) -> usize {
+        // The domains are stored after folding, each point of which holds 2^folding_factor evaluations
+        total_oracle_bits(
+            self.ldt_parameters,
+            std::iter::once(self.starting_domain_log_size + self.starting_folding_factor).chain(
+                self.round_parameters
+                    .iter()
+                    .map(|r| r.evaluation_domain_log_size + r.folding_factor),
+            ),
+        )
+    }
+
+    /// A proxy for the prover time, as the FFT work to encode each committed oracle, i.e. the sum of 2^d * d
+    /// over the (log) sizes d of the evaluation domains (before folding, and with one FFT per batched polynomial
+    /// for the starting oracle).
+    pub fn fft_cost_proxy(&self) -> f64 {
+        let fft_cost =
+            |log_domain_size: usize| 2_f64.powi(log_domain_size as i32) * log_domain_size as f64;
+        let starting_cost = self.ldt_parameters.batch_size as f64
+            * fft_cost(self.starting_domain_log_size + self.starting_folding_factor);
+        let round_cost: f64 = self
+            .round_parameters
+            .iter()
+            .map(|r| fft_cost(r.evaluation_domain_log_size + r.folding_factor))
+            .sum();
+        starting_cost + round_cost
+    }
+
+    /// The size in bits spent on the final polynomial, i.e. its coefficients when sent in the clear,
+    /// or the sumcheck polynomials and the final evaluation when checked with a sumcheck.
+    pub fn final_poly_size_bits(&self) -> usize {
+        let num_elements = self.final_phase.num_elements(
+            self.final_poly_log_degree,
+            sumcheck_degree(&self.ldt_parameters),
+        );
+        FieldElements::extension(self.ldt_parameters.field, num_elements)
+            .with_encoding(self.encoding)
+            .size_bits()
+    }
+
+    /// Returns the (labeled) PoW values exceeding `max_pow_bits`, which likely signal a misconfiguration.
+    pub fn pow_bits_exceeded(&self) -> Vec<(String, f64)> {
+        let mut pow_bits = vec![("batching".to_string(), self.batching_pow_bits)];
+        for (j, &bits) in self.starting_folding_pow_bits.iter().enumerate() {
+            pow_bits.push((format!("starting folding {j}"), bits));
+        }
+        for (i, r) in self.round_parameters.iter().enumerate() {
+            for (j, &bits) in r.folding_pow_bits.iter().enumerate() {
+                pow_bits.push((format!("round {i} folding {j}"), bits));
+            }
+            pow_bits.push((format!("round {i} queries"), r.query_pow_bits));
+        }
+        pow_bits.push(("final".to_string(), self.final_query_pow_bits));
+
+        pow_bits.retain(|&(_, bits)| bits > self.max_pow_bits as f64);
+        pow_bits
+    }
+
+    /// Exports the round-specific parameters as CSV, with one row per round followed by the final round.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        writeln!(csv, "{ROUNDS_CSV_HEADER}").unwrap();
+        for (i, r) in self.round_parameters.iter().enumerate() {
+            writeln!(
+                csv,
+                "{},{},{},{},{},{},{:.1}",
+                i,
+                r.folding_factor,
+                r.evaluation_domain_log_size,
+                r.log_inv_rate,
+                r.num_queries,
+                r.ood_samples,
+                r.folding_pow_bits.iter().sum::<f64>() + r.query_pow_bits
+            )
+            .unwrap();
+        }
+        writeln!(
+            csv,
+            "final,,,{},{},0,{:.1}",
+            self.final_log_inv_rate, self.final_queries, self.final_query_pow_bits
+        )
+        .unwrap();
+        csv
+    }
+
     /// Prints a summary of the configuration for WHIR.
     pub fn print_config_summary(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let precision = float_precision(f);
         writeln!(f, "{}", self.ldt_parameters)?;
         writeln!(
             f,
@@ -520,7 +1380,7 @@ impl WhirConfig {
         if self.ldt_parameters.batch_size > 1 {
             writeln!(
                 f,
-                "Batch size: {}, batching_pow_bits: {:.1}",
+                "Batch size: {}, batching_pow_bits: {:.precision$}",
                 self.ldt_parameters.batch_size, self.batching_pow_bits
             )?;
         }
@@ -530,7 +1390,7 @@ impl WhirConfig {
             "Initial folding factor: {}, initial_folding_pow_bits: ",
             self.starting_folding_factor,
         )?;
-        pretty_print_float_slice(f, &self.starting_folding_pow_bits)?;
+        pretty_print_float_slice(f, &self.starting_folding_pow_bits, precision)?;
 
         for r in &self.round_parameters {
             r.fmt(f)?;
@@ -538,13 +1398,15 @@ impl WhirConfig {
 
         writeln!(
             f,
-            "final_queries: {}, final polynomial: {}, final_rate: 2^-{}, final_pow_bits: {:.1}",
+            "final_queries: {}, final polynomial: {}, final_rate: 2^-{}, final_pow_bits: {:.precision$}",
             self.final_queries,
             self.final_poly_log_degree,
             self.final_log_inv_rate,
             self.final_query_pow_bits,
         )?;
 
+        print_pow_warning(f, self.max_pow_bits, &self.pow_bits_exceeded())?;
+
         Ok(())
     }
 }
@@ -557,11 +1419,1176 @@ impl Display for WhirConfig {
 
 impl Display for RoundConfig {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let precision = float_precision(f);
         write!(
             f,
-            "Folding factor: {}, domain_size: 2^{}, num_queries: {}, query_pow: {:.1}, rate: 2^-{}, ood_samples: {}, pow_bits: ",
+            "Folding factor: {}, domain_size: 2^{}, num_queries: {}, query_pow: {:.precision$}, rate: 2^-{}, ood_samples: {}, pow_bits: ",
             self.folding_factor, self.evaluation_domain_log_size, self.num_queries, self.query_pow_bits, self.log_inv_rate, self.ood_samples,
         )?;
-        pretty_print_float_slice(f, &self.folding_pow_bits)
+        pretty_print_float_slice(f, &self.folding_pow_bits, precision)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        marginal_round_cost, min_security_level, optimize_folding, size_scaling, WhirParameters,
+        WhirProtocol,
+    };
+    use crate::{
+        errors::{OodStrategy, SecurityAssumption, WhirError},
+        field::{BABYBEAR_4, BABYBEAR_5, GOLDILOCKS_2, GOLDILOCKS_3},
+        hash::DigestConfig,
+        protocol::{
+            proof_size::{Encoding, FieldElements, FinalPhase, InterleavingMode, ProofElement},
+            SecurityBound,
+        },
+        LowDegreeParameters,
+    };
+
+    fn ldt_parameters() -> LowDegreeParameters {
+        LowDegreeParameters {
+            field: GOLDILOCKS_2,
+            log_degree: 20,
+            batch_size: 1,
+            constraint_degree: 2,
+            num_constraints: 1,
+        }
+    }
+
+    fn whir_parameters() -> WhirParameters {
+        WhirParameters::fixed_domain_shift(1, 4, 3, SecurityAssumption::CapacityBound, 100, 20, 256)
+    }
+
+    #[test]
+    fn test_valid_parameters() {
+        assert!(WhirProtocol::new(ldt_parameters(), whir_parameters()).is_ok());
+    }
+
+    #[test]
+    fn test_zero_folding_factor() {
+        let mut parameters = whir_parameters();
+        parameters.folding_factors[1] = 0;
+        assert_eq!(
+            WhirProtocol::new(ldt_parameters(), parameters).unwrap_err(),
+            WhirError::ZeroFoldingFactor
+        );
+    }
+
+    #[test]
+    fn test_min_final_log_degree() {
+        // The default parameters fold 2^20 down to 2^4
+        let mut parameters = whir_parameters();
+        parameters.min_final_log_degree = 4;
+        assert!(WhirProtocol::new(ldt_parameters(), parameters.clone()).is_ok());
+
+        parameters.min_final_log_degree = 5;
+        assert_eq!(
+            WhirProtocol::new(ldt_parameters(), parameters).unwrap_err(),
+            WhirError::FinalDegreeTooSmall {
+                final_log_degree: 4,
+                min_final_log_degree: 5
+            }
+        );
+    }
+
+    #[test]
+    fn test_size_scaling() {
+        let scaling = size_scaling(ldt_parameters(), whir_parameters(), 0..31);
+
+        // The default parameters end at degree 2^4, so at least two folds of 4 are needed
+        assert_eq!(scaling.first().unwrap().0, 12);
+        assert_eq!(scaling.last().unwrap().0, 30);
+        for pair in scaling.windows(2) {
+            assert!(pair[1].1 >= pair[0].1);
+        }
+
+        // The proof size grows polylogarithmically: going from 2^12 to 2^30 is far less than (30/12)^3 larger
+        let (first, last) = (scaling[0].1 as f64, scaling.last().unwrap().1 as f64);
+        assert!(last / first < (30_f64 / 12.).powi(3));
+    }
+
+    #[test]
+    fn test_initial_claim() {
+        let whir_protocol = WhirProtocol::new(ldt_parameters(), whir_parameters()).unwrap();
+        let proof = whir_protocol.protocol.to_proof();
+        let first_iteration = proof
+            .rounds
+            .iter()
+            .find(|round| round.name == "whir_iteration")
+            .unwrap();
+
+        // The claimed evaluation is a single extension field element
+        match first_iteration.elements[0] {
+            ProofElement::FieldElements(claim) => {
+                assert_eq!(claim, FieldElements::extension(GOLDILOCKS_2, 1));
+                assert_eq!(claim.size_bits(), 128);
+            }
+            _ => panic!("expected the claimed evaluation first"),
+        }
+    }
+
+    #[test]
+    fn test_folding_pow_bits() {
+        let uncapped = WhirProtocol::new(ldt_parameters(), whir_parameters()).unwrap();
+        let no_folding_pow = |ldt_parameters| {
+            let parameters = WhirParameters {
+                folding_pow_bits: Some(0),
+                ..whir_parameters()
+            };
+            WhirProtocol::new(ldt_parameters, parameters).unwrap()
+        };
+
+        let capped = no_folding_pow(ldt_parameters());
+        assert!(capped
+            .config
+            .starting_folding_pow_bits
+            .iter()
+            .chain(
+                capped
+                    .config
+                    .round_parameters
+                    .iter()
+                    .flat_map(|r| r.folding_pow_bits.iter())
+            )
+            .all(|&bits| bits == 0.));
+
+        // The queries are unaffected
+        assert_eq!(capped.config.final_queries, uncapped.config.final_queries);
+        for (capped_round, uncapped_round) in capped
+            .config
+            .round_parameters
+            .iter()
+            .zip(&uncapped.config.round_parameters)
+        {
+            assert_eq!(capped_round.num_queries, uncapped_round.num_queries);
+            assert_eq!(capped_round.query_pow_bits, uncapped_round.query_pow_bits);
+        }
+
+        // Without grinding, the proximity gaps errors require a larger field
+        assert!(uncapped.protocol.rbr_error() >= 100.);
+        assert!(capped.protocol.rbr_error() < 100.);
+        let larger_field = no_folding_pow(LowDegreeParameters {
+            field: GOLDILOCKS_3,
+            ..ldt_parameters()
+        });
+        assert!(larger_field.protocol.rbr_error() >= 100.);
+    }
+
+    #[test]
+    fn test_over_folded() {
+        let mut parameters = whir_parameters();
+        parameters.folding_factors = vec![6; 3];
+        assert_eq!(
+            WhirProtocol::new(ldt_parameters(), parameters).unwrap_err(),
+            WhirError::OverFolded {
+                total_reduction: 22,
+                log_degree: 20
+            }
+        );
+    }
+
+    #[test]
+    fn test_mismatched_round_vectors() {
+        let mut parameters = whir_parameters();
+        parameters.log_inv_rates.pop();
+        assert_eq!(
+            WhirProtocol::new(ldt_parameters(), parameters).unwrap_err(),
+            WhirError::MismatchedRoundVectors
+        );
+    }
+
+    #[test]
+    fn test_zero_rounds() {
+        let parameters = |starting_folding_factor| WhirParameters {
+            starting_folding_factor,
+            folding_factors: vec![],
+            log_inv_rates: vec![],
+            ..whir_parameters()
+        };
+
+        // Without any fold, the whole polynomial is sent and checked against the starting commitment
+        let whir_protocol = WhirProtocol::new(ldt_parameters(), parameters(0)).unwrap();
+        assert!(whir_protocol.config.round_parameters.is_empty());
+        assert_eq!(whir_protocol.config.final_poly_log_degree, 20);
+        assert_eq!(whir_protocol.protocol.num_classical_rounds(), 1);
+        let final_poly_size_bits = (1 << 20) * GOLDILOCKS_2.extension_bit_size();
+        assert!(10 * final_poly_size_bits > 9 * whir_protocol.protocol.proof_size_bits());
+        // Without claims, there is nothing to send before the final round
+        let ldt_test = LowDegreeParameters {
+            constraint_degree: 0,
+            ..ldt_parameters()
+        };
+        let whir_protocol = WhirProtocol::new(ldt_test, parameters(0)).unwrap();
+        assert_eq!(whir_protocol.protocol.rounds_iter().count(), 1);
+
+        // Otherwise, only the starting fold is done
+        let whir_protocol = WhirProtocol::new(ldt_parameters(), parameters(4)).unwrap();
+        assert_eq!(whir_protocol.config.final_poly_log_degree, 16);
+        assert_eq!(whir_protocol.protocol.num_classical_rounds(), 4 + 1);
+
+        // With rounds, the starting fold cannot be skipped
+        assert_eq!(
+            WhirProtocol::new(
+                ldt_parameters(),
+                WhirParameters {
+                    starting_folding_factor: 0,
+                    ..whir_parameters()
+                }
+            )
+            .unwrap_err(),
+            WhirError::ZeroFoldingFactor
+        );
+    }
+
+    #[test]
+    fn test_optimize_folding() {
+        let ldt_parameters = ldt_parameters();
+        let base = whir_parameters();
+        let baseline = WhirProtocol::new(ldt_parameters, base.clone())
+            .unwrap()
+            .protocol
+            .proof_size_bits();
+
+        let (optimized, proof_size) = optimize_folding(ldt_parameters, base, 5).unwrap();
+        let total_reduction =
+            optimized.starting_folding_factor + optimized.folding_factors.iter().sum::<usize>();
+
+        assert!(total_reduction <= ldt_parameters.log_degree);
+        assert!(proof_size < baseline);
+    }
+
+    #[test]
+    fn test_mixed_security_assumptions() {
+        let uniform = WhirProtocol::new(ldt_parameters(), whir_parameters()).unwrap();
+
+        let mut parameters = whir_parameters();
+        parameters.security_assumptions = vec![
+            SecurityAssumption::JohnsonBound,
+            SecurityAssumption::CapacityBound,
+            SecurityAssumption::CapacityBound,
+        ];
+        let mixed = WhirProtocol::new(ldt_parameters(), parameters).unwrap();
+
+        assert_ne!(
+            uniform.protocol.proof_size_bits(),
+            mixed.protocol.proof_size_bits()
+        );
+    }
+
+    #[test]
+    fn test_mismatched_security_assumptions() {
+        let mut parameters = whir_parameters();
+        parameters.security_assumptions = vec![SecurityAssumption::JohnsonBound];
+        assert_eq!(
+            WhirProtocol::new(ldt_parameters(), parameters).unwrap_err(),
+            WhirError::MismatchedRoundVectors
+        );
+    }
+
+    #[test]
+    fn test_committed_field_report() {
+        let whir_protocol = WhirProtocol::new(ldt_parameters(), whir_parameters()).unwrap();
+        let report = whir_protocol.protocol.committed_field_report();
+        assert_eq!(
+            report.len(),
+            whir_protocol.config.round_parameters.len() + 1
+        );
+
+        // Only the starting oracle is over the base field
+        assert!(!report[0].is_extension);
+        assert_eq!(report[0].element_size_bits, 64);
+        assert!(report[1..]
+            .iter()
+            .all(|oracle| oracle.is_extension && oracle.element_size_bits == 128));
+    }
+
+    #[test]
+    fn test_total_oracle_bits() {
+        let whir_protocol = WhirProtocol::new(ldt_parameters(), whir_parameters()).unwrap();
+        let total_oracle_bits = whir_protocol.config.total_oracle_bits();
+
+        // The starting oracle alone has 2^21 base field evaluations
+        assert!(total_oracle_bits > (1 << 21) * 64);
+        assert!(total_oracle_bits > whir_protocol.protocol.proof_size_bits());
+
+        let mut parameters = whir_parameters();
+        parameters.starting_log_inv_rate = 2;
+        let lower_rate = WhirProtocol::new(ldt_parameters(), parameters).unwrap();
+        assert!(lower_rate.config.total_oracle_bits() > total_oracle_bits);
+    }
+
+    #[test]
+    fn test_encoding() {
+        let ldt_parameters = LowDegreeParameters {
+            field: BABYBEAR_5,
+            ..ldt_parameters()
+        };
+        let proof_size_bits = |encoding| {
+            let whir_parameters = WhirParameters {
+                encoding,
+                ..whir_parameters()
+            };
+            WhirProtocol::new(ldt_parameters, whir_parameters)
+                .unwrap()
+                .protocol
+                .proof_size_bits()
+        };
+
+        // BabyBear elements are padded from 27 to 32 (resp. 64) bits
+        assert!(proof_size_bits(Encoding::ByteAligned) > proof_size_bits(Encoding::Bitpacked));
+        assert!(proof_size_bits(Encoding::WordAligned) > proof_size_bits(Encoding::ByteAligned));
+    }
+
+    #[test]
+    fn test_final_poly_warning() {
+        let whir_protocol = WhirProtocol::new(ldt_parameters(), whir_parameters()).unwrap();
+        assert_eq!(whir_protocol.config.final_poly_size_bits(), (1 << 4) * 128);
+        assert!(!whir_protocol.to_string().contains("Final polynomial is"));
+
+        // A single round of folding leaves a polynomial of degree 2^12 to send in the clear
+        let under_folded = WhirParameters::fixed_domain_shift(
+            1,
+            4,
+            1,
+            SecurityAssumption::CapacityBound,
+            100,
+            20,
+            256,
+        );
+        let whir_protocol = WhirProtocol::new(ldt_parameters(), under_folded).unwrap();
+        assert_eq!(whir_protocol.config.final_poly_log_degree, 12);
+        assert!(whir_protocol.to_string().contains("⚠ Final polynomial is"));
+    }
+
+    #[test]
+    fn test_pow_bits_exceeded() {
+        let mut whir_protocol = WhirProtocol::new(ldt_parameters(), whir_parameters()).unwrap();
+        assert!(whir_protocol.config.pow_bits_exceeded().is_empty());
+        assert!(!whir_protocol.config.to_string().contains("PoW exceeds max"));
+
+        // The queries are ground to 20 bits, so they all exceed a max of 10
+        whir_protocol.config.max_pow_bits = 10;
+        let exceeded = whir_protocol.config.pow_bits_exceeded();
+        assert!(exceeded.len() > whir_protocol.config.round_parameters.len());
+        assert!(exceeded.iter().any(|(location, _)| location == "final"));
+        assert!(exceeded.iter().all(|&(_, bits)| bits > 10.));
+        assert!(whir_protocol
+            .config
+            .to_string()
+            .contains("⚠ PoW exceeds max"));
+    }
+
+    #[test]
+    fn test_to_proof() {
+        let whir_protocol = WhirProtocol::new(ldt_parameters(), whir_parameters()).unwrap();
+        let proof = whir_protocol.protocol.to_proof();
+
+        assert_eq!(
+            proof.total_size_bits(),
+            whir_protocol.protocol.proof_size_bits()
+        );
+        assert!(proof
+            .rounds
+            .iter()
+            .enumerate()
+            .all(|(i, round)| round.round_number == i));
+    }
+
+    #[test]
+    fn test_zero_knowledge() {
+        let whir_protocol = WhirProtocol::new(ldt_parameters(), whir_parameters()).unwrap();
+        let zk_whir_protocol = WhirProtocol::new(
+            ldt_parameters(),
+            WhirParameters {
+                zero_knowledge: true,
+                ..whir_parameters()
+            },
+        )
+        .unwrap();
+
+        assert!(
+            zk_whir_protocol.protocol.proof_size_bits() > whir_protocol.protocol.proof_size_bits()
+        );
+    }
+
+    #[test]
+    fn test_ood_entries_per_assumption() {
+        for security_assumption in [
+            SecurityAssumption::UniqueDecoding,
+            SecurityAssumption::JohnsonBound,
+            SecurityAssumption::CapacityBound,
+        ] {
+            let whir_parameters =
+                WhirParameters::fixed_domain_shift(1, 4, 3, security_assumption, 100, 20, 256);
+            let whir_protocol = WhirProtocol::new(ldt_parameters(), whir_parameters).unwrap();
+
+            // Only list decoding needs OOD samples, one verifier message per round
+            let num_rounds = whir_protocol.config.round_parameters.len();
+            let expected_ood_entries = match security_assumption {
+                SecurityAssumption::UniqueDecoding => 0,
+                _ => num_rounds,
+            };
+            let breakdown = whir_protocol.protocol.rbr_breakdown();
+            let num_entries = |name: &str| {
+                breakdown
+                    .iter()
+                    .filter(|message| message.terms.iter().any(|(term, _)| term == name))
+                    .count()
+            };
+            assert_eq!(num_entries("ood_error"), expected_ood_entries);
+
+            // Otherwise one message per folded variable, and one per round of queries plus the final queries
+            let num_folds = whir_protocol.config.starting_folding_factor
+                + whir_protocol
+                    .config
+                    .round_parameters
+                    .iter()
+                    .map(|round| round.folding_factor)
+                    .sum::<usize>();
+            assert_eq!(num_entries("folding_error"), num_folds);
+            assert_eq!(num_entries("query_error"), num_rounds + 1);
+            assert_eq!(
+                breakdown.len(),
+                num_folds + expected_ood_entries + num_rounds + 1
+            );
+
+            // No phantom entries without error
+            assert!(breakdown
+                .iter()
+                .all(|message| message.terms.iter().all(|(_, error)| *error > 0.)));
+        }
+    }
+
+    #[test]
+    fn test_constraint_degree_sizing() {
+        let proof_sizes: Vec<_> = (1..=6)
+            .map(|constraint_degree| {
+                let ldt_parameters = LowDegreeParameters {
+                    constraint_degree,
+                    num_constraints: 1,
+                    ..ldt_parameters()
+                };
+                WhirProtocol::new(ldt_parameters, whir_parameters())
+                    .unwrap()
+                    .protocol
+                    .proof_size_bits()
+            })
+            .collect();
+
+        // Linear constraints are still proven with quadratic sumchecks
+        assert_eq!(proof_sizes[0], proof_sizes[1]);
+        // Each extra degree adds one coefficient to every sumcheck polynomial
+        let increments: Vec<_> = proof_sizes[1..].windows(2).map(|w| w[1] - w[0]).collect();
+        assert!(increments[0] > 0);
+        assert!(increments
+            .iter()
+            .all(|&increment| increment == increments[0]));
+    }
+
+    #[test]
+    fn test_min_security_level() {
+        let compiled_security = |security_level| {
+            let parameters = WhirParameters {
+                security_level,
+                ..whir_parameters()
+            };
+            WhirProtocol::new(ldt_parameters(), parameters)
+                .unwrap()
+                .protocol
+                .compiled_classical_security(32)
+        };
+
+        let security_level =
+            min_security_level(ldt_parameters(), whir_parameters(), 100., 32).unwrap();
+        assert!(compiled_security(security_level) >= 100.);
+        assert!(compiled_security(security_level - 1) < 100.);
+    }
+
+    #[test]
+    fn test_tapered() {
+        // Same total reduction of 16 as the uniform configuration
+        let tapered_parameters = WhirParameters::tapered(
+            20,
+            1,
+            7,
+            &[4, 3, 2],
+            SecurityAssumption::CapacityBound,
+            100,
+            20,
+            256,
+        )
+        .unwrap();
+        assert_eq!(tapered_parameters.log_inv_rates, vec![7, 10, 12]);
+
+        // A uniform taper follows the schedule of fixed_domain_shift
+        let uniform_parameters = WhirParameters::tapered(
+            20,
+            1,
+            4,
+            &[4, 4, 4],
+            SecurityAssumption::CapacityBound,
+            100,
+            20,
+            256,
+        )
+        .unwrap();
+        assert_eq!(
+            uniform_parameters.log_inv_rates,
+            whir_parameters().log_inv_rates
+        );
+
+        let tapered_protocol = WhirProtocol::new(ldt_parameters(), tapered_parameters).unwrap();
+        let uniform_protocol = WhirProtocol::new(ldt_parameters(), whir_parameters()).unwrap();
+        assert_ne!(
+            tapered_protocol.protocol.proof_size_bits(),
+            uniform_protocol.protocol.proof_size_bits()
+        );
+
+        assert_eq!(
+            WhirParameters::tapered(
+                20,
+                1,
+                10,
+                &[6, 6],
+                SecurityAssumption::CapacityBound,
+                100,
+                20,
+                256
+            )
+            .err(),
+            Some(WhirError::OverFolded {
+                total_reduction: 22,
+                log_degree: 20
+            })
+        );
+    }
+
+    #[test]
+    fn test_rbr_breakdown() {
+        let whir_protocol = WhirProtocol::new(ldt_parameters(), whir_parameters()).unwrap();
+
+        let breakdown = whir_protocol.protocol.rbr_breakdown();
+        let combined: Vec<_> = breakdown.iter().map(|round| round.combined).collect();
+        assert_eq!(combined, whir_protocol.protocol.rbr_errors());
+        assert!(breakdown
+            .iter()
+            .any(|round| round.terms.iter().any(|(name, _)| name == "ood_error")));
+    }
+
+    #[test]
+    fn test_with_field() {
+        let ldt_parameters = LowDegreeParameters {
+            field: GOLDILOCKS_3,
+            ..ldt_parameters()
+        };
+        let whir_protocol = WhirProtocol::new(ldt_parameters, whir_parameters()).unwrap();
+
+        // Over the same field, the protocol is unchanged
+        let same_field = whir_protocol.config.with_field(GOLDILOCKS_3).unwrap();
+        assert_eq!(
+            same_field.protocol.proof_size_bits(),
+            whir_protocol.protocol.proof_size_bits()
+        );
+
+        // Over the smaller BabyBear extension, more OOD samples are needed to rule out the list
+        let babybear = whir_protocol.config.with_field(BABYBEAR_4).unwrap();
+        assert_eq!(babybear.config.ldt_parameters.field, BABYBEAR_4);
+        let ood_samples = |whir_protocol: &WhirProtocol| {
+            whir_protocol
+                .config
+                .round_parameters
+                .iter()
+                .map(|round| round.ood_samples)
+                .collect::<Vec<_>>()
+        };
+        assert!(ood_samples(&babybear)
+            .iter()
+            .zip(ood_samples(&whir_protocol))
+            .all(|(&babybear, goldilocks)| babybear > goldilocks));
+    }
+
+    #[test]
+    fn test_resecure() {
+        let sized_digests = WhirParameters {
+            digest_size_bits: None,
+            ..whir_parameters()
+        };
+        let whir_protocol = WhirProtocol::new(ldt_parameters(), sized_digests).unwrap();
+
+        // The reconstructed parameters rebuild the same protocol
+        let rebuilt =
+            WhirProtocol::new(ldt_parameters(), whir_protocol.config.to_parameters()).unwrap();
+        assert_eq!(
+            rebuilt.protocol.proof_size_bits(),
+            whir_protocol.protocol.proof_size_bits()
+        );
+        assert_eq!(
+            whir_protocol
+                .resecure(100)
+                .unwrap()
+                .protocol
+                .proof_size_bits(),
+            whir_protocol.protocol.proof_size_bits()
+        );
+
+        let at_80 = whir_protocol.resecure(80).unwrap();
+        let at_128 = whir_protocol.resecure(128).unwrap();
+        assert_eq!(at_80.config.security_level, 80);
+        assert_eq!(at_128.config.security_level, 128);
+
+        // The digests sized for the security level follow it, while an explicit size is kept
+        let digest_size_bits = |whir_protocol: &WhirProtocol| {
+            whir_protocol.protocol.security_summary(0).digest_size_bits
+        };
+        assert_eq!(digest_size_bits(&at_80), 160);
+        assert_eq!(digest_size_bits(&at_128), 256);
+        let explicit = WhirProtocol::new(ldt_parameters(), whir_parameters()).unwrap();
+        assert_eq!(digest_size_bits(&explicit.resecure(80).unwrap()), 256);
+
+        // Same folding and rates, but more queries are needed at 128 bits
+        assert_eq!(at_80.config.rate_schedule(), at_128.config.rate_schedule());
+        assert!(at_80
+            .config
+            .round_parameters
+            .iter()
+            .zip(&at_128.config.round_parameters)
+            .all(|(at_80, at_128)| at_80.num_queries < at_128.num_queries));
+        assert!(at_80.config.final_queries < at_128.config.final_queries);
+        assert!(at_80.protocol.proof_size_bits() < whir_protocol.protocol.proof_size_bits());
+        assert!(whir_protocol.protocol.proof_size_bits() < at_128.protocol.proof_size_bits());
+    }
+
+    #[test]
+    fn test_fft_cost_proxy() {
+        let fft_cost_proxy = |log_inv_rate| {
+            WhirProtocol::new(
+                ldt_parameters(),
+                WhirParameters::fixed_domain_shift(
+                    log_inv_rate,
+                    4,
+                    3,
+                    SecurityAssumption::CapacityBound,
+                    100,
+                    20,
+                    256,
+                ),
+            )
+            .unwrap()
+            .config
+            .fft_cost_proxy()
+        };
+
+        // The starting domain has size 2^21, followed by domains of sizes 2^20, 2^19 and 2^18
+        let expected = [21., 20., 19., 18.]
+            .iter()
+            .map(|&d: &f64| d.exp2() * d)
+            .sum::<f64>();
+        assert_eq!(fft_cost_proxy(1), expected);
+
+        // Every domain doubles with the starting rate
+        assert!(fft_cost_proxy(2) > 2. * fft_cost_proxy(1));
+    }
+
+    #[test]
+    fn test_constraint_batching() {
+        let whir_protocol = |constraint_degrees: &[usize]| {
+            WhirProtocol::new(
+                ldt_parameters().with_constraint_degrees(constraint_degrees),
+                whir_parameters(),
+            )
+            .unwrap()
+        };
+        let constraint_batching_error = |whir_protocol: &WhirProtocol| {
+            whir_protocol
+                .protocol
+                .rbr_breakdown()
+                .into_iter()
+                .flat_map(|round| round.terms)
+                .filter(|(name, _)| name == "constraint_batching_error")
+                .map(|(_, error)| error)
+                .fold(f64::INFINITY, f64::min)
+        };
+
+        // A single constraint needs no combination, while ten of them cost log2(10) bits more than one would
+        let one = whir_protocol(&[2]);
+        let ten = whir_protocol(&[2; 10]);
+        assert_eq!(constraint_batching_error(&one), f64::INFINITY);
+        let ten_error = constraint_batching_error(&ten);
+        assert!(ten_error < constraint_batching_error(&one));
+        let single_error = SecurityAssumption::CapacityBound.constraint_folding_error(
+            20,
+            1,
+            GOLDILOCKS_2.extension_bit_size(),
+            1,
+        );
+        assert!((single_error - ten_error - 10_f64.log2()).abs() < 1e-9);
+
+        // The sumcheck messages are sized by the largest degree
+        let mixed = ldt_parameters().with_constraint_degrees(&[2, 3, 2]);
+        assert_eq!(mixed.constraint_degree, 3);
+        assert_eq!(mixed.num_constraints, 3);
+        assert!(
+            whir_protocol(&[2, 3, 2]).protocol.proof_size_bits()
+                > whir_protocol(&[2, 2, 2]).protocol.proof_size_bits()
+        );
+    }
+
+    #[test]
+    fn test_auto_rounds() {
+        let auto_rounds = WhirParameters::auto_rounds(
+            ldt_parameters(),
+            1,
+            4,
+            SecurityAssumption::CapacityBound,
+            100,
+            20,
+            256,
+        )
+        .unwrap();
+        // A third round costs more than it saves on the final polynomial of degree 2^8
+        assert_eq!(auto_rounds.folding_factors.len(), 2);
+        let proof_size = |whir_parameters| {
+            WhirProtocol::new(ldt_parameters(), whir_parameters)
+                .unwrap()
+                .protocol
+                .proof_size_bits()
+        };
+        let auto_size = proof_size(auto_rounds);
+
+        // Stopping early leaves a larger final polynomial to send, going further adds costlier rounds
+        for num_rounds in 1..=4 {
+            let hand_picked = WhirParameters::fixed_domain_shift(
+                1,
+                4,
+                num_rounds,
+                SecurityAssumption::CapacityBound,
+                100,
+                20,
+                256,
+            );
+            assert!(auto_size <= proof_size(hand_picked));
+        }
+    }
+
+    #[test]
+    fn test_marginal_round_cost() {
+        let with_rounds = |num_rounds| {
+            WhirParameters::fixed_domain_shift(
+                1,
+                4,
+                num_rounds,
+                SecurityAssumption::CapacityBound,
+                100,
+                20,
+                256,
+            )
+        };
+
+        let proof_size = |num_rounds| {
+            WhirProtocol::new(ldt_parameters(), with_rounds(num_rounds))
+                .unwrap()
+                .protocol
+                .proof_size_bits() as i64
+        };
+
+        // With a single round, the final polynomial has degree 2^12 and another round shrinks it considerably
+        let cost = marginal_round_cost(ldt_parameters(), with_rounds(1)).unwrap();
+        assert!(cost < 0);
+        assert_eq!(cost, proof_size(2) - proof_size(1));
+
+        // With three rounds, the final polynomial has degree 2^4 and is cheaper than another commitment
+        let cost = marginal_round_cost(ldt_parameters(), with_rounds(3)).unwrap();
+        assert!(cost > 0);
+        assert_eq!(cost, proof_size(4) - proof_size(3));
+
+        // There is no room for a fifth round
+        assert_eq!(
+            marginal_round_cost(ldt_parameters(), with_rounds(4)),
+            Err(WhirError::OverFolded {
+                total_reduction: 24,
+                log_degree: 20
+            })
+        );
+    }
+
+    #[test]
+    fn test_ood_strategy() {
+        // Over the BabyBear extension, repeating the sample pays for the large first round degree on every sample
+        let ldt_parameters = LowDegreeParameters {
+            field: BABYBEAR_4,
+            log_degree: 30,
+            ..ldt_parameters()
+        };
+        let whir_protocol = |ood_strategy| {
+            WhirProtocol::new(
+                ldt_parameters,
+                WhirParameters {
+                    ood_strategy,
+                    ..whir_parameters()
+                },
+            )
+            .unwrap()
+        };
+        let repeated = whir_protocol(OodStrategy::Repeated);
+        let single_point = whir_protocol(OodStrategy::SinglePoint);
+        assert_eq!(single_point.config.ood_strategy, OodStrategy::SinglePoint);
+
+        let ood_samples = |whir_protocol: &WhirProtocol| {
+            whir_protocol
+                .config
+                .round_parameters
+                .iter()
+                .map(|round| round.ood_samples)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ood_samples(&repeated), vec![3, 3, 3]);
+        assert_eq!(ood_samples(&single_point), vec![2, 2, 2]);
+
+        // Each saved sample is one extension field element less in the proof
+        let saved_bits = 3 * BABYBEAR_4.extension_bit_size();
+        assert_eq!(
+            repeated.protocol.proof_size_bits() - single_point.protocol.proof_size_bits(),
+            saved_bits
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(whir_parameters().validate(&ldt_parameters()), Ok(()));
+        assert_eq!(
+            whir_parameters().validate(&LowDegreeParameters {
+                constraint_degree: 2,
+                num_constraints: 0,
+                ..ldt_parameters()
+            }),
+            Err(vec![WhirError::NoConstraints {
+                constraint_degree: 2
+            }])
+        );
+
+        let whir_parameters = WhirParameters {
+            starting_folding_factor: 0,
+            log_inv_rates: vec![1],
+            ..whir_parameters()
+        };
+        assert_eq!(
+            whir_parameters.validate(&ldt_parameters()),
+            Err(vec![
+                WhirError::ZeroFoldingFactor,
+                WhirError::MismatchedRoundVectors
+            ])
+        );
+
+        // Construction fails on the first problem
+        assert_eq!(
+            WhirProtocol::new(ldt_parameters(), whir_parameters).unwrap_err(),
+            WhirError::ZeroFoldingFactor
+        );
+    }
+
+    #[test]
+    fn test_digest_collision_bits() {
+        let security_summary = |digest, security_level| {
+            let whir_parameters = WhirParameters {
+                digest: Some(digest),
+                ..WhirParameters::fixed_domain_shift(
+                    1,
+                    4,
+                    3,
+                    SecurityAssumption::CapacityBound,
+                    security_level,
+                    20,
+                    256,
+                )
+            };
+            WhirProtocol::new(ldt_parameters(), whir_parameters)
+                .unwrap()
+                .protocol
+                .security_summary(80)
+        };
+
+        // Both output 256 bits, but Poseidon2 only gives 100 bits of collision resistance
+        let sha3 = security_summary(DigestConfig::Sha3_256, 128);
+        let poseidon = security_summary(DigestConfig::Poseidon2Goldilocks, 128);
+        assert_eq!(sha3.digest_size_bits, 256);
+        assert_eq!(poseidon.digest_size_bits, 256);
+        assert_eq!(sha3.collision_bits, 128);
+        assert_eq!(poseidon.collision_bits, 100);
+
+        assert_eq!(sha3.classical_bound, SecurityBound::Soundness);
+        assert_eq!(poseidon.classical_bound, SecurityBound::Digest);
+        let expected = 200. - (3_f64.log2() + 160.);
+        assert!((poseidon.classical_security - expected).abs() < 1e-9);
+
+        // Below 128 bits, the hashes still output 256 bits
+        let sha3 = security_summary(DigestConfig::Sha3_256, 100);
+        assert_eq!(sha3.digest_size_bits, 256);
+        assert_eq!(sha3.collision_bits, 128);
+    }
+
+    #[test]
+    fn test_rate_schedule() {
+        let (log_inv_rate, folding_factor, num_rounds) = (1, 4, 3);
+        let whir_parameters = WhirParameters::fixed_domain_shift(
+            log_inv_rate,
+            folding_factor,
+            num_rounds,
+            SecurityAssumption::CapacityBound,
+            100,
+            20,
+            256,
+        );
+        let whir_protocol = WhirProtocol::new(ldt_parameters(), whir_parameters).unwrap();
+
+        let rates = whir_protocol.config.rate_schedule();
+        assert_eq!(rates.len(), num_rounds + 1);
+        assert_eq!(rates[0], log_inv_rate);
+        assert!(rates.windows(2).all(|w| w[0] < w[1]));
+        for (i, &rate) in rates[1..].iter().enumerate() {
+            assert_eq!(rate, log_inv_rate + (i + 1) * (folding_factor - 1));
+        }
+
+        // The degree drops by 2^4 in each round, while the domain only halves
+        let degrees = whir_protocol.config.degree_schedule();
+        assert_eq!(degrees, vec![20, 16, 12, 8]);
+        let domains: Vec<_> = degrees.iter().zip(&rates).map(|(d, r)| d + r).collect();
+        assert_eq!(domains, vec![21, 20, 19, 18]);
+    }
+
+    #[test]
+    fn test_to_parameters() {
+        let ldt_parameters = LowDegreeParameters {
+            batch_size: 2,
+            ..ldt_parameters()
+        };
+        let round_trip = |whir_parameters: WhirParameters| {
+            let config = WhirProtocol::new(ldt_parameters, whir_parameters)
+                .unwrap()
+                .config;
+            let rebuilt = WhirProtocol::new(config.ldt_parameters, config.to_parameters())
+                .unwrap()
+                .config;
+            assert_eq!(rebuilt, config);
+        };
+
+        round_trip(whir_parameters());
+        round_trip(WhirParameters {
+            security_assumptions: vec![
+                SecurityAssumption::JohnsonBound,
+                SecurityAssumption::CapacityBound,
+                SecurityAssumption::UniqueDecoding,
+            ],
+            eta_factor: Some(4.),
+            max_list_size_bits: Some(20.),
+            folding_pow_bits: Some(10),
+            digest: Some(DigestConfig::Sha3_256),
+            no_grinding: true,
+            zero_knowledge: true,
+            base_field_statement: true,
+            encoding: Encoding::ByteAligned,
+            interleaving: InterleavingMode::Separate,
+            final_phase: FinalPhase::Sumcheck,
+            ood_strategy: OodStrategy::SinglePoint,
+            ..whir_parameters()
+        });
+    }
+
+    #[test]
+    fn test_grinding_cap() {
+        let whir_protocol = |grinding_cap| {
+            WhirProtocol::new(
+                ldt_parameters(),
+                WhirParameters {
+                    grinding_cap,
+                    ..whir_parameters()
+                },
+            )
+            .unwrap()
+        };
+        let uncapped = whir_protocol(None);
+        assert_eq!(uncapped.config.security_shortfall, 0.);
+
+        // A cap above the grinding used changes nothing
+        let loose = whir_protocol(Some(64));
+        assert_eq!(loose.protocol.rbr_errors(), uncapped.protocol.rbr_errors());
+        assert_eq!(loose.config.security_shortfall, 0.);
+
+        // The queries are still configured for up to 20 bits of grinding, of which only 10 are done
+        let capped = whir_protocol(Some(10));
+        let num_queries = |whir_protocol: &WhirProtocol| {
+            whir_protocol
+                .config
+                .round_parameters
+                .iter()
+                .map(|round| round.num_queries)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(num_queries(&capped), num_queries(&uncapped));
+        assert!(capped
+            .config
+            .round_parameters
+            .iter()
+            .all(|round| round.query_pow_bits <= 10.));
+
+        let security_shortfall = capped.config.security_shortfall;
+        assert!(security_shortfall > 0. && security_shortfall <= 10.);
+        assert_eq!(security_shortfall, 100. - capped.protocol.rbr_error());
+        for log_ro_queries in [0, 64] {
+            let reduction = uncapped
+                .protocol
+                .compiled_classical_security(log_ro_queries)
+                - capped.protocol.compiled_classical_security(log_ro_queries);
+            assert!((reduction - security_shortfall).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_final_phase() {
+        // A single round of folding by 2^4 leaves a final polynomial of degree 2^12
+        let whir_protocol = |constraint_degree, final_phase| {
+            WhirProtocol::new(
+                LowDegreeParameters {
+                    constraint_degree,
+                    ..ldt_parameters()
+                },
+                WhirParameters {
+                    final_phase,
+                    ..WhirParameters::fixed_domain_shift(
+                        1,
+                        4,
+                        1,
+                        SecurityAssumption::CapacityBound,
+                        100,
+                        20,
+                        256,
+                    )
+                },
+            )
+            .unwrap()
+        };
+        let direct = whir_protocol(2, FinalPhase::DirectSend);
+        let sumcheck = whir_protocol(2, FinalPhase::Sumcheck);
+        assert_eq!(direct.config.final_poly_log_degree, 12);
+        assert_eq!(sumcheck.config.final_poly_log_degree, 12);
+        assert!(direct
+            .protocol
+            .prover_messages()
+            .any(|message| message.is_final_polynomial()));
+        assert!(!sumcheck
+            .protocol
+            .prover_messages()
+            .any(|message| message.is_final_polynomial()));
+
+        // The 2^12 coefficients are replaced by 12 quadratics and the final evaluation
+        let saved_bits = (1 << 12) * 128 - (3 * 12 + 1) * 128;
+        assert_eq!(direct.config.final_poly_size_bits(), (1 << 12) * 128);
+        assert_eq!(sumcheck.config.final_poly_size_bits(), (3 * 12 + 1) * 128);
+        assert_eq!(
+            direct.protocol.proof_size_bits() - sumcheck.protocol.proof_size_bits(),
+            saved_bits
+        );
+        assert!(2 * sumcheck.protocol.proof_size_bits() < direct.protocol.proof_size_bits());
+
+        // The sumcheck errors are negligible, so the soundness is unchanged
+        assert!(sumcheck
+            .protocol
+            .error_term_names()
+            .contains("final_sumcheck_error"));
+        assert_eq!(direct.protocol.rbr_error(), sumcheck.protocol.rbr_error());
+
+        // Like the folding sumchecks, a higher constraint degree means larger sumcheck polynomials and a larger error
+        let cubic_sumcheck = whir_protocol(3, FinalPhase::Sumcheck);
+        assert_eq!(
+            cubic_sumcheck.config.final_poly_size_bits(),
+            (4 * 12 + 1) * 128
+        );
+        let final_sumcheck_error = |whir_protocol: &WhirProtocol| {
+            whir_protocol
+                .protocol
+                .rbr_breakdown()
+                .iter()
+                .flat_map(|message| message.terms.clone())
+                .filter(|(name, _)| name == "final_sumcheck_error")
+                .map(|(_, error)| error)
+                .fold(f64::INFINITY, f64::min)
+        };
+        let degree_loss = 3f64.log2() - 2f64.log2();
+        assert!(
+            (final_sumcheck_error(&sumcheck) - final_sumcheck_error(&cubic_sumcheck) - degree_loss)
+                .abs()
+                < 1e-9
+        );
+    }
+
+    #[test]
+    fn test_first_sumcheck_field() {
+        let num_base_messages = |ldt_parameters| {
+            let whir_parameters = WhirParameters {
+                base_field_statement: true,
+                ..whir_parameters()
+            };
+            WhirProtocol::new(ldt_parameters, whir_parameters)
+                .unwrap()
+                .protocol
+                .prover_messages()
+                .filter(|message| {
+                    matches!(message.element(), ProofElement::FieldElements(elements) if !elements.is_extension)
+                })
+                .count()
+        };
+
+        // By default the statement is over the extension, and so is every sumcheck polynomial
+        let default_protocol = WhirProtocol::new(ldt_parameters(), whir_parameters()).unwrap();
+        assert!(default_protocol.protocol.prover_messages().all(|message| {
+            !matches!(message.element(), ProofElement::FieldElements(elements) if !elements.is_extension)
+        }));
+
+        // For a base field statement, only the first sumcheck polynomial, sent before any verifier randomness,
+        // is over the base field
+        assert_eq!(num_base_messages(ldt_parameters()), 1);
+        assert_eq!(
+            num_base_messages(LowDegreeParameters {
+                batch_size: 2,
+                ..ldt_parameters()
+            }),
+            0
+        );
+        assert_eq!(
+            num_base_messages(LowDegreeParameters {
+                num_constraints: 2,
+                ..ldt_parameters()
+            }),
+            0
+        );
+    }
+
+    #[test]
+    fn test_max_list_size_bits() {
+        let ldt_parameters = LowDegreeParameters {
+            field: BABYBEAR_5,
+            ..ldt_parameters()
+        };
+        let ood_samples = |max_list_size_bits| {
+            let whir_protocol = WhirProtocol::new(
+                ldt_parameters,
+                WhirParameters {
+                    max_list_size_bits,
+                    ..whir_parameters()
+                },
+            )
+            .unwrap();
+            assert_eq!(whir_protocol.config.max_list_size_bits, max_list_size_bits);
+            whir_protocol
+                .config
+                .round_parameters
+                .iter()
+                .map(|round| round.ood_samples)
+                .sum::<usize>()
+        };
+
+        // A smaller list is pinned down by fewer OOD samples
+        assert_eq!(ood_samples(None), 6);
+        assert_eq!(ood_samples(Some(5.)), 3);
     }
 }
@@ -0,0 +1,296 @@
+use stir_whir_estimation::{
+    basefold::{BasefoldParameters, BasefoldProtocol},
+    errors::{ParameterError, SecurityAssumption},
+    field::GOLDILOCKS_2,
+    fri::{FriParameters, FriProtocol},
+    ligero::{LigeroParameters, LigeroProtocol},
+    protocol::Report,
+    stir::{StirParameters, StirProtocol},
+    whir::{WhirParameters, WhirProtocol},
+    LowDegreeParameters, LowDegreeTest,
+};
+
+const LDT_PARAMETERS: LowDegreeParameters = LowDegreeParameters::new(GOLDILOCKS_2, 20, 1, 0);
+
+const PCS_PARAMETERS: LowDegreeParameters = LowDegreeParameters::new(GOLDILOCKS_2, 20, 1, 2);
+
+#[test]
+fn stir_smoke() {
+    let stir_parameters = StirParameters::fixed_domain_shift(
+        1,
+        4,
+        3,
+        SecurityAssumption::CapacityBound,
+        100,
+        20,
+        256,
+    );
+    let stir_protocol = StirProtocol::new(LDT_PARAMETERS, stir_parameters).unwrap();
+    assert!(stir_protocol.protocol.proof_size_bits() > 0);
+}
+
+#[test]
+fn fri_smoke() {
+    let fri_parameters =
+        FriParameters::fixed_folding(1, 4, 3, SecurityAssumption::CapacityBound, 100, 20, 256);
+    let fri_protocol = FriProtocol::new(LDT_PARAMETERS, fri_parameters).unwrap();
+    assert!(fri_protocol.protocol.proof_size_bits() > 0);
+}
+
+#[test]
+fn basefold_smoke() {
+    let basefold_parameters =
+        BasefoldParameters::fixed_folding(1, 4, 3, SecurityAssumption::CapacityBound, 100, 20, 256);
+    let basefold_protocol = BasefoldProtocol::new(PCS_PARAMETERS, basefold_parameters).unwrap();
+    assert!(basefold_protocol.protocol.proof_size_bits() > 0);
+}
+
+#[test]
+fn whir_smoke() {
+    let whir_parameters = WhirParameters::fixed_domain_shift(
+        1,
+        4,
+        3,
+        SecurityAssumption::CapacityBound,
+        100,
+        20,
+        256,
+    );
+    let whir_protocol = WhirProtocol::new(PCS_PARAMETERS, whir_parameters).unwrap();
+    assert!(whir_protocol.protocol.proof_size_bits() > 0);
+}
+
+#[test]
+fn constraint_degree_without_constraints() {
+    let no_constraints = LowDegreeParameters {
+        num_constraints: 0,
+        ..PCS_PARAMETERS
+    };
+    let no_constraints_error = ParameterError::NoConstraints {
+        constraint_degree: 2,
+    };
+
+    let basefold_parameters =
+        BasefoldParameters::fixed_folding(1, 4, 3, SecurityAssumption::CapacityBound, 100, 20, 256);
+    assert_eq!(
+        basefold_parameters.validate(&no_constraints),
+        Err(vec![no_constraints_error])
+    );
+
+    let ligero_parameters =
+        LigeroParameters::square(20, 2, SecurityAssumption::CapacityBound, 100, 20, 256);
+    assert_eq!(
+        LigeroProtocol::new(no_constraints, ligero_parameters).err(),
+        Some(no_constraints_error)
+    );
+
+    // Without a constraint degree, the number of constraints is irrelevant
+    let no_constraints = LowDegreeParameters {
+        num_constraints: 0,
+        ..LDT_PARAMETERS
+    };
+    assert_eq!(no_constraints.validate(), Ok(()));
+}
+
+#[test]
+fn reports_match_protocols() {
+    let fri_protocol = FriProtocol::new(
+        LDT_PARAMETERS,
+        FriParameters::fixed_folding(1, 4, 3, SecurityAssumption::CapacityBound, 100, 20, 256),
+    )
+    .unwrap();
+    let report = fri_protocol.report();
+    assert_eq!(
+        report.proof_size_bits,
+        fri_protocol.protocol.proof_size_bits()
+    );
+    assert_eq!(report.num_queries, fri_protocol.config.queries);
+    assert_eq!(report.num_rounds, 3);
+    assert_eq!(report.final_poly_log_degree, 4);
+    assert_eq!(report.rbr_error, fri_protocol.protocol.rbr_error());
+    assert_eq!(
+        report.compiled_classical_100,
+        fri_protocol.protocol.compiled_classical_security(100)
+    );
+
+    let stir_protocol = StirProtocol::new(
+        LDT_PARAMETERS,
+        StirParameters::fixed_domain_shift(
+            1,
+            4,
+            3,
+            SecurityAssumption::CapacityBound,
+            100,
+            20,
+            256,
+        ),
+    )
+    .unwrap();
+    let basefold_protocol = BasefoldProtocol::new(
+        PCS_PARAMETERS,
+        BasefoldParameters::fixed_folding(1, 4, 3, SecurityAssumption::CapacityBound, 100, 20, 256),
+    )
+    .unwrap();
+    let whir_protocol = WhirProtocol::new(
+        PCS_PARAMETERS,
+        WhirParameters::fixed_domain_shift(
+            1,
+            4,
+            3,
+            SecurityAssumption::CapacityBound,
+            100,
+            20,
+            256,
+        ),
+    )
+    .unwrap();
+    assert_eq!(
+        stir_protocol.report().proof_size_bits,
+        stir_protocol.protocol.proof_size_bits()
+    );
+    assert_eq!(
+        basefold_protocol.report().proof_size_bits,
+        basefold_protocol.protocol.proof_size_bits()
+    );
+    assert_eq!(
+        whir_protocol.report().proof_size_bits,
+        whir_protocol.protocol.proof_size_bits()
+    );
+}
+
+#[test]
+fn low_degree_tests_as_trait_objects() {
+    let ldts: Vec<(&str, Box<dyn LowDegreeTest>)> = vec![
+        (
+            "FRI",
+            Box::new(
+                FriProtocol::new(
+                    LDT_PARAMETERS,
+                    FriParameters::fixed_folding(
+                        1,
+                        4,
+                        3,
+                        SecurityAssumption::CapacityBound,
+                        100,
+                        20,
+                        256,
+                    ),
+                )
+                .unwrap(),
+            ),
+        ),
+        (
+            "STIR",
+            Box::new(
+                StirProtocol::new(
+                    LDT_PARAMETERS,
+                    StirParameters::fixed_domain_shift(
+                        1,
+                        4,
+                        3,
+                        SecurityAssumption::CapacityBound,
+                        100,
+                        20,
+                        256,
+                    ),
+                )
+                .unwrap(),
+            ),
+        ),
+        (
+            "WHIR",
+            Box::new(
+                WhirProtocol::new(
+                    PCS_PARAMETERS,
+                    WhirParameters::fixed_domain_shift(
+                        1,
+                        4,
+                        3,
+                        SecurityAssumption::CapacityBound,
+                        100,
+                        20,
+                        256,
+                    ),
+                )
+                .unwrap(),
+            ),
+        ),
+        (
+            "Basefold",
+            Box::new(
+                BasefoldProtocol::new(
+                    PCS_PARAMETERS,
+                    BasefoldParameters::fixed_folding(
+                        1,
+                        4,
+                        3,
+                        SecurityAssumption::CapacityBound,
+                        100,
+                        20,
+                        256,
+                    ),
+                )
+                .unwrap(),
+            ),
+        ),
+    ];
+
+    for (name, ldt) in &ldts {
+        assert_eq!(ldt.proof_size_bits(), ldt.protocol().proof_size_bits());
+        assert_eq!(ldt.proof_size_bits(), ldt.report().proof_size_bits);
+        assert!(ldt.compiled_classical_security(0) >= 100., "{name}");
+    }
+}
+
+#[test]
+fn proof_size_import_paths() {
+    use stir_whir_estimation::{proof_size, protocol};
+
+    let leaf = proof_size::FieldElements::extension(GOLDILOCKS_2, 16);
+    let queries = proof_size::MerkleQueries {
+        merkle_tree: protocol::proof_size::MerkleTree::new(20, GOLDILOCKS_2, 16, true),
+        num_openings: 50,
+        shared_leaves: 1,
+    };
+
+    assert_eq!(
+        leaf.size_bits(),
+        protocol::proof_size::FieldElements::extension(GOLDILOCKS_2, 16).size_bits()
+    );
+    assert_eq!(
+        proof_size::ProofElement::MerkleQueries(queries).size_bits(),
+        protocol::proof_size::ProofElement::MerkleQueries(queries).size_bits()
+    );
+}
+
+#[test]
+fn error_term_names() {
+    let stir_protocol = StirProtocol::new(
+        LDT_PARAMETERS,
+        StirParameters::fixed_domain_shift(
+            1,
+            4,
+            3,
+            SecurityAssumption::CapacityBound,
+            100,
+            20,
+            256,
+        ),
+    )
+    .unwrap();
+    let fri_protocol = FriProtocol::new(
+        LDT_PARAMETERS,
+        FriParameters::fixed_folding(1, 4, 3, SecurityAssumption::CapacityBound, 100, 20, 256),
+    )
+    .unwrap();
+
+    let stir_names = stir_protocol.protocol.error_term_names();
+    let fri_names = fri_protocol.protocol.error_term_names();
+    for name in ["prox_gaps_error_1", "prox_gaps_error_2"] {
+        assert!(stir_names.contains(name));
+        assert!(!fri_names.contains(name));
+    }
+    for name in ["folding_error", "query_error"] {
+        assert!(fri_names.contains(name));
+    }
+}